@@ -0,0 +1,449 @@
+//! Hardware purchase advisor.
+//!
+//! Answers "what is the cheapest (or smallest) box that runs this set of
+//! models?" by enumerating GPU configurations from a catalog, synthesizing
+//! `SystemSpecs` for each one and checking every model with `ModelFit::analyze`.
+
+use crate::fit::{FitLevel, ModelFit};
+use crate::hardware::{GpuBackend, GpuInfo, SystemSpecs};
+use crate::models::LlmModel;
+
+/// A purchasable GPU model.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GpuCatalogEntry {
+    pub name: String,
+    /// Per-card VRAM in GB.
+    pub vram_gb: f64,
+    /// Memory bandwidth in GB/s.
+    pub bandwidth_gb_s: f64,
+    pub backend: GpuBackend,
+    /// Approximate street/launch price in USD, if known.
+    #[serde(default)]
+    pub price_usd: Option<f64>,
+}
+
+/// Built-in GPU catalog. VRAM figures match `estimate_vram_from_name` so the
+/// advisor and the detection fallback agree on what a card provides.
+/// Prices are launch MSRPs and only meant for relative ranking; data center
+/// parts have no list price and sort after priced configurations.
+pub fn default_gpu_catalog() -> Vec<GpuCatalogEntry> {
+    let cuda = GpuBackend::Cuda;
+    let rocm = GpuBackend::Rocm;
+    let table: &[(&str, f64, f64, GpuBackend, Option<f64>)] = &[
+        // NVIDIA RTX 50 series
+        ("GeForce RTX 5090", 32.0, 1792.0, cuda, Some(1999.0)),
+        ("GeForce RTX 5080", 16.0, 960.0, cuda, Some(999.0)),
+        ("GeForce RTX 5070 Ti", 16.0, 896.0, cuda, Some(749.0)),
+        ("GeForce RTX 5070", 12.0, 672.0, cuda, Some(549.0)),
+        ("GeForce RTX 5060 Ti", 16.0, 448.0, cuda, Some(429.0)),
+        ("GeForce RTX 5060", 8.0, 448.0, cuda, Some(299.0)),
+        // NVIDIA RTX 40 series
+        ("GeForce RTX 4090", 24.0, 1008.0, cuda, Some(1599.0)),
+        ("GeForce RTX 4080", 16.0, 717.0, cuda, Some(1199.0)),
+        ("GeForce RTX 4070 Ti", 12.0, 504.0, cuda, Some(799.0)),
+        ("GeForce RTX 4070", 12.0, 504.0, cuda, Some(599.0)),
+        ("GeForce RTX 4060 Ti", 16.0, 288.0, cuda, Some(499.0)),
+        ("GeForce RTX 4060", 8.0, 272.0, cuda, Some(299.0)),
+        // NVIDIA RTX 30 series
+        ("GeForce RTX 3090", 24.0, 936.0, cuda, Some(1499.0)),
+        ("GeForce RTX 3080 Ti", 12.0, 912.0, cuda, Some(1199.0)),
+        ("GeForce RTX 3080", 10.0, 760.0, cuda, Some(699.0)),
+        ("GeForce RTX 3070", 8.0, 448.0, cuda, Some(499.0)),
+        ("GeForce RTX 3060 Ti", 8.0, 448.0, cuda, Some(399.0)),
+        ("GeForce RTX 3060", 12.0, 360.0, cuda, Some(329.0)),
+        // Data center
+        ("NVIDIA H100", 80.0, 2000.0, cuda, None),
+        ("NVIDIA A100", 80.0, 1935.0, cuda, None),
+        ("NVIDIA L40", 48.0, 864.0, cuda, None),
+        ("NVIDIA A10", 24.0, 600.0, cuda, None),
+        ("NVIDIA T4", 16.0, 320.0, cuda, None),
+        // AMD RX 9000 series
+        ("Radeon RX 9070 XT", 16.0, 644.0, rocm, Some(599.0)),
+        ("Radeon RX 9070", 12.0, 644.0, rocm, Some(549.0)),
+        // AMD RX 7000 series
+        ("Radeon RX 7900 XTX", 24.0, 960.0, rocm, Some(999.0)),
+        ("Radeon RX 7900 XT", 20.0, 800.0, rocm, Some(899.0)),
+        ("Radeon RX 7800 XT", 16.0, 624.0, rocm, Some(499.0)),
+        ("Radeon RX 7700 XT", 12.0, 432.0, rocm, Some(449.0)),
+        ("Radeon RX 7600", 8.0, 288.0, rocm, Some(269.0)),
+        // AMD RX 6000 series
+        ("Radeon RX 6950 XT", 16.0, 576.0, rocm, Some(1099.0)),
+        ("Radeon RX 6800 XT", 16.0, 512.0, rocm, Some(649.0)),
+        ("Radeon RX 6700 XT", 12.0, 384.0, rocm, Some(479.0)),
+        ("Radeon RX 6600 XT", 8.0, 256.0, rocm, Some(379.0)),
+    ];
+
+    table
+        .iter()
+        .map(
+            |&(name, vram_gb, bandwidth_gb_s, backend, price_usd)| GpuCatalogEntry {
+                name: name.to_string(),
+                vram_gb,
+                bandwidth_gb_s,
+                backend,
+                price_usd,
+            },
+        )
+        .collect()
+}
+
+/// Load a user-supplied catalog (JSON array of `GpuCatalogEntry`).
+pub fn load_gpu_catalog(path: &std::path::Path) -> Result<Vec<GpuCatalogEntry>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    serde_json::from_str(&text).map_err(|e| format!("invalid catalog {}: {e}", path.display()))
+}
+
+/// What "best" means when ranking configurations that satisfy the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvisorObjective {
+    /// Lowest total GPU price; configurations without a price sort last.
+    Cheapest,
+    /// Least total VRAM, then lowest price.
+    Smallest,
+}
+
+/// Inputs for a purchase search.
+pub struct AdvisorRequest<'a> {
+    pub models: Vec<&'a LlmModel>,
+    /// Context length every model must support. `None` keeps each model's default.
+    pub target_context: Option<u32>,
+    /// Every model must reach at least this fit level.
+    pub min_fit: FitLevel,
+    /// Optional speed floor (estimated tok/s) every model must reach.
+    /// Without it, cheap cards with lots of RAM win via CPU offload.
+    pub min_tps: Option<f64>,
+    /// Largest number of identical cards to consider per configuration.
+    pub max_gpus: u32,
+    /// Candidate system RAM sizes in GB.
+    pub ram_options_gb: Vec<f64>,
+    /// CPU cores the machine has, for offload and CPU-only estimates.
+    /// Defaults to this host's.
+    pub cpu_cores: usize,
+    pub objective: AdvisorObjective,
+}
+
+impl<'a> AdvisorRequest<'a> {
+    pub fn new(models: Vec<&'a LlmModel>) -> Self {
+        AdvisorRequest {
+            models,
+            target_context: None,
+            min_fit: FitLevel::Good,
+            min_tps: None,
+            max_gpus: 4,
+            ram_options_gb: vec![16.0, 32.0, 64.0, 128.0, 256.0],
+            cpu_cores: std::thread::available_parallelism().map_or(8, |n| n.get()),
+            objective: AdvisorObjective::Cheapest,
+        }
+    }
+}
+
+/// A candidate machine: N identical GPUs plus a system RAM size.
+#[derive(Debug, Clone)]
+pub struct HardwareConfig {
+    pub gpu: GpuCatalogEntry,
+    pub gpu_count: u32,
+    pub ram_gb: f64,
+}
+
+impl HardwareConfig {
+    pub fn total_vram_gb(&self) -> f64 {
+        self.gpu.vram_gb * self.gpu_count as f64
+    }
+
+    pub fn total_price_usd(&self) -> Option<f64> {
+        self.gpu.price_usd.map(|p| p * self.gpu_count as f64)
+    }
+
+    /// Synthesize the specs this machine would report: per-card VRAM and
+    /// a card count, as detection reports them, so `ModelFit::analyze`
+    /// pools multi-GPU configs the same way. Only ~85% of RAM is assumed
+    /// free for models.
+    pub fn to_specs(&self, cpu_cores: usize) -> SystemSpecs {
+        SystemSpecs {
+            total_ram_gb: self.ram_gb,
+            available_ram_gb: self.ram_gb * 0.85,
+            total_cpu_cores: cpu_cores,
+            cpu_name: "Advisor CPU".to_string(),
+            has_gpu: true,
+            gpu_vram_gb: Some(self.gpu.vram_gb),
+            gpu_name: Some(self.gpu.name.clone()),
+            gpu_count: self.gpu_count,
+            unified_memory: false,
+            backend: self.gpu.backend,
+            gpus: vec![GpuInfo {
+                name: self.gpu.name.clone(),
+                vram_gb: Some(self.gpu.vram_gb),
+                backend: self.gpu.backend,
+                count: self.gpu_count,
+                unified_memory: false,
+                vram_used_gb: None,
+                provenance: Vec::new(),
+                pcie_link: None,
+                memory_bandwidth_gb_s: Some(self.gpu.bandwidth_gb_s),
+            }],
+            cgroup_limits: None,
+            storage: None,
+            cpu_features: None,
+            memory_topology: None,
+            detection: Default::default(),
+            board: None,
+            npus: Vec::new(),
+            power: None,
+        }
+    }
+}
+
+/// A configuration that satisfies the request, with the per-model analysis.
+pub struct AdvisorResult {
+    pub config: HardwareConfig,
+    pub fits: Vec<ModelFit>,
+}
+
+impl AdvisorResult {
+    /// The weakest fit level across all requested models.
+    pub fn worst_fit(&self) -> FitLevel {
+        self.fits
+            .iter()
+            .map(|f| f.fit_level)
+            .max_by_key(|level| level.rank())
+            .unwrap_or(FitLevel::Perfect)
+    }
+}
+
+/// Enumerate catalog configurations and return those where every model
+/// reaches `min_fit`, best first according to the request objective.
+pub fn advise(request: &AdvisorRequest, catalog: &[GpuCatalogEntry]) -> Vec<AdvisorResult> {
+    let models: Vec<LlmModel> = request
+        .models
+        .iter()
+        .map(|m| match request.target_context {
+            Some(ctx) => at_context(m, ctx),
+            None => (*m).clone(),
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    for gpu in catalog {
+        for gpu_count in 1..=request.max_gpus.max(1) {
+            // Smallest RAM that works for this GPU setup wins; larger sizes
+            // would only add cost.
+            for &ram_gb in &request.ram_options_gb {
+                let config = HardwareConfig {
+                    gpu: gpu.clone(),
+                    gpu_count,
+                    ram_gb,
+                };
+                let specs = config.to_specs(request.cpu_cores);
+                let fits: Vec<ModelFit> = models
+                    .iter()
+                    .map(|m| ModelFit::analyze(m, &specs))
+                    .collect();
+                let satisfied = fits.iter().all(|f| {
+                    f.fit_level.rank() <= request.min_fit.rank()
+                        && request.min_tps.is_none_or(|tps| f.estimated_tps >= tps)
+                });
+                if satisfied {
+                    results.push(AdvisorResult { config, fits });
+                    break;
+                }
+            }
+        }
+    }
+
+    results.sort_by(|a, b| compare_configs(&a.config, &b.config, request.objective));
+    results
+}
+
+fn compare_configs(
+    a: &HardwareConfig,
+    b: &HardwareConfig,
+    objective: AdvisorObjective,
+) -> std::cmp::Ordering {
+    let by_price = || match (a.total_price_usd(), b.total_price_usd()) {
+        (Some(pa), Some(pb)) => pa.partial_cmp(&pb).unwrap_or(std::cmp::Ordering::Equal),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    };
+    let by_vram = || {
+        a.total_vram_gb()
+            .partial_cmp(&b.total_vram_gb())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    };
+    let by_ram = || {
+        a.ram_gb
+            .partial_cmp(&b.ram_gb)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    };
+
+    match objective {
+        AdvisorObjective::Cheapest => by_price()
+            .then_with(by_vram)
+            .then_with(by_ram)
+            .then(a.gpu_count.cmp(&b.gpu_count)),
+        AdvisorObjective::Smallest => by_vram()
+            .then_with(by_price)
+            .then_with(by_ram)
+            .then(a.gpu_count.cmp(&b.gpu_count)),
+    }
+}
+
+/// Clone a model re-targeted to a context length, adding the KV cache for
+/// that context to its memory figures (the database figures exclude it).
+fn at_context(model: &LlmModel, ctx: u32) -> LlmModel {
+    let mut m = model.clone();
+    let kv_cache_gb = model.kv_cache_gb(ctx);
+    m.min_ram_gb += kv_cache_gb;
+    m.recommended_ram_gb += kv_cache_gb;
+    m.min_vram_gb = m.min_vram_gb.map(|v| v + kv_cache_gb);
+    m.context_length = ctx;
+    m
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::estimate_vram_from_name;
+
+    fn test_model(name: &str, params_b: f64, min_vram: f64) -> LlmModel {
+        LlmModel {
+            name: name.to_string(),
+            provider: "Test".to_string(),
+            parameter_count: format!("{}B", params_b),
+            parameters_raw: Some((params_b * 1_000_000_000.0) as u64),
+            min_ram_gb: min_vram * 1.1,
+            recommended_ram_gb: min_vram * 1.6,
+            min_vram_gb: Some(min_vram),
+            quantization: "Q4_K_M".to_string(),
+            context_length: 8192,
+            use_case: "General".to_string(),
+            is_moe: false,
+            num_experts: None,
+            active_experts: None,
+            active_parameters: None,
+        }
+    }
+
+    #[test]
+    fn test_catalog_matches_name_estimates() {
+        for entry in default_gpu_catalog() {
+            assert_eq!(
+                entry.vram_gb,
+                estimate_vram_from_name(&entry.name),
+                "catalog VRAM disagrees with estimate for {}",
+                entry.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_advise_picks_cheapest_single_card() {
+        let model = test_model("Small", 7.0, 5.0);
+        let request = AdvisorRequest::new(vec![&model]);
+        let results = advise(&request, &default_gpu_catalog());
+
+        let best = &results[0].config;
+        assert_eq!(best.gpu_count, 1);
+        assert_eq!(best.total_price_usd(), Some(269.0));
+        assert!(
+            results[0]
+                .fits
+                .iter()
+                .all(|f| f.fit_level != FitLevel::TooTight)
+        );
+    }
+
+    #[test]
+    fn test_advise_uses_multi_gpu_for_large_models() {
+        let model = test_model("Large", 70.0, 40.0);
+        let catalog: Vec<GpuCatalogEntry> = default_gpu_catalog()
+            .into_iter()
+            .filter(|g| g.name.contains("4090"))
+            .collect();
+        let mut request = AdvisorRequest::new(vec![&model]);
+        // Too little RAM for CPU offload, so the model has to live in VRAM.
+        request.ram_options_gb = vec![32.0];
+        let results = advise(&request, &catalog);
+
+        assert!(!results.is_empty());
+        assert_eq!(results[0].config.gpu_count, 2);
+
+        // The specs read like detection's, per card, and `analyze` on them
+        // agrees with the advisor: layers split across both cards.
+        let specs = results[0].config.to_specs(16);
+        assert_eq!(specs.gpu_vram_gb, Some(24.0));
+        let fit = ModelFit::analyze(&model, &specs);
+        assert_eq!(fit.run_mode, crate::fit::RunMode::Gpu);
+        assert!(
+            fit.notes
+                .iter()
+                .any(|n| n.starts_with("Layers split across 2 GPUs"))
+        );
+    }
+
+    #[test]
+    fn test_advise_target_context_raises_requirements() {
+        let model = test_model("Mid", 14.0, 9.0);
+        let mut request = AdvisorRequest::new(vec![&model]);
+        request.min_fit = FitLevel::Perfect;
+        let default_ctx = advise(&request, &default_gpu_catalog());
+        request.target_context = Some(131072);
+        let long_ctx = advise(&request, &default_gpu_catalog());
+
+        assert!(long_ctx[0].config.total_vram_gb() > default_ctx[0].config.total_vram_gb());
+    }
+
+    #[test]
+    fn test_advise_min_tps_rules_out_cpu_offload() {
+        let model = test_model("Large", 70.0, 40.0);
+        let mut request = AdvisorRequest::new(vec![&model]);
+        let offload_ok = advise(&request, &default_gpu_catalog());
+        request.min_tps = Some(3.0);
+        let fast_only = advise(&request, &default_gpu_catalog());
+
+        assert!(offload_ok[0].config.total_vram_gb() < 40.0);
+        assert!(fast_only[0].config.total_vram_gb() >= 40.0);
+        assert!(fast_only[0].fits.iter().all(|f| f.estimated_tps >= 3.0));
+    }
+
+    #[test]
+    fn test_advise_min_tps_uses_card_bandwidth() {
+        let model = test_model("Mid", 14.0, 9.0);
+        let mut catalog: Vec<GpuCatalogEntry> = default_gpu_catalog()
+            .into_iter()
+            .filter(|g| g.name.contains("4090"))
+            .collect();
+        catalog.push(GpuCatalogEntry {
+            name: "Budget 16GB".to_string(),
+            vram_gb: 16.0,
+            bandwidth_gb_s: 128.0,
+            backend: GpuBackend::Cuda,
+            price_usd: Some(199.0),
+        });
+        let mut request = AdvisorRequest::new(vec![&model]);
+        request.max_gpus = 1;
+        let any = advise(&request, &catalog);
+        assert_eq!(any[0].config.gpu.name, "Budget 16GB");
+
+        // Both hold the model; the budget card's bandwidth caps its speed.
+        let budget_tps = any[0].fits[0].estimated_tps;
+        assert!(budget_tps <= 128.0 * 0.7 / model.download_size_gb("Q4_K_M") + 1e-9);
+        request.min_tps = Some(budget_tps + 1.0);
+        let fast = advise(&request, &catalog);
+        assert_eq!(fast.len(), 1);
+        assert_eq!(fast[0].config.gpu.name, "GeForce RTX 4090");
+    }
+
+    #[test]
+    fn test_advise_smallest_objective_orders_by_vram() {
+        let model = test_model("Small", 3.0, 2.0);
+        let mut request = AdvisorRequest::new(vec![&model]);
+        request.objective = AdvisorObjective::Smallest;
+        let results = advise(&request, &default_gpu_catalog());
+
+        for pair in results.windows(2) {
+            assert!(pair[0].config.total_vram_gb() <= pair[1].config.total_vram_gb());
+        }
+    }
+}
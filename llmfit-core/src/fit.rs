@@ -1,4 +1,4 @@
-use crate::hardware::{GpuBackend, SystemSpecs};
+use crate::hardware::{GpuBackend, NpuInfo, PCIE_REFERENCE_GB_S, SystemSpecs};
 use crate::models::{self, LlmModel, UseCase};
 
 /// Inference runtime — the software framework used for inference.
@@ -7,6 +7,8 @@ use crate::models::{self, LlmModel, UseCase};
 pub enum InferenceRuntime {
     LlamaCpp, // llama.cpp / Ollama
     Mlx,      // Apple MLX framework
+    Npu,      // OpenVINO / ONNX Runtime / RKLLM on an NPU
+    Vllm,     // vLLM serving on a CUDA/ROCm GPU
 }
 
 impl InferenceRuntime {
@@ -14,6 +16,8 @@ impl InferenceRuntime {
         match self {
             InferenceRuntime::LlamaCpp => "llama.cpp",
             InferenceRuntime::Mlx => "MLX",
+            InferenceRuntime::Npu => "NPU",
+            InferenceRuntime::Vllm => "vLLM",
         }
     }
 }
@@ -60,6 +64,27 @@ pub enum FitLevel {
     TooTight, // Does not fit in available memory
 }
 
+impl FitLevel {
+    /// Lower is better: Perfect = 0 .. TooTight = 3.
+    pub fn rank(self) -> u8 {
+        match self {
+            FitLevel::Perfect => 0,
+            FitLevel::Good => 1,
+            FitLevel::Marginal => 2,
+            FitLevel::TooTight => 3,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FitLevel::Perfect => "Perfect",
+            FitLevel::Good => "Good",
+            FitLevel::Marginal => "Marginal",
+            FitLevel::TooTight => "Too Tight",
+        }
+    }
+}
+
 /// Execution path -- how will inference run?
 /// This is the "optimization" dimension, independent of memory fit.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
@@ -68,6 +93,111 @@ pub enum RunMode {
     MoeOffload, // MoE: active experts in VRAM, inactive offloaded to RAM
     CpuOffload, // Partial GPU offload, spills to system RAM -- mixed
     CpuOnly,    // Entirely in system RAM, no GPU -- slow
+    Npu,        // Small model on an NPU, weights in system RAM
+    DiskStream, // Larger than RAM, mmapped weights paged from disk -- very slow
+}
+
+/// Free space (GB) a download should leave behind before it counts as a comfortable fit.
+const DISK_HEADROOM_GB: f64 = 5.0;
+
+/// Disk fit -- is there room in the provider's storage dir for the download?
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum DiskFit {
+    Fits,    // Download fits with headroom to spare
+    Tight,   // Fits, but leaves less than DISK_HEADROOM_GB free
+    NoSpace, // Download is larger than the free space
+    Unknown, // Free space could not be determined
+}
+
+impl DiskFit {
+    pub fn from_sizes(download_gb: f64, free_gb: Option<f64>) -> Self {
+        match free_gb {
+            None => DiskFit::Unknown,
+            Some(free) if download_gb > free => DiskFit::NoSpace,
+            Some(free) if free - download_gb < DISK_HEADROOM_GB => DiskFit::Tight,
+            Some(_) => DiskFit::Fits,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiskFit::Fits => "Fits",
+            DiskFit::Tight => "Tight",
+            DiskFit::NoSpace => "No Space",
+            DiskFit::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Share of VRAM vLLM claims at startup (`--gpu-memory-utilization`).
+pub const VLLM_GPU_MEMORY_UTILIZATION: f64 = 0.9;
+
+/// CUDA graphs, activations and sampler buffers vLLM keeps outside the
+/// weights and KV cache (GB).
+const VLLM_OVERHEAD_GB: f64 = 1.5;
+
+/// vLLM's default `--max-num-seqs`: the scheduler never runs more than
+/// this many sequences, however much KV cache is left.
+const VLLM_MAX_NUM_SEQS: u32 = 256;
+
+/// How vLLM would serve a model. Unlike llama.cpp it pre-allocates a fixed
+/// share of VRAM, loads the weights, and pages the remainder out as a KV
+/// cache shared by every running sequence.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct VllmPlan {
+    pub quant: String,
+    /// Longest sequence served (`--max-model-len`).
+    pub max_model_len: u32,
+    pub weights_gb: f64,
+    pub kv_cache_gb: f64,
+    /// Full-length sequences the KV cache holds at once.
+    pub max_concurrent_seqs: u32,
+}
+
+impl VllmPlan {
+    /// Best-quality plan that holds at least one full-length sequence,
+    /// halving the context once if it must. `None` without a CUDA/ROCm GPU
+    /// or when no quant fits.
+    pub fn for_system(model: &LlmModel, system: &SystemSpecs) -> Option<Self> {
+        let pool = vllm_pool_gb(system)?;
+        let ctx = model.context_length;
+        for max_model_len in [ctx, ctx / 2] {
+            if max_model_len < ctx && max_model_len < 1024 {
+                break;
+            }
+            let seq_gb = model.kv_cache_gb(max_model_len).max(0.001);
+            for &quant in models::VLLM_QUANT_HIERARCHY {
+                if vllm_required_gb(model, quant, max_model_len) > pool {
+                    continue;
+                }
+                let weights_gb = model.download_size_gb(quant);
+                let kv_cache_gb = pool - weights_gb - VLLM_OVERHEAD_GB;
+                return Some(VllmPlan {
+                    quant: quant.to_string(),
+                    max_model_len,
+                    weights_gb,
+                    kv_cache_gb,
+                    max_concurrent_seqs: ((kv_cache_gb / seq_gb) as u32).min(VLLM_MAX_NUM_SEQS),
+                });
+            }
+        }
+        None
+    }
+}
+
+/// VRAM vLLM may pre-allocate; `None` unless the GPU is CUDA or ROCm.
+/// Same-model cards serve one model with tensor parallelism
+/// (`--tensor-parallel-size`), so the share of every card counts.
+fn vllm_pool_gb(system: &SystemSpecs) -> Option<f64> {
+    if !matches!(system.backend, GpuBackend::Cuda | GpuBackend::Rocm) {
+        return None;
+    }
+    Some(system.gpu_vram_gb? * system.gpu_count.max(1) as f64 * VLLM_GPU_MEMORY_UTILIZATION)
+}
+
+/// Weights, runtime overhead and one `ctx`-token sequence of KV cache.
+fn vllm_required_gb(model: &LlmModel, quant: &str, ctx: u32) -> f64 {
+    model.download_size_gb(quant) + VLLM_OVERHEAD_GB + model.kv_cache_gb(ctx)
 }
 
 /// Multi-dimensional score components (0-100 each).
@@ -100,10 +230,30 @@ pub struct ModelFit {
     pub use_case: UseCase,         // inferred use case category
     pub runtime: InferenceRuntime, // inference runtime (MLX or llama.cpp)
     pub installed: bool,           // model found in a local runtime provider
+    pub download_gb: f64,          // size of the provider's default pull for this model
+    pub disk_fit: DiskFit,         // does that download fit in the provider's storage dir
+    pub vllm: Option<VllmPlan>,    // how vLLM would serve it (`analyze_vllm` only)
 }
 
 impl ModelFit {
     pub fn analyze(model: &LlmModel, system: &SystemSpecs) -> Self {
+        Self::analyze_inner(model, system, false)
+    }
+
+    /// Analyze a model at the GGUF quant it is installed at (in Ollama)
+    /// rather than the best quant for the hardware. Installed weights run
+    /// on llama.cpp, so neither MLX nor the NPU path is considered.
+    pub fn analyze_installed(model: &LlmModel, system: &SystemSpecs, quant: &str) -> Self {
+        let mut fit = Self::analyze_inner(&model.with_quant(quant), system, true);
+        fit.notes.push(format!(
+            "Installed at {}: fit uses the installed weights",
+            quant
+        ));
+        fit
+    }
+
+    /// `pinned`: keep `model.quantization` and run it on llama.cpp.
+    fn analyze_inner(model: &LlmModel, system: &SystemSpecs, pinned: bool) -> Self {
         let mut notes = Vec::new();
 
         let min_vram = model.min_vram_gb.unwrap_or(model.min_ram_gb);
@@ -113,8 +263,9 @@ impl ModelFit {
         // Step 2: score memory fit purely on headroom in that path's memory pool
         let (run_mode, mem_required, mem_available) = if system.has_gpu {
             if system.unified_memory {
-                // Apple Silicon: GPU and CPU share the same memory pool.
-                // No CpuOffload -- there's no separate pool to spill to.
+                // Apple Silicon and AMD APUs: GPU and CPU share the same
+                // memory pool. No CpuOffload -- there's no separate pool to
+                // spill to.
                 if let Some(pool) = system.gpu_vram_gb {
                     notes.push("Unified memory: GPU and CPU share the same pool".to_string());
                     if model.is_moe {
@@ -128,10 +279,19 @@ impl ModelFit {
                 } else {
                     cpu_path(model, system, &mut notes)
                 }
-            } else if let Some(system_vram) = system.gpu_vram_gb {
+            } else if let Some(card_vram) = system.gpu_vram_gb {
+                // llama.cpp splits layers across same-model cards, so their
+                // VRAM pools.
+                let system_vram = card_vram * system.gpu_count.max(1) as f64;
                 if min_vram <= system_vram {
                     // Fits in VRAM -- GPU path
                     notes.push("GPU: model loaded into VRAM".to_string());
+                    if min_vram > card_vram {
+                        notes.push(format!(
+                            "Layers split across {} GPUs ({:.0} GB each)",
+                            system.gpu_count, card_vram
+                        ));
+                    }
                     if model.is_moe {
                         notes.push(format!(
                             "MoE: all {} experts loaded in VRAM (optimal)",
@@ -151,6 +311,8 @@ impl ModelFit {
                         model.min_ram_gb,
                         system.available_ram_gb,
                     )
+                } else if let Some(path) = disk_stream_path(model, system, &mut notes) {
+                    path
                 } else {
                     // Doesn't fit anywhere -- report against VRAM since GPU is preferred
                     notes.push("Insufficient VRAM and system RAM".to_string());
@@ -165,8 +327,11 @@ impl ModelFit {
                 notes.push("GPU detected but VRAM unknown".to_string());
                 cpu_path(model, system, &mut notes)
             }
-        } else {
+        } else if pinned {
             cpu_path(model, system, &mut notes)
+        } else {
+            npu_path(model, system, &mut notes)
+                .unwrap_or_else(|| cpu_path(model, system, &mut notes))
         };
 
         // Score fit purely on memory headroom (Perfect requires GPU)
@@ -187,10 +352,34 @@ impl ModelFit {
         if run_mode == RunMode::CpuOnly {
             notes.push("No GPU -- inference will be slow".to_string());
         }
+        if matches!(run_mode, RunMode::MoeOffload | RunMode::CpuOffload)
+            && pcie_offload_factor(system) < 0.95
+            && let Some(link) = system.gpus.first().and_then(|g| g.pcie_link)
+        {
+            notes.push(format!(
+                "Offload crosses a {} link (~{:.1} GB/s); a Gen4 x16 slot would be faster",
+                link.label(),
+                link.bandwidth_gb_s()
+            ));
+        }
         if matches!(run_mode, RunMode::CpuOffload | RunMode::CpuOnly) && system.total_cpu_cores < 4
         {
             notes.push("Low CPU core count may bottleneck inference".to_string());
         }
+        if matches!(
+            run_mode,
+            RunMode::CpuOffload | RunMode::CpuOnly | RunMode::DiskStream
+        ) && let Some(node_gb) = system
+            .memory_topology
+            .as_ref()
+            .and_then(|m| m.largest_node_gb())
+            && mem_required > node_gb
+        {
+            notes.push(format!(
+                "Spans NUMA nodes ({:.1} GB vs {:.1} GB per node); cross-node reads slow inference, try numactl --interleave=all",
+                mem_required, node_gb
+            ));
+        }
 
         // Compute MoE offloaded amount if applicable
         let moe_offloaded_gb = if run_mode == RunMode::MoeOffload {
@@ -200,30 +389,41 @@ impl ModelFit {
         };
 
         // Determine inference runtime
-        let runtime = if system.backend == GpuBackend::Metal && system.unified_memory {
+        let runtime = if pinned {
+            InferenceRuntime::LlamaCpp
+        } else if system.backend == GpuBackend::Metal && system.unified_memory {
             InferenceRuntime::Mlx
+        } else if run_mode == RunMode::Npu {
+            InferenceRuntime::Npu
         } else {
             InferenceRuntime::LlamaCpp
         };
 
         // Dynamic quantization: find best quant that fits
         let budget = mem_available;
-        let hierarchy: &[&str] = if runtime == InferenceRuntime::Mlx {
-            models::MLX_QUANT_HIERARCHY
+        let hierarchy: &[&str] = match runtime {
+            InferenceRuntime::Mlx => models::MLX_QUANT_HIERARCHY,
+            InferenceRuntime::Npu => models::NPU_QUANT_HIERARCHY,
+            InferenceRuntime::Vllm => models::VLLM_QUANT_HIERARCHY,
+            InferenceRuntime::LlamaCpp => models::QUANT_HIERARCHY,
+        };
+        let (best_quant, _best_quant_mem) = if run_mode == RunMode::DiskStream || pinned {
+            // Streaming is already over budget; a larger quant only adds disk reads.
+            // Pinned weights are already on disk at their quant.
+            (model.quantization.as_str(), mem_required)
         } else {
-            models::QUANT_HIERARCHY
+            model
+                .best_quant_for_budget_with(budget, model.context_length, hierarchy)
+                .or_else(|| {
+                    // Fall back to GGUF hierarchy if MLX quants don't fit
+                    if runtime == InferenceRuntime::Mlx {
+                        model.best_quant_for_budget(budget, model.context_length)
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or((model.quantization.as_str(), mem_required))
         };
-        let (best_quant, _best_quant_mem) = model
-            .best_quant_for_budget_with(budget, model.context_length, hierarchy)
-            .or_else(|| {
-                // Fall back to GGUF hierarchy if MLX quants don't fit
-                if runtime == InferenceRuntime::Mlx {
-                    model.best_quant_for_budget(budget, model.context_length)
-                } else {
-                    None
-                }
-            })
-            .unwrap_or((model.quantization.as_str(), mem_required));
         let best_quant_str = if best_quant != model.quantization {
             notes.push(format!(
                 "Best quantization for hardware: {} (model default: {})",
@@ -236,6 +436,7 @@ impl ModelFit {
 
         // Speed estimation
         let estimated_tps = estimate_tps(model, &best_quant_str, system, run_mode, runtime);
+        notes.extend(power_note(system, run_mode));
 
         // Add runtime comparison note on Apple Silicon
         if runtime == InferenceRuntime::Mlx {
@@ -257,14 +458,35 @@ impl ModelFit {
             }
         }
 
+        // NPU vs running the same model on the CPU cores
+        if runtime == InferenceRuntime::Npu {
+            let cpu_tps = estimate_tps(
+                model,
+                &model.quantization,
+                system,
+                RunMode::CpuOnly,
+                InferenceRuntime::LlamaCpp,
+            );
+            notes.push(format!(
+                "CPU-only llama.cpp alternative: {:.1} tok/s",
+                cpu_tps
+            ));
+        }
+
         // Multi-dimensional scoring
+        // Streaming never fits in RAM, so its fit component scores zero.
+        let score_pool = if run_mode == RunMode::DiskStream {
+            system.available_ram_gb
+        } else {
+            mem_available
+        };
         let score_components = compute_scores(
             model,
             &best_quant_str,
             use_case,
             estimated_tps,
             mem_required,
-            mem_available,
+            score_pool,
         );
         let score = weighted_score(score_components, use_case);
 
@@ -272,6 +494,15 @@ impl ModelFit {
             notes.push(format!("Estimated speed: {:.1} tok/s", estimated_tps));
         }
 
+        // Pulls fetch the provider's default build, not best_quant: MLX's
+        // 4-bit conversion, or the catalogue quant Ollama tags ship as.
+        let download_gb = match runtime {
+            InferenceRuntime::Mlx => model.download_size_gb("mlx-4bit"),
+            InferenceRuntime::Npu => model.download_size_gb("npu-int4"),
+            InferenceRuntime::Vllm => model.download_size_gb(&best_quant_str),
+            InferenceRuntime::LlamaCpp => model.download_size_gb(&model.quantization),
+        };
+
         ModelFit {
             model: model.clone(),
             fit_level,
@@ -288,9 +519,138 @@ impl ModelFit {
             use_case,
             runtime,
             installed: false, // set later by App after provider detection
+            download_gb,
+            disk_fit: DiskFit::Unknown, // set later once storage is probed
+            vllm: None,                 // only `analyze_vllm` plans for vLLM
+        }
+    }
+
+    /// Analyze serving `model` with vLLM rather than the runtime `analyze`
+    /// picks. vLLM has no CPU offload and no GGUF: the whole model and at
+    /// least one full sequence of KV cache must fit in its VRAM share.
+    pub fn analyze_vllm(model: &LlmModel, system: &SystemSpecs) -> Self {
+        let mut notes = Vec::new();
+        let use_case = UseCase::from_model(model);
+        let vllm = VllmPlan::for_system(model, system);
+        let pool = vllm_pool_gb(system);
+        let mem_available = pool.unwrap_or(0.0);
+
+        let (mem_required, best_quant) = match &vllm {
+            Some(plan) => {
+                notes.push(format!(
+                    "vLLM pre-allocates {:.0}% of VRAM ({:.1} GB)",
+                    VLLM_GPU_MEMORY_UTILIZATION * 100.0,
+                    mem_available
+                ));
+                if system.gpu_count > 1 {
+                    notes.push(format!(
+                        "Tensor parallel across {} GPUs (--tensor-parallel-size {})",
+                        system.gpu_count, system.gpu_count
+                    ));
+                }
+                notes.push(format!(
+                    "Paged KV cache: {:.1} GB, up to {} concurrent {}-token sequences",
+                    plan.kv_cache_gb, plan.max_concurrent_seqs, plan.max_model_len
+                ));
+                if plan.max_model_len < model.context_length {
+                    notes.push(format!(
+                        "Context capped at {} tokens (--max-model-len)",
+                        plan.max_model_len
+                    ));
+                }
+                (
+                    vllm_required_gb(model, &plan.quant, plan.max_model_len),
+                    plan.quant.clone(),
+                )
+            }
+            None => {
+                // Report the smallest footprint vLLM could get to.
+                let quant = models::VLLM_QUANT_HIERARCHY
+                    .last()
+                    .copied()
+                    .unwrap_or("AWQ-4bit");
+                let ctx = (model.context_length / 2).max(model.context_length.min(1024));
+                let needed = vllm_required_gb(model, quant, ctx);
+                if pool.is_some() {
+                    notes.push(format!(
+                        "vLLM: needs {:.1} GB, more than the {:.1} GB it pre-allocates",
+                        needed, mem_available
+                    ));
+                } else {
+                    notes.push("vLLM needs a CUDA or ROCm GPU".to_string());
+                }
+                (needed, quant.to_string())
+            }
+        };
+
+        let fit_level = score_fit(
+            mem_required,
+            mem_available,
+            model.recommended_ram_gb,
+            RunMode::Gpu,
+        );
+        let utilization_pct = if mem_available > 0.0 {
+            (mem_required / mem_available) * 100.0
+        } else {
+            f64::INFINITY
+        };
+
+        let estimated_tps = if pool.is_some() {
+            estimate_tps(
+                model,
+                &best_quant,
+                system,
+                RunMode::Gpu,
+                InferenceRuntime::Vllm,
+            )
+        } else {
+            0.0
+        };
+        notes.extend(power_note(system, RunMode::Gpu));
+
+        let score_components = compute_scores(
+            model,
+            &best_quant,
+            use_case,
+            estimated_tps,
+            mem_required,
+            mem_available,
+        );
+        let score = weighted_score(score_components, use_case);
+        if estimated_tps > 0.0 {
+            notes.push(format!(
+                "Estimated speed: {:.1} tok/s per sequence",
+                estimated_tps
+            ));
+        }
+
+        ModelFit {
+            model: model.clone(),
+            fit_level,
+            run_mode: RunMode::Gpu,
+            memory_required_gb: mem_required,
+            memory_available_gb: mem_available,
+            utilization_pct,
+            notes,
+            moe_offloaded_gb: None,
+            score,
+            score_components,
+            estimated_tps,
+            download_gb: model.download_size_gb(&best_quant),
+            best_quant,
+            use_case,
+            runtime: InferenceRuntime::Vllm,
+            installed: false,
+            disk_fit: DiskFit::Unknown,
+            vllm,
         }
     }
 
+    /// Record free space in the download dir and derive `disk_fit` from it.
+    pub fn set_disk_free(&mut self, free_gb: Option<f64>) {
+        self.disk_fit = DiskFit::from_sizes(self.download_gb, free_gb);
+    }
+
     pub fn fit_emoji(&self) -> &str {
         match self.fit_level {
             FitLevel::Perfect => "🟢",
@@ -301,12 +661,7 @@ impl ModelFit {
     }
 
     pub fn fit_text(&self) -> &str {
-        match self.fit_level {
-            FitLevel::Perfect => "Perfect",
-            FitLevel::Good => "Good",
-            FitLevel::Marginal => "Marginal",
-            FitLevel::TooTight => "Too Tight",
-        }
+        self.fit_level.label()
     }
 
     pub fn runtime_text(&self) -> &str {
@@ -319,12 +674,14 @@ impl ModelFit {
             RunMode::MoeOffload => "MoE",
             RunMode::CpuOffload => "CPU+GPU",
             RunMode::CpuOnly => "CPU",
+            RunMode::Npu => "NPU",
+            RunMode::DiskStream => "Disk",
         }
     }
 }
 
 /// Pure memory headroom scoring.
-/// - GPU (including Apple Silicon unified memory): can reach Perfect.
+/// - GPU (including unified memory on Apple Silicon and AMD APUs): can reach Perfect.
 /// - CpuOffload: caps at Good.
 /// - CpuOnly: caps at Marginal -- CPU-only inference is always a compromise.
 fn score_fit(
@@ -356,27 +713,81 @@ fn score_fit(
                 FitLevel::Marginal
             }
         }
-        RunMode::CpuOffload => {
-            // Mixed GPU/CPU -- decent but not ideal
+        RunMode::CpuOffload | RunMode::Npu => {
+            // Mixed GPU/CPU, or an NPU on shared RAM -- decent but not ideal
             if mem_available >= mem_required * 1.2 {
                 FitLevel::Good
             } else {
                 FitLevel::Marginal
             }
         }
-        RunMode::CpuOnly => {
+        RunMode::CpuOnly | RunMode::DiskStream => {
             // CPU-only is always a compromise -- cap at Marginal
             FitLevel::Marginal
         }
     }
 }
 
+/// Run small dense models on an NPU instead of the CPU cores, when the NPU
+/// is the faster of the two. NPUs share system RAM, so the pool is the same
+/// as the CPU path's.
+fn npu_path(
+    model: &LlmModel,
+    system: &SystemSpecs,
+    notes: &mut Vec<String>,
+) -> Option<(RunMode, f64, f64)> {
+    let npu = system.npus.first()?;
+    if model.is_moe
+        || model.params_b() > npu.max_params_b()
+        || model.min_ram_gb > system.available_ram_gb
+    {
+        return None;
+    }
+
+    // Compare at the quant each runtime would pick for the same budget.
+    let budget = system.available_ram_gb;
+    let npu_quant = model
+        .best_quant_for_budget_with(budget, model.context_length, models::NPU_QUANT_HIERARCHY)
+        .map_or(model.quantization.as_str(), |(q, _)| q);
+    let cpu_quant = model
+        .best_quant_for_budget(budget, model.context_length)
+        .map_or(model.quantization.as_str(), |(q, _)| q);
+    let npu_tps = estimate_tps(
+        model,
+        npu_quant,
+        system,
+        RunMode::Npu,
+        InferenceRuntime::Npu,
+    );
+    let cpu_tps = estimate_tps(
+        model,
+        cpu_quant,
+        system,
+        RunMode::CpuOnly,
+        InferenceRuntime::LlamaCpp,
+    );
+    if npu_tps <= cpu_tps {
+        notes.push(format!(
+            "NPU: {} could run the model, but slower than the CPU cores ({:.1} vs {:.1} tok/s)",
+            npu.name, npu_tps, cpu_tps
+        ));
+        return None;
+    }
+    notes.push(format!("NPU: {} runs the model from system RAM", npu.name));
+    Some((RunMode::Npu, model.min_ram_gb, system.available_ram_gb))
+}
+
 /// Determine memory pool for CPU-only inference.
 fn cpu_path(
     model: &LlmModel,
     system: &SystemSpecs,
     notes: &mut Vec<String>,
 ) -> (RunMode, f64, f64) {
+    if model.min_ram_gb > system.available_ram_gb
+        && let Some(path) = disk_stream_path(model, system, notes)
+    {
+        return path;
+    }
     notes.push("CPU-only: model loaded into system RAM".to_string());
     if model.is_moe {
         notes.push("MoE architecture, but expert offloading requires a GPU".to_string());
@@ -384,6 +795,40 @@ fn cpu_path(
     (RunMode::CpuOnly, model.min_ram_gb, system.available_ram_gb)
 }
 
+/// Models up to this multiple of resident memory can stream from disk;
+/// beyond it nearly every weight is re-read per token.
+const MAX_STREAM_RATIO: f64 = 2.0;
+
+/// Memory that can hold mmapped weights: available RAM plus free swap, which
+/// lets the kernel push other processes' pages out.
+fn stream_resident_gb(system: &SystemSpecs) -> f64 {
+    system.available_ram_gb + system.storage.as_ref().map_or(0.0, |s| s.swap_free_gb)
+}
+
+/// Try running a model that exceeds RAM by letting llama.cpp mmap its
+/// weights and page the remainder from disk. Returns the streaming pool as
+/// the available memory so fit and utilization stay meaningful.
+fn disk_stream_path(
+    model: &LlmModel,
+    system: &SystemSpecs,
+    notes: &mut Vec<String>,
+) -> Option<(RunMode, f64, f64)> {
+    let storage = system.storage.as_ref()?;
+    let kind = storage.disk_kind?;
+    let resident = stream_resident_gb(system);
+    let capacity = resident * MAX_STREAM_RATIO;
+    if resident <= 0.0 || model.min_ram_gb > capacity {
+        return None;
+    }
+    notes.push("Larger than RAM: weights mmapped and paged from disk".to_string());
+    notes.push(format!(
+        "~{:.1} GB streamed from {} per token",
+        (model.min_ram_gb - resident).max(0.0),
+        kind.label()
+    ));
+    Some((RunMode::DiskStream, model.min_ram_gb, capacity))
+}
+
 /// Try MoE expert offloading: active experts in VRAM, inactive in RAM.
 /// Falls back to CPU paths if offloading isn't viable.
 fn moe_offload_path(
@@ -420,6 +865,8 @@ fn moe_offload_path(
             model.min_ram_gb,
             system.available_ram_gb,
         )
+    } else if let Some(path) = disk_stream_path(model, system, notes) {
+        path
     } else {
         notes.push("Insufficient VRAM and system RAM".to_string());
         notes.push(format!(
@@ -454,14 +901,20 @@ pub fn rank_models_by_fit_opts_col(
             }
         }
 
-        // TooTight always sorts last regardless of column
-        let a_runnable = a.fit_level != FitLevel::TooTight;
-        let b_runnable = b.fit_level != FitLevel::TooTight;
-
-        match (a_runnable, b_runnable) {
-            (true, false) => return std::cmp::Ordering::Less,
-            (false, true) => return std::cmp::Ordering::Greater,
-            _ => {}
+        // TooTight always sorts last regardless of column, and disk streaming
+        // sorts after everything that runs from memory
+        let tier = |f: &ModelFit| {
+            if f.fit_level == FitLevel::TooTight {
+                2
+            } else if f.run_mode == RunMode::DiskStream {
+                1
+            } else {
+                0
+            }
+        };
+        let tier_cmp = tier(a).cmp(&tier(b));
+        if tier_cmp != std::cmp::Ordering::Equal {
+            return tier_cmp;
         }
 
         // Sort by selected column
@@ -513,8 +966,9 @@ fn estimate_tps(
 ) -> f64 {
     // Backend speed constant K (higher = faster)
     let k: f64 = match (system.backend, runtime) {
+        (_, InferenceRuntime::Npu) => system.npus.first().map_or(30.0, npu_k),
         (GpuBackend::Metal, InferenceRuntime::Mlx) => 250.0,
-        (GpuBackend::Metal, InferenceRuntime::LlamaCpp) => 160.0,
+        (GpuBackend::Metal, _) => 160.0,
         (GpuBackend::Cuda, _) => 220.0,
         (GpuBackend::Rocm, _) => 180.0,
         (GpuBackend::Vulkan, _) => 150.0,
@@ -523,6 +977,14 @@ fn estimate_tps(
         (GpuBackend::CpuX86, _) => 70.0,
     };
 
+    // AMD APUs and Jetson share DDR/LPDDR with the CPU: a fraction of a
+    // discrete card's bandwidth (Apple's wide unified memory is priced into Metal's K).
+    let k = if system.unified_memory && system.backend != GpuBackend::Metal {
+        k * 0.4
+    } else {
+        k
+    };
+
     let params = model.params_b().max(0.1);
     let mut base = k / params;
 
@@ -534,28 +996,194 @@ fn estimate_tps(
         base *= 1.1;
     }
 
+    // Unified memory (and an NPU's) is system RAM: when its bandwidth is
+    // known, it bounds decoding the same way it bounds the CPU path below.
+    if (run_mode == RunMode::Npu || (run_mode == RunMode::Gpu && system.unified_memory))
+        && let Some(bw) = system.memory_bandwidth_gb_s()
+    {
+        let weights_gb = model.download_size_gb(quant).max(0.1);
+        base = base.min(bw * 0.7 / weights_gb);
+    }
+
+    // A discrete card's decoding is bound by its VRAM bandwidth: each token
+    // reads the weights (only the active experts, for MoE) once. Split
+    // layers run card after card, so the primary card's bandwidth covers
+    // them all.
+    if run_mode == RunMode::Gpu
+        && !system.unified_memory
+        && let Some(bw) = system.gpus.first().and_then(|g| g.memory_bandwidth_gb_s)
+    {
+        let active_share = match model.active_parameters {
+            Some(active) if model.is_moe => active as f64 / 1e9 / params,
+            _ => 1.0,
+        };
+        let read_gb = (model.download_size_gb(quant) * active_share).max(0.1);
+        base = base.min(bw * 0.7 / read_gb);
+    }
+
     // Run mode penalties
     match run_mode {
-        RunMode::Gpu => {}                  // full speed
-        RunMode::MoeOffload => base *= 0.8, // expert switching latency
-        RunMode::CpuOffload => base *= 0.5, // significant penalty
-        RunMode::CpuOnly => base *= 0.3,    // worst case—override K to CPU
+        RunMode::Gpu | RunMode::Npu => {} // full speed
+        RunMode::MoeOffload => base *= 0.8 * pcie_offload_factor(system), // expert switching latency
+        RunMode::CpuOffload => base *= 0.5 * pcie_offload_factor(system), // significant penalty
+        RunMode::CpuOnly | RunMode::DiskStream => base *= 0.3, // worst case—override K to CPU
     }
 
     // CPU-only should use CPU K regardless of detected GPU
-    if run_mode == RunMode::CpuOnly {
-        let cpu_k = if cfg!(target_arch = "aarch64") {
+    if matches!(run_mode, RunMode::CpuOnly | RunMode::DiskStream) {
+        base = (cpu_k(system) / params) * models::quant_speed_multiplier(quant);
+        base *= cpu_thread_factor(system);
+
+        // Each token reads every weight once; ~70% of peak RAM bandwidth is
+        // what llama.cpp sustains in practice.
+        if let Some(bw) = system.memory_bandwidth_gb_s() {
+            let weights_gb = model.download_size_gb(quant).max(0.1);
+            base = base.min(bw * 0.7 / weights_gb);
+        }
+    }
+
+    // Streaming is disk-bound: every token re-reads the non-resident weights.
+    if run_mode == RunMode::DiskStream
+        && let Some(read_gb_s) = system.storage.as_ref().and_then(|s| s.disk_read_gb_s)
+    {
+        let streamed_gb = (model.min_ram_gb - stream_resident_gb(system)).max(0.1);
+        base = base.min(read_gb_s / streamed_gb);
+        return base.max(0.01);
+    }
+
+    base *= power_derating(system, run_mode);
+
+    base.max(0.1)
+}
+
+/// Extra offload penalty for a narrow PCIe link (eGPU over Thunderbolt,
+/// x4 slots), relative to Gen4 x16. Only activations and prompt batches
+/// cross the link, so speed falls much slower than bandwidth.
+fn pcie_offload_factor(system: &SystemSpecs) -> f64 {
+    match system.gpus.first().and_then(|g| g.pcie_link) {
+        Some(link) => (link.bandwidth_gb_s() / PCIE_REFERENCE_GB_S)
+            .min(1.0)
+            .powf(0.25),
+        None => 1.0,
+    }
+}
+
+/// Note explaining `power_derating`, when it slows this run mode down.
+fn power_note(system: &SystemSpecs, run_mode: RunMode) -> Option<String> {
+    let derating = power_derating(system, run_mode);
+    let power = system.power.as_ref().filter(|_| derating < 1.0)?;
+    Some(format!(
+        "Power ({}): speed derated to {:.0}%",
+        power.label(),
+        derating * 100.0
+    ))
+}
+
+/// Speed multiplier for the power source. Laptop GPUs drop to a fraction
+/// of their power limit on battery; CPUs clock down less, and Apple
+/// Silicon and NPUs hardly at all.
+fn power_derating(system: &SystemSpecs, run_mode: RunMode) -> f64 {
+    let Some(power) = &system.power else {
+        return 1.0;
+    };
+    let discrete_gpu = matches!(
+        run_mode,
+        RunMode::Gpu | RunMode::MoeOffload | RunMode::CpuOffload
+    ) && !system.unified_memory;
+    if discrete_gpu {
+        return match power
+            .gpu_power_ratio()
+            .filter(|_| system.backend == GpuBackend::Cuda)
+        {
+            // Decoding is mostly memory-bound: speed falls slower than power.
+            Some(ratio) if ratio < 0.95 => ratio.sqrt(),
+            _ if power.on_battery => 0.4,
+            _ => 1.0,
+        };
+    }
+    if !power.on_battery {
+        return 1.0;
+    }
+    match run_mode {
+        RunMode::Npu => 1.0,
+        RunMode::Gpu if system.backend == GpuBackend::Metal => 1.0,
+        _ => 0.75,
+    }
+}
+
+/// NPU speed constant K from its TOPS. Decode on NPUs is held back by
+/// runtime overheads well before compute runs out, so first-generation
+/// NPUs only match the CPU cores (at a fraction of the power).
+fn npu_k(npu: &NpuInfo) -> f64 {
+    match npu.tops {
+        Some(tops) if tops >= 40.0 => 120.0,
+        Some(tops) if tops >= 10.0 => 70.0,
+        _ => 40.0,
+    }
+}
+
+/// CPU speed constant K, scaled down for slow board CPUs (Raspberry Pi,
+/// RK3588) whose cores share an ISA with much faster desktop parts.
+fn cpu_k(system: &SystemSpecs) -> f64 {
+    let board_factor = system
+        .board
+        .as_ref()
+        .and_then(|b| b.cpu_speed_factor)
+        .unwrap_or(1.0);
+    cpu_isa_k(system) * board_factor
+}
+
+/// CPU speed constant K, scaled by the SIMD extensions llama.cpp's
+/// quantized kernels use. Without detected features, falls back to the
+/// build target's baseline.
+fn cpu_isa_k(system: &SystemSpecs) -> f64 {
+    let Some(f) = &system.cpu_features else {
+        return if cfg!(target_arch = "aarch64") {
             90.0
         } else {
             70.0
         };
-        base = (cpu_k / params) * models::quant_speed_multiplier(quant);
-        if system.total_cpu_cores >= 8 {
-            base *= 1.1;
+    };
+    if f.neon || f.sve {
+        let mut k = 90.0;
+        if f.sve {
+            k *= 1.1;
+        }
+        if f.i8mm {
+            k *= 1.15;
         }
+        return k;
+    }
+    // No AVX2 means scalar/SSE kernels (Atom, pre-Haswell).
+    let mut k = if f.avx2 { 70.0 } else { 35.0 };
+    if f.avx512 {
+        k *= 1.15;
+    }
+    if f.avx_vnni {
+        k *= 1.1;
+    }
+    if f.amx {
+        k *= 1.3;
     }
+    k
+}
 
-    base.max(0.1)
+/// Threading multiplier from physical cores (logical if unknown).
+/// Token generation is memory-bound, so returns diminish past ~16 cores.
+fn cpu_thread_factor(system: &SystemSpecs) -> f64 {
+    let cores = system
+        .cpu_features
+        .as_ref()
+        .and_then(|f| f.physical_cores)
+        .unwrap_or(system.total_cpu_cores);
+    match cores {
+        0..=2 => 0.6,
+        3..=5 => 0.85,
+        6..=7 => 1.0,
+        8..=15 => 1.1,
+        16..=31 => 1.2,
+        _ => 1.3,
+    }
 }
 
 // ────────────────────────────────────────────────────────────────────
@@ -720,7 +1348,10 @@ fn weighted_score(sc: ScoreComponents, use_case: UseCase) -> f64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::hardware::{GpuBackend, SystemSpecs};
+    use crate::hardware::{
+        BoardInfo, CpuFeatures, DiskKind, GpuBackend, GpuInfo, MemoryTopology, NumaNode, PcieLink,
+        PowerState, StorageInfo, SystemSpecs,
+    };
 
     // ────────────────────────────────────────────────────────────────────
     // Helper to create test model
@@ -766,6 +1397,14 @@ mod tests {
                 GpuBackend::CpuX86
             },
             gpus: vec![],
+            cgroup_limits: None,
+            storage: None,
+            cpu_features: None,
+            memory_topology: None,
+            detection: Default::default(),
+            board: None,
+            npus: Vec::new(),
+            power: None,
         }
     }
 
@@ -896,6 +1535,59 @@ mod tests {
         assert_eq!(fit.fit_level, FitLevel::TooTight);
     }
 
+    fn nvme_storage(swap_free_gb: f64) -> StorageInfo {
+        StorageInfo {
+            swap_total_gb: swap_free_gb,
+            swap_free_gb,
+            disk_name: Some("nvme0n1".to_string()),
+            disk_kind: Some(DiskKind::Nvme),
+            disk_read_gb_s: Some(DiskKind::Nvme.typical_read_gb_s()),
+        }
+    }
+
+    #[test]
+    fn test_model_fit_disk_stream_when_slightly_over_ram() {
+        let model = test_model("30B", 20.0, Some(20.0));
+        let mut system = test_system(16.0, false, None);
+        system.storage = Some(nvme_storage(0.0));
+
+        let fit = ModelFit::analyze(&model, &system);
+
+        assert_eq!(fit.run_mode, RunMode::DiskStream);
+        assert_eq!(fit.fit_level, FitLevel::Marginal);
+        assert_eq!(fit.best_quant, model.quantization);
+        // 20 GB model, 12.8 GB resident: ~7.2 GB read from NVMe per token.
+        let disk_bound = 3.0 / (20.0 - 12.8);
+        assert!(fit.estimated_tps <= disk_bound + 1e-9);
+        assert!(fit.notes.iter().any(|n| n.contains("NVMe")));
+    }
+
+    #[test]
+    fn test_model_fit_disk_stream_counts_swap() {
+        let model = test_model("70B", 40.0, Some(40.0));
+        let mut system = test_system(16.0, true, Some(8.0));
+        system.storage = Some(nvme_storage(0.0));
+        assert_eq!(
+            ModelFit::analyze(&model, &system).fit_level,
+            FitLevel::TooTight
+        );
+
+        system.storage = Some(nvme_storage(8.0));
+        let fit = ModelFit::analyze(&model, &system);
+        assert_eq!(fit.run_mode, RunMode::DiskStream);
+    }
+
+    #[test]
+    fn test_model_fit_no_disk_stream_without_storage_info() {
+        let model = test_model("30B", 20.0, Some(20.0));
+        let system = test_system(16.0, false, None);
+
+        let fit = ModelFit::analyze(&model, &system);
+
+        assert_eq!(fit.run_mode, RunMode::CpuOnly);
+        assert_eq!(fit.fit_level, FitLevel::TooTight);
+    }
+
     #[test]
     fn test_model_fit_utilization() {
         let model = test_model("7B", 4.0, Some(4.0));
@@ -970,6 +1662,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rank_models_puts_disk_stream_after_in_memory() {
+        let small = test_model("3B", 2.0, Some(2.0));
+        let large = test_model("30B", 20.0, Some(20.0));
+        let mut system = test_system(16.0, false, None);
+        system.storage = Some(nvme_storage(0.0));
+
+        let streamed = ModelFit::analyze(&large, &system);
+        let in_memory = ModelFit::analyze(&small, &system);
+        assert_eq!(streamed.run_mode, RunMode::DiskStream);
+
+        let ranked = rank_models_by_fit(vec![streamed, in_memory]);
+        assert_eq!(ranked[0].run_mode, RunMode::CpuOnly);
+        assert_eq!(ranked[1].run_mode, RunMode::DiskStream);
+    }
+
     // ────────────────────────────────────────────────────────────────────
     // Scoring function tests
     // ────────────────────────────────────────────────────────────────────
@@ -1179,4 +1887,362 @@ mod tests {
         assert!(tps_gpu > 0.0);
         assert!(tps_cpu > 0.0);
     }
+
+    #[test]
+    fn test_disk_fit_from_free_space() {
+        let model = test_model("7B", 4.0, Some(4.0));
+        let system = test_system(16.0, true, Some(10.0));
+        let mut fit = ModelFit::analyze(&model, &system);
+        assert_eq!(fit.disk_fit, DiskFit::Unknown);
+
+        let expected = 7.0 * models::quant_bpp(&model.quantization);
+        assert!((fit.download_gb - expected).abs() < 1e-9);
+
+        fit.set_disk_free(Some(100.0));
+        assert_eq!(fit.disk_fit, DiskFit::Fits);
+        fit.set_disk_free(Some(fit.download_gb + 1.0));
+        assert_eq!(fit.disk_fit, DiskFit::Tight);
+        fit.set_disk_free(Some(1.0));
+        assert_eq!(fit.disk_fit, DiskFit::NoSpace);
+        fit.set_disk_free(None);
+        assert_eq!(fit.disk_fit, DiskFit::Unknown);
+    }
+
+    #[test]
+    fn test_cpu_estimate_scales_with_features_and_cores() {
+        let model = test_model("7B", 4.0, None);
+        let mut zen4 = test_system(64.0, false, None);
+        zen4.total_cpu_cores = 64;
+        zen4.cpu_features = Some(CpuFeatures {
+            physical_cores: Some(32),
+            avx2: true,
+            avx512: true,
+            avx_vnni: true,
+            ..Default::default()
+        });
+        let mut atom = test_system(8.0, false, None);
+        atom.total_cpu_cores = 4;
+        atom.cpu_features = Some(CpuFeatures {
+            physical_cores: Some(4),
+            ..Default::default()
+        });
+
+        let tps = |s: &SystemSpecs| {
+            estimate_tps(
+                &model,
+                "Q4_K_M",
+                s,
+                RunMode::CpuOnly,
+                InferenceRuntime::LlamaCpp,
+            )
+        };
+        assert!(tps(&zen4) > 2.5 * tps(&atom));
+
+        // Unknown features keep the build target's K and the 8-core bonus.
+        let plain = test_system(16.0, false, None);
+        let k = if cfg!(target_arch = "aarch64") {
+            90.0
+        } else {
+            70.0
+        };
+        let expected = k / 7.0 * models::quant_speed_multiplier("Q4_K_M") * 1.1;
+        assert!((tps(&plain) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ram_bandwidth_caps_cpu_speed_and_numa_note() {
+        let model = test_model("70B", 40.0, None);
+        let mut system = test_system(128.0, false, None);
+        system.memory_topology = Some(MemoryTopology {
+            numa_nodes: vec![
+                NumaNode {
+                    id: 0,
+                    total_gb: 32.0,
+                    cpus: 8,
+                },
+                NumaNode {
+                    id: 1,
+                    total_gb: 32.0,
+                    cpus: 8,
+                },
+            ],
+            memory_type: Some("DDR4".to_string()),
+            speed_mt_s: Some(2400),
+            channels: Some(1),
+        });
+
+        // 2400 MT/s x 8 B x 1 channel = 19.2 GB/s, 70% usable.
+        let tps = estimate_tps(
+            &model,
+            "Q4_K_M",
+            &system,
+            RunMode::CpuOnly,
+            InferenceRuntime::LlamaCpp,
+        );
+        let cap = 19.2 * 0.7 / model.download_size_gb("Q4_K_M");
+        assert!((tps - cap).abs() < 1e-9);
+
+        let fit = ModelFit::analyze(&model, &system);
+        assert_eq!(fit.run_mode, RunMode::CpuOnly);
+        assert!(fit.notes.iter().any(|n| n.starts_with("Spans NUMA nodes")));
+    }
+
+    #[test]
+    fn test_amd_apu_uses_unified_pool_at_shared_bandwidth() {
+        // Strix Halo: 128 GB LPDDR5X, most of it reachable through GTT.
+        let model = test_model("70B", 40.0, Some(40.0));
+        let mut apu = test_system(124.0, true, Some(110.5));
+        apu.backend = GpuBackend::Rocm;
+        apu.unified_memory = true;
+        let mut discrete = apu.clone();
+        discrete.unified_memory = false;
+
+        let fit = ModelFit::analyze(&model, &apu);
+        assert_eq!(fit.run_mode, RunMode::Gpu);
+        assert_eq!(fit.runtime, InferenceRuntime::LlamaCpp);
+        assert_eq!(fit.memory_available_gb, 110.5);
+        assert!(fit.notes.iter().any(|n| n.contains("Unified memory")));
+
+        let tps = |s: &SystemSpecs| {
+            estimate_tps(
+                &model,
+                "Q4_K_M",
+                s,
+                RunMode::Gpu,
+                InferenceRuntime::LlamaCpp,
+            )
+        };
+        assert!(tps(&apu) < tps(&discrete));
+
+        // 8000 MT/s x 8 B x 4 channels = 256 GB/s, 70% usable.
+        apu.memory_topology = Some(MemoryTopology {
+            memory_type: Some("LPDDR5X".to_string()),
+            speed_mt_s: Some(8000),
+            channels: Some(4),
+            ..Default::default()
+        });
+        let cap = 256.0 * 0.7 / model.download_size_gb("Q4_K_M");
+        assert!(tps(&apu) <= cap + 1e-9);
+    }
+
+    #[test]
+    fn test_board_profile_caps_cpu_speed() {
+        let model = test_model("7B", 4.0, None);
+        let desktop = test_system(8.0, false, None);
+        let mut pi = desktop.clone();
+        pi.board = Some(BoardInfo {
+            model: "Raspberry Pi 5 Model B Rev 1.0".to_string(),
+            tegra: false,
+            memory_bandwidth_gb_s: Some(17.1),
+            cpu_speed_factor: Some(0.5),
+        });
+
+        let tps = |s: &SystemSpecs| {
+            estimate_tps(
+                &model,
+                "Q4_K_M",
+                s,
+                RunMode::CpuOnly,
+                InferenceRuntime::LlamaCpp,
+            )
+        };
+        assert!(tps(&pi) < tps(&desktop));
+        assert!(tps(&pi) <= 17.1 * 0.7 / model.download_size_gb("Q4_K_M") + 1e-9);
+    }
+
+    #[test]
+    fn test_small_models_run_on_npu() {
+        let mut laptop = test_system(32.0, false, None);
+        laptop.npus = vec![NpuInfo {
+            name: "Intel AI Boost NPU (Lunar Lake)".to_string(),
+            driver: "intel_vpu".to_string(),
+            tops: Some(48.0),
+        }];
+
+        let small = test_model("3B", 3.0, None);
+        let fit = ModelFit::analyze(&small, &laptop);
+        assert_eq!(fit.run_mode, RunMode::Npu);
+        assert_eq!(fit.runtime, InferenceRuntime::Npu);
+        assert_eq!(fit.best_quant, "npu-int8");
+        assert_eq!(fit.fit_level, FitLevel::Good);
+        assert!(fit.notes.iter().any(|n| n.starts_with("NPU: Intel")));
+        let cpu = ModelFit::analyze(&small, &test_system(32.0, false, None));
+        assert_eq!(cpu.run_mode, RunMode::CpuOnly);
+        assert!(fit.score > cpu.score);
+
+        // Past the NPU's size limit, the CPU path takes over.
+        let large = test_model("14B", 10.0, None);
+        let fit = ModelFit::analyze(&large, &laptop);
+        assert_eq!(fit.run_mode, RunMode::CpuOnly);
+        assert_eq!(fit.runtime, InferenceRuntime::LlamaCpp);
+
+        // A GPU is always preferred.
+        let mut gpu_laptop = test_system(32.0, true, Some(8.0));
+        gpu_laptop.npus = laptop.npus.clone();
+        assert_eq!(
+            ModelFit::analyze(&small, &gpu_laptop).run_mode,
+            RunMode::Gpu
+        );
+
+        // A first-generation NPU no faster than the cores stays a note.
+        let mut meteor_lake = laptop.clone();
+        meteor_lake.npus[0].name = "Intel AI Boost NPU (Meteor Lake)".to_string();
+        meteor_lake.npus[0].tops = Some(11.0);
+        let fit = ModelFit::analyze(&small, &meteor_lake);
+        assert_eq!(fit.run_mode, RunMode::CpuOnly);
+        assert_eq!(fit.runtime, InferenceRuntime::LlamaCpp);
+        assert!(
+            fit.notes
+                .iter()
+                .any(|n| n.contains("slower than the CPU cores"))
+        );
+    }
+
+    #[test]
+    fn test_battery_derates_laptop_gpu() {
+        let model = test_model("7B", 6.0, Some(5.0));
+        let plugged = test_system(32.0, true, Some(8.0));
+        let mut unplugged = plugged.clone();
+        unplugged.power = Some(PowerState {
+            on_battery: true,
+            battery_percent: Some(54),
+            ..Default::default()
+        });
+
+        let fast = ModelFit::analyze(&model, &plugged);
+        let slow = ModelFit::analyze(&model, &unplugged);
+        assert!((slow.estimated_tps - fast.estimated_tps * 0.4).abs() < 0.01);
+        assert!(
+            slow.notes
+                .iter()
+                .any(|n| n == "Power (battery 54%): speed derated to 40%")
+        );
+
+        // A reported power limit takes precedence over the flat battery factor.
+        let mut capped = plugged.clone();
+        capped.power = Some(PowerState {
+            on_battery: false,
+            battery_percent: None,
+            gpu_power_limit_w: Some(35.0),
+            gpu_max_power_limit_w: Some(140.0),
+        });
+        let capped_fit = ModelFit::analyze(&model, &capped);
+        assert!((capped_fit.estimated_tps - fast.estimated_tps * 0.5).abs() < 0.01);
+
+        // Apple Silicon keeps its speed on battery.
+        let mut mac = test_system(32.0, true, Some(32.0));
+        mac.backend = GpuBackend::Metal;
+        mac.unified_memory = true;
+        let mut mac_battery = mac.clone();
+        mac_battery.power = unplugged.power.clone();
+        assert_eq!(
+            ModelFit::analyze(&model, &mac).estimated_tps,
+            ModelFit::analyze(&model, &mac_battery).estimated_tps
+        );
+    }
+
+    #[test]
+    fn test_narrow_pcie_link_slows_offload() {
+        let model = test_model("13B", 9.0, Some(8.5));
+        let slot = |generation, width| {
+            let mut system = test_system(64.0, true, Some(6.0));
+            system.gpus.push(GpuInfo {
+                name: "Test GPU".to_string(),
+                vram_gb: Some(6.0),
+                backend: GpuBackend::Cuda,
+                count: 1,
+                unified_memory: false,
+                vram_used_gb: None,
+                provenance: Vec::new(),
+                pcie_link: Some(PcieLink { generation, width }),
+                memory_bandwidth_gb_s: None,
+            });
+            system
+        };
+        let x16 = ModelFit::analyze(&model, &slot(4, 16));
+        let egpu = ModelFit::analyze(&model, &slot(3, 4));
+        assert_eq!(egpu.run_mode, RunMode::CpuOffload);
+        assert_eq!(
+            x16.estimated_tps,
+            ModelFit::analyze(&model, &test_system(64.0, true, Some(6.0))).estimated_tps
+        );
+        assert!(egpu.estimated_tps < x16.estimated_tps * 0.65);
+        assert!(
+            egpu.notes
+                .iter()
+                .any(|n| n.starts_with("Offload crosses a PCIe 3.0 x4 link"))
+        );
+        assert!(!x16.notes.iter().any(|n| n.starts_with("Offload crosses")));
+    }
+
+    #[test]
+    fn test_vllm_plan_sizes_kv_cache_and_concurrency() {
+        // 24 GB card: vLLM claims 21.6 GB, BF16 7B weights take 14 GB and
+        // the rest pages out as KV cache (~0.23 GB per 4K sequence).
+        let model = test_model("7B", 4.0, Some(4.0));
+        let system = test_system(64.0, true, Some(24.0));
+        let fit = ModelFit::analyze_vllm(&model, &system);
+        assert_eq!(fit.runtime, InferenceRuntime::Vllm);
+        assert_eq!(fit.run_mode, RunMode::Gpu);
+        assert_ne!(fit.fit_level, FitLevel::TooTight);
+        assert_eq!(fit.best_quant, "BF16");
+        assert!((fit.memory_available_gb - 21.6).abs() < 1e-9);
+        let plan = fit.vllm.as_ref().unwrap();
+        assert_eq!(plan.max_model_len, 4096);
+        assert_eq!(plan.max_concurrent_seqs, 26);
+        assert!(fit.estimated_tps > 0.0);
+
+        // llama.cpp stays the default and plans nothing for vLLM.
+        let auto = ModelFit::analyze(&model, &system);
+        assert_eq!(auto.runtime, InferenceRuntime::LlamaCpp);
+        assert!(auto.vllm.is_none());
+        assert!(!auto.notes.iter().any(|n| n.contains("vLLM")));
+
+        // Two cards pool their shares with tensor parallelism.
+        let mut pair = system.clone();
+        pair.gpu_count = 2;
+        let fit = ModelFit::analyze_vllm(&model, &pair);
+        assert!((fit.memory_available_gb - 43.2).abs() < 1e-9);
+        assert!(
+            fit.notes
+                .iter()
+                .any(|n| n.starts_with("Tensor parallel across 2 GPUs"))
+        );
+
+        // A 12 GB card drops a 13B model to AWQ with only a few sequences.
+        let model = test_model("13B", 8.0, Some(8.0));
+        let fit = ModelFit::analyze_vllm(&model, &test_system(32.0, true, Some(12.0)));
+        assert_eq!(fit.best_quant, "AWQ-4bit");
+        assert_eq!(fit.vllm.as_ref().unwrap().max_concurrent_seqs, 4);
+
+        // No CPU offload: 70B on the same card just doesn't fit.
+        let model = test_model("70B", 40.0, Some(40.0));
+        let fit = ModelFit::analyze_vllm(&model, &test_system(128.0, true, Some(12.0)));
+        assert_eq!(fit.fit_level, FitLevel::TooTight);
+        assert!(fit.vllm.is_none());
+        assert!(fit.notes.iter().any(|n| n.starts_with("vLLM: needs")));
+    }
+
+    #[test]
+    fn test_vllm_needs_cuda_or_rocm() {
+        let model = test_model("7B", 4.0, Some(4.0));
+        let cpu = test_system(64.0, false, None);
+        let fit = ModelFit::analyze_vllm(&model, &cpu);
+        assert_eq!(fit.fit_level, FitLevel::TooTight);
+        assert_eq!(fit.estimated_tps, 0.0);
+        assert!(
+            fit.notes
+                .contains(&"vLLM needs a CUDA or ROCm GPU".to_string())
+        );
+        assert!(ModelFit::analyze(&model, &cpu).vllm.is_none());
+
+        let mut mac = test_system(64.0, true, Some(48.0));
+        mac.backend = GpuBackend::Metal;
+        mac.unified_memory = true;
+        assert!(VllmPlan::for_system(&model, &mac).is_none());
+
+        let mut rocm = test_system(64.0, true, Some(24.0));
+        rocm.backend = GpuBackend::Rocm;
+        assert!(VllmPlan::for_system(&model, &rocm).is_some());
+    }
 }
@@ -0,0 +1,593 @@
+//! Fleet placement planner.
+//!
+//! Given saved `SystemSpecs` for many hosts and a list of models with replica
+//! counts, assigns each replica to a host (and to specific GPUs on it) while
+//! tracking how much VRAM and RAM earlier placements have already consumed.
+//! Several models may share one card as long as their combined footprint fits.
+
+use std::path::Path;
+
+use crate::fit::{FitLevel, ModelFit, RunMode};
+use crate::hardware::SystemSpecs;
+use crate::models::LlmModel;
+
+/// A host in the fleet, named after the spec file it was loaded from.
+#[derive(Debug, Clone)]
+pub struct FleetHost {
+    pub name: String,
+    pub specs: SystemSpecs,
+}
+
+impl FleetHost {
+    /// Per-card VRAM free for new models, one entry per physical GPU: the
+    /// card's size less `vram_used_gb` when the spec file recorded it, like
+    /// `available_ram_gb` for RAM. Unified-memory hosts expose their shared
+    /// pool as a single card.
+    pub fn gpu_cards(&self) -> Vec<f64> {
+        let specs = &self.specs;
+        if !specs.has_gpu {
+            return Vec::new();
+        }
+        if specs.unified_memory {
+            return specs.gpu_vram_gb.into_iter().collect();
+        }
+        let mut cards = Vec::new();
+        for gpu in &specs.gpus {
+            if let Some(vram) = gpu.vram_free_gb().or(gpu.vram_gb) {
+                cards.extend(std::iter::repeat_n(vram, gpu.count.max(1) as usize));
+            }
+        }
+        if cards.is_empty()
+            && let Some(vram) = specs.gpu_vram_gb
+        {
+            cards.extend(std::iter::repeat_n(vram, specs.gpu_count.max(1) as usize));
+        }
+        cards
+    }
+}
+
+/// Load every `*.json` file in `dir` (output of `llmfit --json system`) as a
+/// fleet host, sorted by name.
+pub fn load_fleet_dir(dir: &Path) -> Result<Vec<FleetHost>, String> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("failed to read {}: {e}", dir.display()))?;
+    let mut hosts = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let specs = SystemSpecs::load_json_file(&path)?;
+        hosts.push(FleetHost { name, specs });
+    }
+    if hosts.is_empty() {
+        return Err(format!("no host spec files (*.json) in {}", dir.display()));
+    }
+    hosts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(hosts)
+}
+
+/// A model that must run somewhere in the fleet, `replicas` times.
+pub struct FleetDemand<'a> {
+    pub model: &'a LlmModel,
+    pub replicas: u32,
+}
+
+/// One replica assigned to a host.
+pub struct Placement {
+    pub model: String,
+    /// 1-based replica number.
+    pub replica: u32,
+    pub host: String,
+    /// Indices into the host's GPU cards holding (part of) the model.
+    pub gpu_indices: Vec<usize>,
+    pub vram_gb: f64,
+    pub ram_gb: f64,
+    /// Analysis against the memory that was still free at placement time.
+    pub fit: ModelFit,
+}
+
+/// Memory accounting for one host after planning.
+pub struct HostUsage {
+    pub name: String,
+    pub gpu_total_gb: Vec<f64>,
+    pub gpu_used_gb: Vec<f64>,
+    pub ram_total_gb: f64,
+    pub ram_used_gb: f64,
+    pub unified_memory: bool,
+}
+
+/// Replicas that could not be placed.
+pub struct Unplaced {
+    pub model: String,
+    pub replicas: u32,
+    pub reason: String,
+}
+
+pub struct FleetPlan {
+    pub placements: Vec<Placement>,
+    pub hosts: Vec<HostUsage>,
+    pub unplaced: Vec<Unplaced>,
+}
+
+/// Free memory on a host while the plan is being built.
+#[derive(Clone)]
+struct HostState {
+    cards: Vec<f64>,
+    ram_free: f64,
+    models: Vec<String>,
+}
+
+struct Candidate {
+    host: usize,
+    allocation: Vec<(usize, f64)>,
+    ram_gb: f64,
+    fit: ModelFit,
+    leftover_gb: f64,
+}
+
+/// Assign every replica to a host, largest models first. A replica goes to
+/// the host where it runs in the best mode (GPU before offload), preferring
+/// hosts that don't already run the same model and then the tightest fit.
+pub fn plan_fleet(hosts: &[FleetHost], demands: &[FleetDemand], min_fit: FitLevel) -> FleetPlan {
+    let fresh: Vec<HostState> = hosts
+        .iter()
+        .map(|h| HostState {
+            cards: h.gpu_cards(),
+            ram_free: h.specs.available_ram_gb,
+            models: Vec::new(),
+        })
+        .collect();
+    let mut states = fresh.clone();
+
+    let mut order: Vec<&FleetDemand> = demands.iter().collect();
+    order.sort_by(|a, b| {
+        footprint(b.model)
+            .partial_cmp(&footprint(a.model))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut placements = Vec::new();
+    let mut unplaced = Vec::new();
+    for demand in order {
+        let mut missing = 0;
+        for replica in 1..=demand.replicas {
+            let best = hosts
+                .iter()
+                .enumerate()
+                .filter_map(|(i, h)| candidate(i, h, &states[i], demand.model, min_fit))
+                .min_by(|a, b| compare_candidates(a, b, &states, &demand.model.name));
+            let Some(c) = best else {
+                missing += 1;
+                continue;
+            };
+
+            let state = &mut states[c.host];
+            for &(card, gb) in &c.allocation {
+                state.cards[card] -= gb;
+            }
+            state.ram_free -= c.ram_gb;
+            state.models.push(demand.model.name.clone());
+
+            placements.push(Placement {
+                model: demand.model.name.clone(),
+                replica,
+                host: hosts[c.host].name.clone(),
+                gpu_indices: c.allocation.iter().map(|&(card, _)| card).collect(),
+                vram_gb: c.allocation.iter().map(|&(_, gb)| gb).sum(),
+                ram_gb: c.ram_gb,
+                fit: c.fit,
+            });
+        }
+
+        if missing > 0 {
+            let fits_empty_host = hosts
+                .iter()
+                .enumerate()
+                .any(|(i, h)| candidate(i, h, &fresh[i], demand.model, min_fit).is_some());
+            let reason = if fits_empty_host {
+                "not enough free memory left on any host"
+            } else {
+                "does not fit on any host at the requested fit level"
+            };
+            unplaced.push(Unplaced {
+                model: demand.model.name.clone(),
+                replicas: missing,
+                reason: reason.to_string(),
+            });
+        }
+    }
+
+    placements.sort_by(|a, b| {
+        a.host
+            .cmp(&b.host)
+            .then_with(|| a.model.cmp(&b.model))
+            .then(a.replica.cmp(&b.replica))
+    });
+
+    let hosts = hosts
+        .iter()
+        .zip(&states)
+        .map(|(h, s)| {
+            let gpu_total_gb = h.gpu_cards();
+            let gpu_used_gb = gpu_total_gb
+                .iter()
+                .zip(&s.cards)
+                .map(|(total, free)| total - free)
+                .collect();
+            HostUsage {
+                name: h.name.clone(),
+                gpu_total_gb,
+                gpu_used_gb,
+                ram_total_gb: h.specs.available_ram_gb,
+                ram_used_gb: h.specs.available_ram_gb - s.ram_free,
+                unified_memory: h.specs.unified_memory,
+            }
+        })
+        .collect();
+
+    FleetPlan {
+        placements,
+        hosts,
+        unplaced,
+    }
+}
+
+/// Rough size used to order placements (largest first).
+fn footprint(model: &LlmModel) -> f64 {
+    model.min_vram_gb.unwrap_or(model.min_ram_gb)
+}
+
+/// Specs for a host restricted to what is still free: `vram_gb` pooled
+/// over `gpu_count` cards, reported per card as detection does.
+fn budget_specs(specs: &SystemSpecs, vram_gb: f64, gpu_count: u32, ram_gb: f64) -> SystemSpecs {
+    let mut budget = specs.clone();
+    budget.gpu_vram_gb = Some(vram_gb / gpu_count.max(1) as f64);
+    budget.gpu_count = gpu_count;
+    budget.available_ram_gb = if specs.unified_memory {
+        vram_gb
+    } else {
+        ram_gb
+    };
+    budget
+}
+
+/// Disk streaming never counts: a serving fleet can't share a host's page
+/// cache between replicas, and the speed is unusable for serving anyway.
+fn meets(fit: &ModelFit, min_fit: FitLevel) -> bool {
+    fit.fit_level != FitLevel::TooTight
+        && fit.run_mode != RunMode::DiskStream
+        && fit.fit_level.rank() <= min_fit.rank()
+}
+
+/// The best way to run `model` on one host given its remaining memory.
+fn candidate(
+    index: usize,
+    host: &FleetHost,
+    state: &HostState,
+    model: &LlmModel,
+    min_fit: FitLevel,
+) -> Option<Candidate> {
+    if state.cards.is_empty() {
+        let mut specs = host.specs.clone();
+        specs.available_ram_gb = state.ram_free;
+        let fit = ModelFit::analyze(model, &specs);
+        return meets(&fit, min_fit).then(|| Candidate {
+            host: index,
+            allocation: Vec::new(),
+            ram_gb: fit.memory_required_gb,
+            leftover_gb: state.ram_free - fit.memory_required_gb,
+            fit,
+        });
+    }
+
+    // Single card, tightest first so large cards stay free for large models.
+    let mut by_free: Vec<usize> = (0..state.cards.len()).collect();
+    by_free.sort_by(|&a, &b| {
+        state.cards[a]
+            .partial_cmp(&state.cards[b])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    for &card in &by_free {
+        let free = state.cards[card];
+        let fit = ModelFit::analyze(model, &budget_specs(&host.specs, free, 1, state.ram_free));
+        if fit.run_mode == RunMode::Gpu && meets(&fit, min_fit) {
+            return Some(Candidate {
+                host: index,
+                allocation: vec![(card, fit.memory_required_gb)],
+                ram_gb: 0.0,
+                leftover_gb: free - fit.memory_required_gb,
+                fit,
+            });
+        }
+    }
+
+    // Split across the host's cards, filling the emptiest ones first.
+    let usable: Vec<usize> = by_free
+        .iter()
+        .rev()
+        .copied()
+        .filter(|&c| state.cards[c] > 0.0)
+        .collect();
+    if usable.len() > 1 {
+        let pooled: f64 = usable.iter().map(|&c| state.cards[c]).sum();
+        let fit = ModelFit::analyze(
+            model,
+            &budget_specs(&host.specs, pooled, usable.len() as u32, state.ram_free),
+        );
+        if fit.run_mode == RunMode::Gpu && meets(&fit, min_fit) {
+            let mut remaining = fit.memory_required_gb;
+            let mut allocation = Vec::new();
+            for &card in &usable {
+                if remaining <= 0.0 {
+                    break;
+                }
+                let take = remaining.min(state.cards[card]);
+                allocation.push((card, take));
+                remaining -= take;
+            }
+            return Some(Candidate {
+                host: index,
+                allocation,
+                ram_gb: 0.0,
+                leftover_gb: pooled - fit.memory_required_gb,
+                fit,
+            });
+        }
+    }
+
+    // Offload paths use the card with the most free VRAM.
+    let card = *by_free.last()?;
+    let free = state.cards[card];
+    let fit = ModelFit::analyze(model, &budget_specs(&host.specs, free, 1, state.ram_free));
+    if !meets(&fit, min_fit) {
+        return None;
+    }
+    let (allocation, ram_gb) = match fit.run_mode {
+        RunMode::Gpu => (vec![(card, fit.memory_required_gb)], 0.0),
+        RunMode::MoeOffload => (
+            vec![(card, fit.memory_required_gb)],
+            fit.moe_offloaded_gb.unwrap_or(0.0),
+        ),
+        // Offloaded layers fill whatever the card has left
+        RunMode::CpuOffload => (vec![(card, free)], fit.memory_required_gb),
+        RunMode::CpuOnly | RunMode::Npu | RunMode::DiskStream => {
+            (Vec::new(), fit.memory_required_gb)
+        }
+    };
+    Some(Candidate {
+        host: index,
+        allocation,
+        ram_gb,
+        leftover_gb: state.ram_free - ram_gb,
+        fit,
+    })
+}
+
+fn compare_candidates(
+    a: &Candidate,
+    b: &Candidate,
+    states: &[HostState],
+    model_name: &str,
+) -> std::cmp::Ordering {
+    let mode_rank = |c: &Candidate| match c.fit.run_mode {
+        RunMode::Gpu => 0,
+        RunMode::MoeOffload => 1,
+        RunMode::CpuOffload => 2,
+        RunMode::Npu => 3,
+        RunMode::CpuOnly => 4,
+        RunMode::DiskStream => 5,
+    };
+    let colocated = |c: &Candidate| states[c.host].models.iter().any(|m| m == model_name);
+    mode_rank(a)
+        .cmp(&mode_rank(b))
+        .then(colocated(a).cmp(&colocated(b)))
+        .then_with(|| {
+            a.leftover_gb
+                .partial_cmp(&b.leftover_gb)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .then(a.host.cmp(&b.host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::{GpuBackend, GpuInfo};
+
+    fn test_model(name: &str, min_vram: f64) -> LlmModel {
+        LlmModel {
+            name: name.to_string(),
+            provider: "Test".to_string(),
+            parameter_count: "7B".to_string(),
+            parameters_raw: Some(7_000_000_000),
+            min_ram_gb: min_vram * 1.1,
+            recommended_ram_gb: min_vram * 1.2,
+            min_vram_gb: Some(min_vram),
+            quantization: "Q4_K_M".to_string(),
+            context_length: 8192,
+            use_case: "General".to_string(),
+            is_moe: false,
+            num_experts: None,
+            active_experts: None,
+            active_parameters: None,
+        }
+    }
+
+    fn gpu_host(name: &str, vram: f64, count: u32, ram: f64) -> FleetHost {
+        FleetHost {
+            name: name.to_string(),
+            specs: SystemSpecs {
+                total_ram_gb: ram,
+                available_ram_gb: ram,
+                total_cpu_cores: 16,
+                cpu_name: "Test CPU".to_string(),
+                has_gpu: true,
+                gpu_vram_gb: Some(vram),
+                gpu_name: Some("Test GPU".to_string()),
+                gpu_count: count,
+                unified_memory: false,
+                backend: GpuBackend::Cuda,
+                gpus: vec![GpuInfo {
+                    name: "Test GPU".to_string(),
+                    vram_gb: Some(vram),
+                    backend: GpuBackend::Cuda,
+                    count,
+                    unified_memory: false,
+                    vram_used_gb: None,
+                    provenance: Vec::new(),
+                    pcie_link: None,
+                    memory_bandwidth_gb_s: None,
+                }],
+                cgroup_limits: None,
+                storage: None,
+                cpu_features: None,
+                memory_topology: None,
+                detection: Default::default(),
+                board: None,
+                npus: Vec::new(),
+                power: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_models_share_a_card() {
+        let hosts = vec![gpu_host("a", 24.0, 1, 8.0)];
+        let small = test_model("Small", 6.0);
+        let medium = test_model("Medium", 10.0);
+        let demands = vec![
+            FleetDemand {
+                model: &small,
+                replicas: 1,
+            },
+            FleetDemand {
+                model: &medium,
+                replicas: 1,
+            },
+        ];
+        let plan = plan_fleet(&hosts, &demands, FitLevel::Marginal);
+
+        assert!(plan.unplaced.is_empty());
+        assert_eq!(plan.placements.len(), 2);
+        assert!(plan.placements.iter().all(|p| p.gpu_indices == vec![0]));
+        assert_eq!(plan.hosts[0].gpu_used_gb, vec![16.0]);
+    }
+
+    #[test]
+    fn test_vram_in_use_is_not_planned() {
+        let mut busy = gpu_host("busy", 24.0, 1, 8.0);
+        busy.specs.gpus[0].vram_used_gb = Some(14.0);
+        assert_eq!(busy.gpu_cards(), vec![10.0]);
+
+        let model = test_model("Medium", 16.0);
+        let demands = vec![FleetDemand {
+            model: &model,
+            replicas: 1,
+        }];
+        let plan = plan_fleet(
+            &[busy, gpu_host("idle", 24.0, 1, 8.0)],
+            &demands,
+            FitLevel::Marginal,
+        );
+        assert_eq!(plan.placements[0].host, "idle");
+    }
+
+    #[test]
+    fn test_replicas_spread_across_hosts() {
+        let hosts = vec![gpu_host("a", 24.0, 1, 8.0), gpu_host("b", 24.0, 1, 8.0)];
+        let model = test_model("Small", 6.0);
+        let demands = vec![FleetDemand {
+            model: &model,
+            replicas: 2,
+        }];
+        let plan = plan_fleet(&hosts, &demands, FitLevel::Marginal);
+
+        let hosts_used: Vec<&str> = plan.placements.iter().map(|p| p.host.as_str()).collect();
+        assert_eq!(hosts_used, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_large_model_splits_across_cards() {
+        let hosts = vec![gpu_host("a", 24.0, 2, 8.0)];
+        let model = test_model("Large", 40.0);
+        let demands = vec![FleetDemand {
+            model: &model,
+            replicas: 1,
+        }];
+        let plan = plan_fleet(&hosts, &demands, FitLevel::Marginal);
+
+        assert_eq!(plan.placements.len(), 1);
+        assert_eq!(plan.placements[0].gpu_indices.len(), 2);
+        assert_eq!(plan.placements[0].vram_gb, 40.0);
+    }
+
+    #[test]
+    fn test_cpu_offload_books_the_card() {
+        let hosts = vec![gpu_host("a", 24.0, 1, 64.0)];
+        let model = test_model("Big", 30.0);
+        let demands = vec![FleetDemand {
+            model: &model,
+            replicas: 1,
+        }];
+        let plan = plan_fleet(&hosts, &demands, FitLevel::Marginal);
+
+        assert_eq!(plan.placements.len(), 1);
+        assert_eq!(plan.placements[0].gpu_indices, vec![0]);
+        assert_eq!(plan.hosts[0].gpu_used_gb, vec![24.0]);
+    }
+
+    #[test]
+    fn test_unplaceable_replicas_are_reported() {
+        let hosts = vec![gpu_host("a", 24.0, 1, 8.0)];
+        let model = test_model("Medium", 16.0);
+        let huge = test_model("Huge", 200.0);
+        let demands = vec![
+            FleetDemand {
+                model: &model,
+                replicas: 2,
+            },
+            FleetDemand {
+                model: &huge,
+                replicas: 1,
+            },
+        ];
+        let plan = plan_fleet(&hosts, &demands, FitLevel::Marginal);
+
+        assert_eq!(plan.placements.len(), 1);
+        assert_eq!(plan.unplaced.len(), 2);
+        let medium = plan.unplaced.iter().find(|u| u.model == "Medium").unwrap();
+        assert_eq!(medium.replicas, 1);
+        assert!(medium.reason.contains("free memory"));
+        let huge = plan.unplaced.iter().find(|u| u.model == "Huge").unwrap();
+        assert!(huge.reason.contains("does not fit"));
+    }
+
+    #[test]
+    fn test_load_fleet_dir_reads_json_files() {
+        let dir = std::env::temp_dir().join(format!("llmfit-fleet-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let spec = serde_json::json!({
+            "system": {
+                "total_ram_gb": 32.0,
+                "available_ram_gb": 24.0,
+                "cpu_cores": 8,
+                "backend": "CPU (x86)",
+            }
+        });
+        std::fs::write(dir.join("beta.json"), spec.to_string()).unwrap();
+        std::fs::write(dir.join("alpha.json"), spec.to_string()).unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let hosts = load_fleet_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<&str> = hosts.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha", "beta"]);
+        assert!(hosts[0].gpu_cards().is_empty());
+    }
+}
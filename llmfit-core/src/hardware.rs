@@ -1,8 +1,12 @@
-use std::collections::BTreeMap;
-use sysinfo::System;
+use crate::probe::{
+    CACHE_TTL, HardwareDump, LiveProbe, Probe, ProbeCache, ReplayProbe, SysInfoSnapshot,
+    command_key,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
 
 /// The acceleration backend for inference speed estimation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum GpuBackend {
     Cuda,
     Metal,
@@ -25,6 +29,20 @@ impl GpuBackend {
             GpuBackend::CpuX86 => "CPU (x86)",
         }
     }
+
+    /// Inverse of `label()`, used when reading saved `llmfit --json system` output.
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "CUDA" => Some(GpuBackend::Cuda),
+            "Metal" => Some(GpuBackend::Metal),
+            "ROCm" => Some(GpuBackend::Rocm),
+            "Vulkan" => Some(GpuBackend::Vulkan),
+            "SYCL" => Some(GpuBackend::Sycl),
+            "CPU (ARM)" => Some(GpuBackend::CpuArm),
+            "CPU (x86)" => Some(GpuBackend::CpuX86),
+            _ => None,
+        }
+    }
 }
 
 /// Information about a single detected GPU.
@@ -35,6 +53,234 @@ pub struct GpuInfo {
     pub backend: GpuBackend,
     pub count: u32, // >1 for same-model multi-GPU (e.g. 2x RTX 4090)
     pub unified_memory: bool,
+    /// VRAM already in use per card, when the driver reports it. For grouped
+    /// same-model cards this is the busiest card.
+    pub vram_used_gb: Option<f64>,
+    /// Where the name and VRAM figure came from, including sources that
+    /// were tried and gave nothing.
+    pub provenance: Vec<ProbeStep>,
+    /// PCIe link to the host, for discrete cards. Offloaded layers and
+    /// experts cross it.
+    pub pcie_link: Option<PcieLink>,
+    /// Peak VRAM bandwidth per card in GB/s, when known (advisor catalog
+    /// cards, JSON specs). Decoding on the card is bound by it.
+    pub memory_bandwidth_gb_s: Option<f64>,
+}
+
+impl GpuInfo {
+    /// Per-card VRAM not currently in use, if usage is known.
+    pub fn vram_free_gb(&self) -> Option<f64> {
+        let total = self.vram_gb?;
+        let used = self.vram_used_gb?;
+        Some((total - used).max(0.0))
+    }
+}
+
+/// Per-direction PCIe bandwidth a Gen4 x16 slot gives, GB/s. Offload
+/// speeds are modelled relative to it.
+pub const PCIE_REFERENCE_GB_S: f64 = 31.5;
+
+/// A negotiated PCIe link: generation and lane count.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PcieLink {
+    pub generation: u8,
+    pub width: u8,
+}
+
+impl PcieLink {
+    /// Parse sysfs `max_link_speed` ("16.0 GT/s PCIe", "8 GT/s") and
+    /// `max_link_width` ("16"), or their `current_link_*` counterparts.
+    pub fn from_sysfs(speed: &str, width: &str) -> Option<Self> {
+        let gt_s: f64 = speed.split_whitespace().next()?.parse().ok()?;
+        let generation = match gt_s {
+            s if s >= 64.0 => 6,
+            s if s >= 32.0 => 5,
+            s if s >= 16.0 => 4,
+            s if s >= 8.0 => 3,
+            s if s >= 5.0 => 2,
+            _ => 1,
+        };
+        let width: u8 = width.trim().parse().ok()?;
+        (width > 0).then_some(PcieLink { generation, width })
+    }
+
+    /// Usable bandwidth per direction, GB/s (after line encoding).
+    pub fn bandwidth_gb_s(&self) -> f64 {
+        let per_lane = match self.generation {
+            1 => 0.25,
+            2 => 0.5,
+            3 => 0.985,
+            4 => 1.969,
+            5 => 3.938,
+            _ => 7.563,
+        };
+        per_lane * self.width as f64
+    }
+
+    /// "PCIe 4.0 x16"
+    pub fn label(&self) -> String {
+        format!("PCIe {}.0 x{}", self.generation, self.width)
+    }
+
+    /// The slower generation and narrower width of two link ends.
+    fn min(self, other: PcieLink) -> PcieLink {
+        PcieLink {
+            generation: self.generation.min(other.generation),
+            width: self.width.min(other.width),
+        }
+    }
+}
+
+/// PCI vendor ID of a detected GPU, for matching it to sysfs devices.
+fn pci_vendor_id(gpu: &GpuInfo) -> Option<&'static str> {
+    let name = gpu.name.to_lowercase();
+    match gpu.backend {
+        GpuBackend::Cuda => Some("0x10de"),
+        GpuBackend::Rocm => Some("0x1002"),
+        GpuBackend::Sycl => Some("0x8086"),
+        GpuBackend::Vulkan if name.contains("radeon") || name.contains("amd") => Some("0x1002"),
+        GpuBackend::Vulkan if name.contains("intel") || name.contains("arc") => Some("0x8086"),
+        _ => None,
+    }
+}
+
+/// Record `link` under `key` unless a slower one is already there.
+fn keep_slowest(links: &mut BTreeMap<String, PcieLink>, key: String, link: PcieLink) {
+    let slot = links.entry(key).or_insert(link);
+    if link.bandwidth_gb_s() < slot.bandwidth_gb_s() {
+        *slot = link;
+    }
+}
+
+/// Deserialize an optional field of a specs JSON file. Absent or
+/// null is `None`; anything present that doesn't parse is an error naming
+/// the field, so a hand-edited file can't silently lose it.
+fn json_field<T: serde::de::DeserializeOwned>(
+    value: &serde_json::Value,
+    key: &str,
+) -> Result<Option<T>, String> {
+    match value.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(field) => serde_json::from_value(field.clone())
+            .map(Some)
+            .map_err(|e| format!("invalid field '{}': {e}", key)),
+    }
+}
+
+/// RAM an APU's unified pool leaves to the OS and CPU-side processes.
+const APU_OS_RESERVE_GB: f64 = 4.0;
+
+/// GPU-usable memory on an AMD APU: the VRAM carve-out plus the GTT window
+/// into system RAM. GTT is system RAM, so it is capped to leave the OS some.
+fn amd_apu_pool_gb(carveout_gb: f64, gtt_gb: f64, total_ram_gb: f64) -> f64 {
+    carveout_gb + gtt_gb.min((total_ram_gb - APU_OS_RESERVE_GB).max(0.0))
+}
+
+/// Per-card (total, used) VRAM bytes, keyed like rocm-smi's JSON (`card0`).
+type CardMemory = BTreeMap<String, (Option<u64>, Option<u64>)>;
+
+/// One AMD card's readings, before same-model cards are grouped.
+struct AmdCard {
+    name: String,
+    vram_gb: Option<f64>,
+    vram_used_gb: Option<f64>,
+    provenance: Vec<ProbeStep>,
+}
+
+/// One step detection took towards a figure: what was consulted, and what
+/// it reported or why it gave nothing.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProbeStep {
+    /// What was consulted, e.g. `nvidia-smi` or `sysfs mem_info_vram_total`.
+    pub source: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl ProbeStep {
+    pub fn ok(source: impl Into<String>, detail: impl Into<String>) -> Self {
+        ProbeStep {
+            source: source.into(),
+            ok: true,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn failed(source: impl Into<String>, detail: impl Into<String>) -> Self {
+        ProbeStep {
+            source: source.into(),
+            ok: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// How detection arrived at the RAM figures, and every GPU probe it ran.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DetectionTrail {
+    pub total_ram: Vec<ProbeStep>,
+    pub available_ram: Vec<ProbeStep>,
+    /// One entry per GPU probe, including those that found nothing.
+    pub gpu_probes: Vec<ProbeStep>,
+}
+
+/// Stdout of a command that ran successfully; otherwise records in `steps`
+/// why there was none.
+fn run_traced(
+    probe: &dyn Probe,
+    program: &str,
+    args: &[&str],
+    source: &str,
+    steps: &mut Vec<ProbeStep>,
+) -> Option<String> {
+    let reason = match probe.run(program, args) {
+        Some(output) if output.success => return Some(output.stdout),
+        Some(output) if output.timed_out => "timed out and was killed",
+        Some(_) => "exited with an error",
+        None => "not installed or could not be started",
+    };
+    steps.push(ProbeStep::failed(source, reason));
+    None
+}
+
+/// VRAM guessed from the GPU name, with the step recording the guess.
+fn estimate_vram_traced(name: &str) -> (Option<f64>, ProbeStep) {
+    let estimated = estimate_vram_from_name(name);
+    if estimated > 0.0 {
+        (
+            Some(estimated),
+            ProbeStep::ok(
+                "estimate_vram_from_name",
+                format!("{:.1} GB for \"{}\"", estimated, name),
+            ),
+        )
+    } else {
+        (
+            None,
+            ProbeStep::failed(
+                "estimate_vram_from_name",
+                format!("no known VRAM size for \"{}\"", name),
+            ),
+        )
+    }
+}
+
+/// Summary step for a GPU probe that ran.
+fn gpus_found_step(source: &str, gpus: &[GpuInfo]) -> ProbeStep {
+    if gpus.is_empty() {
+        return ProbeStep::failed(source, "ran but reported no GPUs");
+    }
+    let names: Vec<String> = gpus
+        .iter()
+        .map(|g| {
+            if g.count > 1 {
+                format!("{} x{}", g.name, g.count)
+            } else {
+                g.name.clone()
+            }
+        })
+        .collect();
+    ProbeStep::ok(source, format!("found {}", names.join(", ")))
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -51,32 +297,277 @@ pub struct SystemSpecs {
     pub backend: GpuBackend,
     /// All detected GPUs (may span different vendors/backends).
     pub gpus: Vec<GpuInfo>,
+    /// Container/cgroup limits that RAM and core counts were clamped to.
+    pub cgroup_limits: Option<CgroupLimits>,
+    /// Swap and disk speed, for models streamed from disk.
+    pub storage: Option<StorageInfo>,
+    /// Instruction-set extensions and physical cores, for CPU speed estimates.
+    pub cpu_features: Option<CpuFeatures>,
+    /// NUMA nodes and RAM type/speed, for bandwidth-bound CPU estimates.
+    pub memory_topology: Option<MemoryTopology>,
+    /// Which probes produced the RAM and GPU figures (`system --verbose`).
+    pub detection: DetectionTrail,
+    /// Jetson/SBC board from the device tree, with its bandwidth profile.
+    pub board: Option<BoardInfo>,
+    /// NPUs found alongside (not instead of) the GPUs.
+    pub npus: Vec<NpuInfo>,
+    /// Battery state and GPU power limits (laptops, power-capped GPUs).
+    pub power: Option<PowerState>,
+}
+
+/// `nvidia-smi` arguments for per-GPU memory and name.
+const NVIDIA_SMI_QUERY: &[&str] = &[
+    "--query-gpu=memory.total,name",
+    "--format=csv,noheader,nounits",
+];
+
+/// `nvidia-smi` arguments adding VRAM in use, for
+/// [`SystemSpecs::with_vram_usage`].
+const NVIDIA_USAGE_QUERY: &[&str] = &[
+    "--query-gpu=memory.total,memory.used,name",
+    "--format=csv,noheader,nounits",
+];
+
+/// `nvidia-smi` arguments for each GPU's PCIe link. The `max` fields are
+/// what the card and slot negotiated; `current` drops to Gen1 at idle.
+const NVIDIA_PCIE_QUERY: &[&str] = &[
+    "--query-gpu=pcie.link.gen.max,pcie.link.width.max,name",
+    "--format=csv,noheader,nounits",
+];
+
+/// `nvidia-smi` arguments for the enforced and maximum power limits.
+const NVIDIA_POWER_QUERY: &[&str] = &[
+    "--query-gpu=power.limit,power.max_limit",
+    "--format=csv,noheader,nounits",
+];
+
+/// `pmset` arguments reporting the power source on macOS.
+const PMSET_BATTERY_QUERY: &[&str] = &["-g", "batt"];
+
+/// Commands whose answers change when the charger is plugged in or pulled,
+/// so they are never served from the hardware cache.
+const POWER_COMMANDS: &[(&str, &[&str])] = &[
+    ("nvidia-smi", NVIDIA_POWER_QUERY),
+    ("pmset", PMSET_BATTERY_QUERY),
+];
+
+/// PowerShell arguments listing video controllers as `name|adapter RAM`.
+const WINDOWS_GPU_QUERY: &[&str] = &[
+    "-NoProfile",
+    "-Command",
+    "Get-CimInstance Win32_VideoController | Select-Object Name,AdapterRAM | ForEach-Object { $_.Name + '|' + $_.AdapterRAM }",
+];
+
+/// Commands detection will run on `os`, started together up front so the
+/// slowest one bounds detection time instead of their sum.
+fn slow_probe_commands(os: &str) -> Vec<(&'static str, &'static [&'static str])> {
+    let mut commands: Vec<(&'static str, &'static [&'static str])> = vec![
+        ("nvidia-smi", NVIDIA_SMI_QUERY),
+        ("nvidia-smi", NVIDIA_PCIE_QUERY),
+        ("nvidia-smi", NVIDIA_POWER_QUERY),
+    ];
+    match os {
+        "linux" => commands.extend([
+            ("rocm-smi", &["--showmeminfo", "vram", "--json"][..]),
+            ("rocm-smi", &["--showproductname", "--json"][..]),
+            ("lspci", &[][..]),
+            ("dmidecode", &["-t", "memory"][..]),
+        ]),
+        "macos" => commands.push(("system_profiler", &["SPDisplaysDataType"][..])),
+        "windows" => commands.push(("powershell", WINDOWS_GPU_QUERY)),
+        _ => {}
+    }
+    commands
 }
 
 impl SystemSpecs {
+    /// Detect the running machine, reusing command results cached by an
+    /// earlier run when they are younger than [`CACHE_TTL`].
     pub fn detect() -> Self {
-        let mut sys = System::new_all();
-        sys.refresh_all();
+        Self::detect_cached(false)
+    }
+
+    /// Like [`detect`](Self::detect); with `refresh` every command is run
+    /// again and the cache rewritten.
+    pub fn detect_cached(refresh: bool) -> Self {
+        let path = ProbeCache::default_path();
+        let cached = match &path {
+            Some(path) if !refresh => ProbeCache::load_fresh(path, CACHE_TTL),
+            _ => None,
+        };
+        let fresh = cached.is_none();
+        let probe = match cached {
+            Some(cache) => LiveProbe::with_cached_commands(cache.commands),
+            None => LiveProbe::new(),
+        };
+        let specs = Self::detect_with(&probe);
+        if let (true, Some(path)) = (fresh, path) {
+            let mut commands = probe.command_results();
+            for (program, args) in POWER_COMMANDS {
+                commands.remove(&command_key(program, args));
+            }
+            // A cache that can't be written only costs speed next time.
+            let _ = ProbeCache::new(commands).save(&path);
+        }
+        specs
+    }
+
+    /// Rebuild specs from a dump written by `llmfit system --dump`, exactly
+    /// as detection saw the machine it was recorded on.
+    pub fn detect_from_dump(path: &Path) -> Result<Self, String> {
+        let dump = HardwareDump::load(path)?;
+        let probe = ReplayProbe::new(dump);
+        Ok(Self::detect_with(&probe).with_vram_usage_from(&probe))
+    }
+
+    /// Read the VRAM each card has in use. Detection leaves it out because
+    /// it changes whenever a model loads, so it can't come from the cache;
+    /// this always queries the vendor tools afresh.
+    pub fn with_vram_usage(self) -> Self {
+        self.with_vram_usage_from(&LiveProbe::new())
+    }
+
+    /// Like [`with_vram_usage`](Self::with_vram_usage), reading through `probe`.
+    pub fn with_vram_usage_from(mut self, probe: &dyn Probe) -> Self {
+        let mut readings = Vec::new();
+        if self.gpus.iter().any(|g| g.backend == GpuBackend::Cuda)
+            && let Some(text) = probe.run_stdout("nvidia-smi", NVIDIA_USAGE_QUERY)
+        {
+            readings.extend(Self::parse_nvidia_smi_list(&text));
+        }
+        if self
+            .gpus
+            .iter()
+            .any(|g| g.backend == GpuBackend::Rocm && !g.unified_memory)
+        {
+            readings.extend(Self::detect_amd_gpus_rocm(probe, &mut Vec::new()));
+        }
+        // APUs read their usage from sysfs during detection.
+        for gpu in self.gpus.iter_mut().filter(|g| !g.unified_memory) {
+            if let Some(used) = readings
+                .iter()
+                .find(|r| r.backend == gpu.backend && r.name == gpu.name)
+                .and_then(|r| r.vram_used_gb)
+            {
+                gpu.vram_used_gb = Some(used);
+            }
+        }
+        self
+    }
+
+    /// Detect hardware, reading the machine only through `probe`.
+    pub fn detect_with(probe: &dyn Probe) -> Self {
+        let sys = std::thread::scope(|s| {
+            s.spawn(|| probe.prefetch(&slow_probe_commands(probe.os())));
+            probe.sysinfo()
+        });
 
-        let total_ram_bytes = sys.total_memory();
-        let available_ram_bytes = sys.available_memory();
-        let total_ram_gb = total_ram_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-        let available_ram_gb = if available_ram_bytes == 0 && total_ram_bytes > 0 {
+        let mut detection = DetectionTrail::default();
+        let total_ram_bytes = sys.total_memory;
+        let available_ram_bytes = sys.available_memory;
+        let mut total_ram_gb = total_ram_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+        detection.total_ram.push(ProbeStep::ok(
+            "sysinfo",
+            format!("{:.2} GB total", total_ram_gb),
+        ));
+        let mut available_ram_gb = if available_ram_bytes == 0 && total_ram_bytes > 0 {
             // sysinfo may fail to report available memory on some platforms
             // (e.g. macOS Tahoe / newer macOS versions). Try fallbacks.
-            Self::available_ram_fallback(&sys, total_ram_bytes, total_ram_gb)
+            detection
+                .available_ram
+                .push(ProbeStep::failed("sysinfo", "reported 0 GB available"));
+            Self::available_ram_fallback(
+                probe,
+                &sys,
+                total_ram_bytes,
+                total_ram_gb,
+                &mut detection.available_ram,
+            )
         } else {
-            available_ram_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+            let gb = available_ram_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+            detection
+                .available_ram
+                .push(ProbeStep::ok("sysinfo", format!("{:.2} GB available", gb)));
+            gb
         };
 
-        let total_cpu_cores = sys.cpus().len();
-        let cpu_name = sys
-            .cpus()
-            .first()
-            .map(|cpu| cpu.brand().to_string())
-            .unwrap_or_else(|| "Unknown CPU".to_string());
+        let mut total_cpu_cores = sys.cpu_count.max(1);
+
+        // In containers sysinfo sees the host; clamp to the cgroup's limits.
+        let cgroup_limits = CgroupLimits::detect(probe, sys.cpu_count);
+        if let Some(limits) = &cgroup_limits {
+            if let Some(limit) = limits.memory_limit_gb {
+                let source = format!("cgroup {} memory limit", limits.version);
+                if limit < total_ram_gb {
+                    detection.total_ram.push(ProbeStep::ok(
+                        source.clone(),
+                        format!("clamped to {:.2} GB", limit),
+                    ));
+                }
+                total_ram_gb = total_ram_gb.min(limit);
+                let headroom = (limit - limits.memory_usage_gb.unwrap_or(0.0)).max(0.0);
+                if headroom < available_ram_gb {
+                    detection.available_ram.push(ProbeStep::ok(
+                        source,
+                        format!("clamped to {:.2} GB of headroom", headroom),
+                    ));
+                }
+                available_ram_gb = available_ram_gb.min(headroom);
+            }
+            if let Some(cores) = limits.usable_cores() {
+                total_cpu_cores = total_cpu_cores.min(cores);
+            }
+        }
+
+        let cpu_features = CpuFeatures::detect(probe, &sys).map(|mut features| {
+            let physical = sys.physical_core_count.or(features.physical_cores);
+            features.physical_cores = physical.map(|p| p.min(total_cpu_cores));
+            features
+        });
+
+        let cpu_name = if sys.cpu_brand.is_empty() {
+            "Unknown CPU".to_string()
+        } else {
+            sys.cpu_brand.clone()
+        };
 
-        let gpus = Self::detect_all_gpus(available_ram_gb, &cpu_name);
+        let board = BoardInfo::detect(probe);
+        let mut gpus = Self::detect_all_gpus(
+            probe,
+            total_ram_gb,
+            available_ram_gb,
+            &cpu_name,
+            &mut detection.gpu_probes,
+        );
+        if let Some(board) = board.as_ref().filter(|b| b.tegra) {
+            // Jetson has no (or a memory-less) nvidia-smi; its GPU is the
+            // SoC's, sharing RAM like Apple Silicon.
+            gpus.retain(|g| g.backend != GpuBackend::Cuda);
+            detection.gpu_probes.push(ProbeStep::ok(
+                "device tree",
+                format!("found Tegra board {}", board.model),
+            ));
+            gpus.insert(
+                0,
+                GpuInfo {
+                    name: board.model.clone(),
+                    vram_gb: Some(available_ram_gb),
+                    backend: GpuBackend::Cuda,
+                    count: 1,
+                    unified_memory: true,
+                    vram_used_gb: None,
+                    provenance: vec![
+                        ProbeStep::ok("/proc/device-tree/model", board.model.clone()),
+                        ProbeStep::ok(
+                            "Tegra unified pool",
+                            format!("{:.2} GB of available RAM", available_ram_gb),
+                        ),
+                    ],
+                    pcie_link: None,
+                    memory_bandwidth_gb_s: None,
+                },
+            );
+        }
 
         // Primary GPU = the one with the most VRAM (best for inference).
         // For fit scoring, we use the primary GPU's VRAM pool.
@@ -87,12 +578,12 @@ impl SystemSpecs {
         let gpu_count = primary.map(|g| g.count).unwrap_or(0);
         let unified_memory = primary.map(|g| g.unified_memory).unwrap_or(false);
 
-        let cpu_backend =
-            if cfg!(target_arch = "aarch64") || cpu_name.to_lowercase().contains("apple") {
-                GpuBackend::CpuArm
-            } else {
-                GpuBackend::CpuX86
-            };
+        let cpu_backend = if probe.arch() == "aarch64" || cpu_name.to_lowercase().contains("apple")
+        {
+            GpuBackend::CpuArm
+        } else {
+            GpuBackend::CpuX86
+        };
         let backend = primary.map(|g| g.backend).unwrap_or(cpu_backend);
 
         SystemSpecs {
@@ -107,27 +598,58 @@ impl SystemSpecs {
             unified_memory,
             backend,
             gpus,
+            cgroup_limits,
+            storage: StorageInfo::detect(probe),
+            cpu_features,
+            memory_topology: MemoryTopology::detect(probe),
+            detection,
+            board,
+            npus: NpuInfo::detect(probe),
+            power: PowerState::detect(probe),
         }
     }
 
     /// Detect all GPUs across all vendors. Returns a Vec sorted by VRAM descending
     /// (best GPU first). Unlike the old cascade, this does NOT short-circuit:
     /// a system with both NVIDIA and AMD GPUs will report both.
-    fn detect_all_gpus(available_ram_gb: f64, cpu_name: &str) -> Vec<GpuInfo> {
+    /// Every probe run is recorded in `trail`, whether or not it found a GPU.
+    fn detect_all_gpus(
+        probe: &dyn Probe,
+        total_ram_gb: f64,
+        available_ram_gb: f64,
+        cpu_name: &str,
+        trail: &mut Vec<ProbeStep>,
+    ) -> Vec<GpuInfo> {
         let mut gpus = Vec::new();
 
         // NVIDIA GPUs via nvidia-smi
-        gpus.extend(Self::detect_nvidia_gpus());
+        gpus.extend(Self::detect_nvidia_gpus(probe, trail));
 
-        // AMD GPUs via rocm-smi or sysfs
-        if let Some(amd) = Self::detect_amd_gpu_rocm_info() {
-            gpus.push(amd);
-        } else if let Some(amd) = Self::detect_amd_gpu_sysfs_info() {
-            gpus.push(amd);
+        // AMD GPUs via rocm-smi, or sysfs when ROCm isn't installed. APUs
+        // always come from sysfs, the only place their GTT pool is exposed.
+        let mut rocm = Self::detect_amd_gpus_rocm(probe, trail);
+        // Its VRAM figures may be cached, so usage is left to `with_vram_usage`.
+        for gpu in &mut rocm {
+            gpu.vram_used_gb = None;
+        }
+        let apu_backend = if rocm.is_empty() {
+            GpuBackend::Vulkan
+        } else {
+            GpuBackend::Rocm
+        };
+        let (discrete, apus) = Self::detect_amd_gpus_sysfs(probe, total_ram_gb, apu_backend, trail);
+        if rocm.is_empty() {
+            gpus.extend(discrete);
+        } else {
+            gpus.extend(
+                rocm.into_iter()
+                    .filter(|g| apus.is_empty() || !Self::is_amd_apu(&g.name, g.vram_gb)),
+            );
         }
+        gpus.extend(apus);
 
         // Windows WMI (catches GPUs not found by vendor-specific tools)
-        for wmi_gpu in Self::detect_gpu_windows_info() {
+        for wmi_gpu in Self::detect_gpu_windows_info(probe, trail) {
             // Skip if we already found a GPU with the same name from a vendor tool
             let dominated = gpus.iter().any(|existing| {
                 let existing_lower = existing.name.to_lowercase();
@@ -140,7 +662,8 @@ impl SystemSpecs {
         }
 
         // Intel Arc via sysfs
-        if let Some(vram) = Self::detect_intel_gpu() {
+        if let Some((vram, step)) = Self::detect_intel_gpu(probe) {
+            trail.push(ProbeStep::ok("Intel sysfs/lspci", "found Intel Arc"));
             let already_found = gpus.iter().any(|g| g.name.to_lowercase().contains("intel"));
             if !already_found {
                 gpus.push(GpuInfo {
@@ -149,12 +672,21 @@ impl SystemSpecs {
                     backend: GpuBackend::Sycl,
                     count: 1,
                     unified_memory: false,
+                    vram_used_gb: None,
+                    provenance: vec![step],
+                    pcie_link: None,
+                    memory_bandwidth_gb_s: None,
                 });
             }
+        } else {
+            trail.push(ProbeStep::failed(
+                "Intel sysfs/lspci",
+                "no Intel Arc GPU found",
+            ));
         }
 
         // Apple Silicon (unified memory)
-        if let Some(vram) = Self::detect_apple_gpu(available_ram_gb) {
+        if let Some(vram) = Self::detect_apple_gpu(probe, available_ram_gb, trail) {
             let name = if cpu_name.to_lowercase().contains("apple") {
                 cpu_name.to_string()
             } else {
@@ -166,72 +698,257 @@ impl SystemSpecs {
                 backend: GpuBackend::Metal,
                 count: 1,
                 unified_memory: true,
+                vram_used_gb: None,
+                provenance: vec![ProbeStep::ok(
+                    "system_profiler",
+                    format!("Apple GPU, unified memory of {:.2} GB", vram),
+                )],
+                pcie_link: None,
+                memory_bandwidth_gb_s: None,
             });
         }
 
-        // Sort by VRAM descending so the best GPU is primary
+        Self::attach_pcie_links(probe, &mut gpus);
+        Self::attach_memory_bandwidth(&mut gpus);
+
+        // Sort by VRAM descending so the best GPU is primary. Discrete cards
+        // go first: an APU's unified pool is larger but much slower.
         gpus.sort_by(|a, b| {
             let va = a.vram_gb.unwrap_or(0.0);
             let vb = b.vram_gb.unwrap_or(0.0);
-            vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal)
+            a.unified_memory
+                .cmp(&b.unified_memory)
+                .then(vb.partial_cmp(&va).unwrap_or(std::cmp::Ordering::Equal))
         });
 
         gpus
     }
 
-    /// Detect NVIDIA GPUs via nvidia-smi. Returns one GpuInfo per unique model,
-    /// with count and per-card VRAM for same-model multi-GPU setups.
-    fn detect_nvidia_gpus() -> Vec<GpuInfo> {
-        let output = match std::process::Command::new("nvidia-smi")
-            .arg("--query-gpu=memory.total,name")
-            .arg("--format=csv,noheader,nounits")
-            .output()
+    /// Fill `pcie_link` on discrete GPUs: per model from nvidia-smi, else
+    /// the slowest link among the vendor's display controllers in sysfs.
+    fn attach_pcie_links(probe: &dyn Probe, gpus: &mut [GpuInfo]) {
+        let is_nvidia = |g: &GpuInfo| g.backend == GpuBackend::Cuda && !g.unified_memory;
+        if gpus.iter().any(is_nvidia)
+            && let Some(text) = probe.run_stdout("nvidia-smi", NVIDIA_PCIE_QUERY)
+        {
+            let links = Self::parse_nvidia_pcie_links(&text);
+            for gpu in gpus.iter_mut().filter(|g| is_nvidia(g)) {
+                if let Some(&link) = links.get(&gpu.name) {
+                    gpu.pcie_link = Some(link);
+                    gpu.provenance
+                        .push(ProbeStep::ok("nvidia-smi pcie.link", link.label()));
+                }
+            }
+        }
+
+        if gpus
+            .iter()
+            .all(|g| g.unified_memory || g.pcie_link.is_some())
         {
-            Ok(o) if o.status.success() => o,
-            _ => return Vec::new(),
+            return;
+        }
+        let devices = Self::sysfs_pcie_links(probe);
+        // lspci names the device in each slot, telling one vendor's cards apart.
+        let names = if devices.len() > 1 {
+            probe
+                .run_stdout("lspci", &[])
+                .map(|text| Self::parse_gpu_names_lspci(&text))
+                .unwrap_or_default()
+        } else {
+            Vec::new()
         };
+        let mut entries_per_vendor: BTreeMap<&str, usize> = BTreeMap::new();
+        for vendor in gpus
+            .iter()
+            .filter(|g| !g.unified_memory)
+            .filter_map(pci_vendor_id)
+        {
+            *entries_per_vendor.entry(vendor).or_default() += 1;
+        }
+        for gpu in gpus
+            .iter_mut()
+            .filter(|g| !g.unified_memory && g.pcie_link.is_none())
+        {
+            let Some(vendor) = pci_vendor_id(gpu) else {
+                continue;
+            };
+            let same_vendor: Vec<&(String, String, PcieLink)> =
+                devices.iter().filter(|(_, v, _)| v == vendor).collect();
+            let gpu_name = gpu.name.to_lowercase();
+            let named: Vec<&(String, String, PcieLink)> = same_vendor
+                .iter()
+                .copied()
+                .filter(|(slot, _, _)| {
+                    names.iter().any(|(s, name)| {
+                        let name = name.to_lowercase();
+                        !s.is_empty()
+                            && slot.ends_with(s.as_str())
+                            && (gpu_name.contains(&name) || name.contains(&gpu_name))
+                    })
+                })
+                .collect();
+            // Unnamed devices are only this GPU's if no other entry shares its vendor.
+            let candidates = if !named.is_empty() {
+                named
+            } else if entries_per_vendor.get(vendor) == Some(&1) {
+                same_vendor
+            } else {
+                continue;
+            };
+            // Grouped cards report their slowest link.
+            if let Some(link) = candidates
+                .iter()
+                .map(|(_, _, link)| *link)
+                .min_by(|a, b| a.bandwidth_gb_s().total_cmp(&b.bandwidth_gb_s()))
+            {
+                gpu.pcie_link = Some(link);
+                gpu.provenance
+                    .push(ProbeStep::ok("sysfs max_link_speed", link.label()));
+            }
+        }
+    }
+
+    /// Fill `memory_bandwidth_gb_s` on discrete GPUs from the advisor's
+    /// catalog, matching the longest catalog name the detected name contains
+    /// ("RTX 4070 Ti" before "RTX 4070"), so detected machines and advisor
+    /// configurations share one speed formula.
+    fn attach_memory_bandwidth(gpus: &mut [GpuInfo]) {
+        let catalog = crate::advisor::default_gpu_catalog();
+        for gpu in gpus.iter_mut().filter(|g| !g.unified_memory) {
+            let name = gpu.name.to_lowercase();
+            gpu.memory_bandwidth_gb_s = catalog
+                .iter()
+                .filter(|entry| name.contains(&entry.name.to_lowercase()))
+                .max_by_key(|entry| entry.name.len())
+                .map(|entry| entry.bandwidth_gb_s);
+        }
+    }
+
+    /// Parse `nvidia-smi --query-gpu=pcie.link.gen.max,pcie.link.width.max,name`
+    /// into the slowest link per model.
+    fn parse_nvidia_pcie_links(text: &str) -> BTreeMap<String, PcieLink> {
+        let mut links: BTreeMap<String, PcieLink> = BTreeMap::new();
+        for line in text.lines() {
+            let mut fields = line.splitn(3, ',').map(str::trim);
+            let (Some(generation), Some(width), Some(name)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let (Ok(generation), Ok(width)) = (generation.parse::<u8>(), width.parse::<u8>())
+            else {
+                continue;
+            };
+            keep_slowest(&mut links, name.to_string(), PcieLink { generation, width });
+        }
+        links
+    }
+
+    /// (PCI slot, vendor ID such as "0x10de", link) of every display
+    /// controller under `/sys/bus/pci/devices`. Idle GPUs drop their current
+    /// link to Gen1 to save power, so this is the device's maximum link
+    /// capped by its upstream bridge's (the slot), falling back to the
+    /// current link.
+    fn sysfs_pcie_links(probe: &dyn Probe) -> Vec<(String, String, PcieLink)> {
+        let mut links = Vec::new();
+        if probe.os() != "linux" {
+            return links;
+        }
+        let root = Path::new("/sys/bus/pci/devices");
+        for device in probe.read_dir(root).unwrap_or_default() {
+            let read = |file: &str| probe.read_file(&root.join(&device).join(file));
+            // PCI class 0x03xxxx: VGA, 3D and display controllers.
+            if !read("class").is_some_and(|c| c.trim().starts_with("0x03")) {
+                continue;
+            }
+            let Some(vendor) = read("vendor") else {
+                continue;
+            };
+            // `<device>/..` resolves through the sysfs symlink to the bridge.
+            let link_at = |prefix: &str, kind: &str| {
+                PcieLink::from_sysfs(
+                    &read(&format!("{}{}_link_speed", prefix, kind))?,
+                    &read(&format!("{}{}_link_width", prefix, kind))?,
+                )
+            };
+            let link = match (link_at("", "max"), link_at("../", "max")) {
+                (Some(device), Some(bridge)) => Some(device.min(bridge)),
+                (Some(device), None) => Some(device),
+                (None, _) => link_at("", "current"),
+            };
+            if let Some(link) = link {
+                links.push((device.clone(), vendor.trim().to_lowercase(), link));
+            }
+        }
+        links
+    }
 
-        let text = match String::from_utf8(output.stdout) {
-            Ok(t) => t,
-            Err(_) => return Vec::new(),
+    /// Detect NVIDIA GPUs via nvidia-smi. Returns one GpuInfo per unique model,
+    /// with count and per-card VRAM for same-model multi-GPU setups.
+    fn detect_nvidia_gpus(probe: &dyn Probe, trail: &mut Vec<ProbeStep>) -> Vec<GpuInfo> {
+        let Some(text) = run_traced(probe, "nvidia-smi", NVIDIA_SMI_QUERY, "nvidia-smi", trail)
+        else {
+            return Vec::new();
         };
 
-        Self::parse_nvidia_smi_list(&text)
+        let gpus = Self::parse_nvidia_smi_list(&text);
+        trail.push(gpus_found_step("nvidia-smi", &gpus));
+        gpus
     }
 
-    /// Parse `nvidia-smi --query-gpu=memory.total,name --format=csv,noheader,nounits`.
+    /// Parse `nvidia-smi --query-gpu=memory.total[,memory.used],name --format=csv,noheader,nounits`.
+    /// Leading numeric fields are memory figures (total, then used) and the
+    /// rest of the line is the name, so the older `memory.total,name` form
+    /// and `[N/A]` placeholders parse too.
     /// Groups same-model cards and keeps per-card VRAM (never sums across cards).
     fn parse_nvidia_smi_list(text: &str) -> Vec<GpuInfo> {
-        let mut grouped: BTreeMap<String, (u32, f64)> = BTreeMap::new();
+        // (count, per-card VRAM MiB, used MiB, VRAM came from the name)
+        let mut grouped: BTreeMap<String, (u32, f64, Option<f64>, bool)> = BTreeMap::new();
 
         for line in text.lines() {
             let line = line.trim();
             if line.is_empty() {
                 continue;
             }
-            let parts: Vec<&str> = line.splitn(2, ',').collect();
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
 
-            let name = parts
-                .get(1)
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty())
-                .unwrap_or("NVIDIA GPU")
-                .to_string();
+            let mut memory_mb: Vec<Option<f64>> = Vec::new();
+            let mut rest = &fields[..];
+            while memory_mb.len() < 2 {
+                match rest.split_first() {
+                    Some((first, tail))
+                        if first.parse::<f64>().is_ok() || first.starts_with('[') =>
+                    {
+                        memory_mb.push(first.parse::<f64>().ok());
+                        rest = tail;
+                    }
+                    _ => break,
+                }
+            }
+
+            let joined = rest.join(",");
+            let name = if joined.is_empty() {
+                "NVIDIA GPU".to_string()
+            } else {
+                joined
+            };
 
-            let parsed_vram_mb = parts
-                .first()
-                .and_then(|s| s.trim().parse::<f64>().ok())
-                .unwrap_or(0.0);
+            let parsed_vram_mb = memory_mb.first().copied().flatten().unwrap_or(0.0);
             let vram_mb = if parsed_vram_mb > 0.0 {
                 parsed_vram_mb
             } else {
                 estimate_vram_from_name(&name) * 1024.0
             };
+            let used_mb = memory_mb.get(1).copied().flatten();
 
-            let entry = grouped.entry(name).or_insert((0, 0.0));
+            let entry = grouped.entry(name).or_insert((0, 0.0, None, false));
             entry.0 += 1;
             if vram_mb > entry.1 {
                 entry.1 = vram_mb;
+                entry.3 = parsed_vram_mb <= 0.0;
+            }
+            if let Some(used) = used_mb {
+                entry.2 = Some(entry.2.map_or(used, |u: f64| u.max(used)));
             }
         }
 
@@ -241,241 +958,609 @@ impl SystemSpecs {
 
         grouped
             .into_iter()
-            .map(|(name, (count, per_card_vram_mb))| GpuInfo {
-                name,
-                vram_gb: if per_card_vram_mb > 0.0 {
-                    Some(per_card_vram_mb / 1024.0)
+            .map(|(name, (count, per_card_vram_mb, used_mb, estimated))| {
+                let provenance = if estimated || per_card_vram_mb <= 0.0 {
+                    vec![
+                        ProbeStep::failed("nvidia-smi", "memory.total not reported"),
+                        estimate_vram_traced(&name).1,
+                    ]
                 } else {
-                    None
-                },
-                backend: GpuBackend::Cuda,
-                count,
-                unified_memory: false,
+                    vec![ProbeStep::ok(
+                        "nvidia-smi",
+                        format!("memory.total {:.0} MiB", per_card_vram_mb),
+                    )]
+                };
+                GpuInfo {
+                    vram_gb: if per_card_vram_mb > 0.0 {
+                        Some(per_card_vram_mb / 1024.0)
+                    } else {
+                        None
+                    },
+                    name,
+                    backend: GpuBackend::Cuda,
+                    count,
+                    unified_memory: false,
+                    vram_used_gb: used_mb.map(|mb| mb / 1024.0),
+                    provenance,
+                    pcie_link: None,
+                    memory_bandwidth_gb_s: None,
+                }
             })
             .collect()
     }
 
-    /// Detect AMD GPU via rocm-smi (available on Linux with ROCm installed).
-    /// Parses per-card VRAM and GPU name from rocm-smi output.
-    fn detect_amd_gpu_rocm_info() -> Option<GpuInfo> {
-        // Try rocm-smi --showmeminfo vram for VRAM
-        let vram_output = std::process::Command::new("rocm-smi")
-            .arg("--showmeminfo")
-            .arg("vram")
-            .output()
-            .ok()?;
+    /// Detect AMD GPUs via rocm-smi (available on Linux with ROCm installed).
+    /// Reads per-card VRAM and names from the JSON output, falling back to
+    /// the text output of older rocm-smi releases, and groups same-model
+    /// cards like `parse_nvidia_smi_list`.
+    fn detect_amd_gpus_rocm(probe: &dyn Probe, trail: &mut Vec<ProbeStep>) -> Vec<GpuInfo> {
+        const JSON_SOURCE: &str = "rocm-smi --showmeminfo vram --json";
+        const TEXT_SOURCE: &str = "rocm-smi --showmeminfo vram";
 
-        if !vram_output.status.success() {
-            return None;
+        let json = run_traced(
+            probe,
+            "rocm-smi",
+            &["--showmeminfo", "vram", "--json"],
+            JSON_SOURCE,
+            trail,
+        );
+        let parsed = json.as_deref().and_then(Self::parse_rocm_smi_vram_json);
+        if json.is_some() && parsed.is_none() {
+            trail.push(ProbeStep::failed(
+                JSON_SOURCE,
+                "output was not rocm-smi JSON",
+            ));
         }
-
-        let vram_text = String::from_utf8(vram_output.stdout).ok()?;
-
-        // Parse VRAM total from rocm-smi output.
-        // Typical format includes a line like:
-        //   "GPU[0] : vram Total Memory (B): 8589934592"
-        // or in table format with "Total" and bytes.
-        let mut per_gpu_vram_bytes: Vec<u64> = Vec::new();
-        let mut gpu_count: u32 = 0;
-        for line in vram_text.lines() {
-            let lower = line.to_lowercase();
-            if lower.contains("total") && !lower.contains("used") {
-                // Extract the numeric value (bytes)
-                if let Some(val) = line
-                    .split_whitespace()
-                    .filter_map(|w| w.parse::<u64>().ok())
-                    .next_back()
-                    && val > 0
-                {
-                    per_gpu_vram_bytes.push(val);
-                    gpu_count += 1;
-                }
+        let (mut cards, source) = match parsed {
+            Some(cards) => (cards, JSON_SOURCE),
+            None => {
+                let Some(text) = run_traced(
+                    probe,
+                    "rocm-smi",
+                    &["--showmeminfo", "vram"],
+                    TEXT_SOURCE,
+                    trail,
+                ) else {
+                    return Vec::new();
+                };
+                (Self::parse_rocm_smi_vram_text(&text), TEXT_SOURCE)
             }
-        }
-
-        if gpu_count == 0 {
+        };
+        if cards.is_empty() {
             // rocm-smi succeeded but we couldn't parse VRAM; GPU exists though
-            gpu_count = 1;
+            cards.insert("card0".to_string(), (None, None));
         }
 
-        // Try to get GPU name from rocm-smi --showproductname
-        let gpu_name = std::process::Command::new("rocm-smi")
-            .arg("--showproductname")
-            .output()
-            .ok()
-            .and_then(|o| {
-                if o.status.success() {
-                    String::from_utf8(o.stdout).ok()
-                } else {
-                    None
-                }
-            })
-            .and_then(|text| {
-                // Look for "Card Series" or "Card Model" lines
-                for line in text.lines() {
-                    let lower = line.to_lowercase();
-                    if (lower.contains("card series") || lower.contains("card model"))
-                        && let Some(val) = line.split(':').nth(1)
-                    {
-                        let name = val.trim().to_string();
-                        if !name.is_empty() {
-                            return Some(name);
-                        }
+        let mut name_steps = Vec::new();
+        let names = run_traced(
+            probe,
+            "rocm-smi",
+            &["--showproductname", "--json"],
+            "rocm-smi --showproductname --json",
+            &mut name_steps,
+        )
+        .and_then(|text| Self::parse_rocm_smi_names_json(&text))
+        .or_else(|| {
+            run_traced(
+                probe,
+                "rocm-smi",
+                &["--showproductname"],
+                "rocm-smi --showproductname",
+                &mut name_steps,
+            )
+            .map(|text| Self::parse_rocm_smi_names_text(&text))
+        })
+        .unwrap_or_default();
+
+        let readings = cards
+            .into_iter()
+            .map(|(card, (total, used))| {
+                let mut provenance = name_steps.clone();
+                let name = match names.get(&card) {
+                    Some(name) => {
+                        provenance.push(ProbeStep::ok(
+                            "rocm-smi --showproductname",
+                            format!("{}: \"{}\"", card, name),
+                        ));
+                        name.clone()
+                    }
+                    None => {
+                        provenance.push(ProbeStep::failed(
+                            "rocm-smi --showproductname",
+                            format!("no name for {}", card),
+                        ));
+                        "AMD GPU".to_string()
+                    }
+                };
+                let vram_gb = match total.filter(|&b| b > 0) {
+                    Some(bytes) => {
+                        let gb = bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+                        provenance.push(ProbeStep::ok(source, format!("{}: {:.2} GB", card, gb)));
+                        Some(gb)
                     }
+                    None => {
+                        provenance.push(ProbeStep::failed(
+                            source,
+                            format!("no VRAM total for {}", card),
+                        ));
+                        let (estimated, step) = estimate_vram_traced(&name);
+                        provenance.push(step);
+                        estimated
+                    }
+                };
+                AmdCard {
+                    name,
+                    vram_gb,
+                    vram_used_gb: used.map(|b| b as f64 / (1024.0 * 1024.0 * 1024.0)),
+                    provenance,
                 }
-                None
-            });
-
-        let name = gpu_name.unwrap_or_else(|| "AMD GPU".to_string());
-        let max_per_gpu_bytes = per_gpu_vram_bytes.into_iter().max().unwrap_or(0);
-        let vram_gb = if max_per_gpu_bytes > 0 {
-            Some(max_per_gpu_bytes as f64 / (1024.0 * 1024.0 * 1024.0))
-        } else {
-            let est = estimate_vram_from_name(&name);
-            if est > 0.0 { Some(est) } else { None }
-        };
+            })
+            .collect();
 
-        Some(GpuInfo {
-            name,
-            vram_gb,
-            backend: GpuBackend::Rocm,
-            count: gpu_count,
-            unified_memory: false,
-        })
+        let gpus = Self::group_amd_cards(readings, GpuBackend::Rocm);
+        trail.push(gpus_found_step("rocm-smi", &gpus));
+        gpus
     }
 
-    /// Detect AMD GPU via sysfs on Linux (works without ROCm installed).
-    /// AMD vendor ID is 0x1002.
-    fn detect_amd_gpu_sysfs_info() -> Option<GpuInfo> {
-        if !cfg!(target_os = "linux") {
-            return None;
+    /// Parse `rocm-smi --showmeminfo vram --json` into per-card
+    /// (total, used) bytes. `None` if the output isn't rocm-smi JSON.
+    fn parse_rocm_smi_vram_json(text: &str) -> Option<CardMemory> {
+        let value: serde_json::Value = serde_json::from_str(text).ok()?;
+        let mut cards = BTreeMap::new();
+        for (card, fields) in value.as_object()? {
+            // Newer releases add a "system" object alongside the cards.
+            let Some(fields) = fields.as_object().filter(|_| card.starts_with("card")) else {
+                continue;
+            };
+            let mut total = None;
+            let mut used = None;
+            for (key, value) in fields {
+                let key = key.to_lowercase();
+                let bytes = value
+                    .as_str()
+                    .and_then(|v| v.trim().parse::<u64>().ok())
+                    .or_else(|| value.as_u64());
+                if key.contains("total") && key.contains("used") {
+                    used = bytes;
+                } else if key.contains("total") {
+                    total = bytes;
+                }
+            }
+            cards.insert(card.clone(), (total, used));
         }
+        Some(cards)
+    }
 
-        let entries = std::fs::read_dir("/sys/class/drm").ok()?;
-        for entry in entries.flatten() {
-            let card_path = entry.path();
-            let fname = card_path.file_name()?.to_str()?.to_string();
-            // Only look at cardN entries, not cardN-DP-1 etc.
-            if !fname.starts_with("card") || fname.contains('-') {
+    /// Parse the text form of `rocm-smi --showmeminfo vram`:
+    ///   "GPU[0] : VRAM Total Memory (B): 8589934592"
+    ///   "GPU[0] : VRAM Total Used Memory (B): 1073741824"
+    /// Lines without a `GPU[N]` prefix are numbered in order of appearance.
+    fn parse_rocm_smi_vram_text(text: &str) -> CardMemory {
+        let mut cards: CardMemory = BTreeMap::new();
+        let mut unlabelled = 0;
+        for line in text.lines() {
+            let lower = line.to_lowercase();
+            if !lower.contains("total") {
                 continue;
             }
-
-            let device_path = card_path.join("device");
-            let vendor_path = device_path.join("vendor");
-            if let Ok(vendor) = std::fs::read_to_string(&vendor_path) {
-                if vendor.trim() != "0x1002" {
-                    continue;
-                }
-            } else {
+            let Some(val) = line
+                .split_whitespace()
+                .filter_map(|w| w.parse::<u64>().ok())
+                .next_back()
+            else {
                 continue;
+            };
+            let used = lower.contains("used");
+            let card = match Self::rocm_smi_text_card(line) {
+                Some(card) => card,
+                None => {
+                    let card = format!("card{}", unlabelled);
+                    if !used {
+                        unlabelled += 1;
+                    }
+                    card
+                }
+            };
+            let entry = cards.entry(card).or_default();
+            if used {
+                entry.1 = Some(val);
+            } else if val > 0 {
+                entry.0 = Some(val);
             }
+        }
+        cards
+    }
 
-            // Found an AMD GPU. Try to read VRAM.
-            let mut vram_gb: Option<f64> = None;
-            let vram_path = device_path.join("mem_info_vram_total");
-            if let Ok(vram_str) = std::fs::read_to_string(&vram_path)
-                && let Ok(vram_bytes) = vram_str.trim().parse::<u64>()
-                && vram_bytes > 0
-            {
-                vram_gb = Some(vram_bytes as f64 / (1024.0 * 1024.0 * 1024.0));
-            }
+    /// `GPU[N]` prefix of a rocm-smi text line, as the JSON key `cardN`.
+    fn rocm_smi_text_card(line: &str) -> Option<String> {
+        let rest = line.trim_start().strip_prefix("GPU[")?;
+        let index: u32 = rest[..rest.find(']')?].parse().ok()?;
+        Some(format!("card{}", index))
+    }
 
-            // Try to get GPU name from lspci
-            let gpu_name = Self::get_amd_gpu_name_lspci();
-            let name = gpu_name.unwrap_or_else(|| "AMD GPU".to_string());
+    /// Parse `rocm-smi --showproductname --json` into per-card names,
+    /// preferring "Card Series" over "Card Model" (a PCI ID on new releases).
+    fn parse_rocm_smi_names_json(text: &str) -> Option<BTreeMap<String, String>> {
+        let value: serde_json::Value = serde_json::from_str(text).ok()?;
+        let mut names = BTreeMap::new();
+        for (card, fields) in value.as_object()? {
+            let Some(fields) = fields.as_object().filter(|_| card.starts_with("card")) else {
+                continue;
+            };
+            let field = |wanted: &str| {
+                fields
+                    .iter()
+                    .find(|(k, _)| k.to_lowercase() == wanted)
+                    .and_then(|(_, v)| v.as_str())
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty() && !v.starts_with("0x"))
+            };
+            if let Some(name) = field("card series").or_else(|| field("card model")) {
+                names.insert(card.clone(), name.to_string());
+            }
+        }
+        Some(names)
+    }
+
+    /// Parse the text form of `rocm-smi --showproductname`:
+    ///   "GPU[0] : Card Series: Navi 31 [Radeon RX 7900 XTX]"
+    fn parse_rocm_smi_names_text(text: &str) -> BTreeMap<String, String> {
+        let mut names = BTreeMap::new();
+        let mut unlabelled = 0;
+        for line in text.lines() {
+            let lower = line.to_lowercase();
+            let Some(pos) = lower
+                .find("card series")
+                .or_else(|| lower.find("card model"))
+            else {
+                continue;
+            };
+            let Some(value) = line[pos..].split_once(':').map(|(_, v)| v.trim()) else {
+                continue;
+            };
+            let card = Self::rocm_smi_text_card(line).unwrap_or_else(|| {
+                unlabelled += 1;
+                format!("card{}", unlabelled - 1)
+            });
+            if !value.is_empty() && !value.starts_with("0x") {
+                names.entry(card).or_insert_with(|| value.to_string());
+            }
+        }
+        names
+    }
+
+    /// Detect AMD GPUs via sysfs on Linux (works without ROCm installed),
+    /// one reading per `/sys/class/drm/cardN` with AMD's vendor ID 0x1002.
+    /// Returns discrete cards and APUs separately; APUs are reported with
+    /// unified memory sized from their GTT pool, using `apu_backend`.
+    fn detect_amd_gpus_sysfs(
+        probe: &dyn Probe,
+        total_ram_gb: f64,
+        apu_backend: GpuBackend,
+        trail: &mut Vec<ProbeStep>,
+    ) -> (Vec<GpuInfo>, Vec<GpuInfo>) {
+        if probe.os() != "linux" {
+            return (Vec::new(), Vec::new());
+        }
+
+        let drm = Path::new("/sys/class/drm");
+        let Some(entries) = probe.read_dir(drm) else {
+            trail.push(ProbeStep::failed("sysfs /sys/class/drm", "not present"));
+            return (Vec::new(), Vec::new());
+        };
+
+        let mut lspci: Option<Vec<(String, String)>> = None;
+        let mut readings = Vec::new();
+        let mut apus = Vec::new();
+        for fname in entries {
+            // Only look at cardN entries, not cardN-DP-1 etc.
+            if !fname.starts_with("card") || fname.contains('-') {
+                continue;
+            }
+
+            let device_path = drm.join(&fname).join("device");
+            let vendor_path = device_path.join("vendor");
+            if let Some(vendor) = probe.read_file(&vendor_path) {
+                if vendor.trim() != "0x1002" {
+                    continue;
+                }
+            } else {
+                continue;
+            }
+
+            // Found an AMD GPU. Try to read VRAM.
+            let mut provenance = Vec::new();
+            let mut vram_gb: Option<f64> = None;
+            let vram_path = device_path.join("mem_info_vram_total");
+            if let Some(vram_str) = probe.read_file(&vram_path)
+                && let Ok(vram_bytes) = vram_str.trim().parse::<u64>()
+                && vram_bytes > 0
+            {
+                let gb = vram_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+                provenance.push(ProbeStep::ok(
+                    "sysfs mem_info_vram_total",
+                    format!("{:.2} GB ({})", gb, vram_path.display()),
+                ));
+                vram_gb = Some(gb);
+            } else {
+                provenance.push(ProbeStep::failed(
+                    "sysfs mem_info_vram_total",
+                    format!("missing or unreadable ({})", vram_path.display()),
+                ));
+            }
+            let vram_used_gb = probe
+                .read_file(&device_path.join("mem_info_vram_used"))
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                .map(|b| b as f64 / (1024.0 * 1024.0 * 1024.0));
+
+            // Name from lspci, matched to this card by PCI slot
+            let controllers = lspci.get_or_insert_with(|| {
+                run_traced(probe, "lspci", &[], "lspci", trail)
+                    .map(|text| Self::parse_amd_gpu_names_lspci(&text))
+                    .unwrap_or_default()
+            });
+            let slot = probe
+                .read_file(&device_path.join("uevent"))
+                .and_then(|text| {
+                    text.lines()
+                        .find_map(|l| l.strip_prefix("PCI_SLOT_NAME="))
+                        .map(|s| s.trim().to_string())
+                });
+            let name = match Self::amd_lspci_name(controllers, slot.as_deref()) {
+                Some(name) => {
+                    provenance.push(ProbeStep::ok("lspci", format!("name \"{}\"", name)));
+                    name
+                }
+                None => {
+                    provenance.push(ProbeStep::failed(
+                        "lspci",
+                        "no AMD VGA/3D controller listed",
+                    ));
+                    "AMD GPU".to_string()
+                }
+            };
+
+            if Self::is_amd_apu(&name, vram_gb) {
+                let gtt_path = device_path.join("mem_info_gtt_total");
+                let gtt_gb = probe
+                    .read_file(&gtt_path)
+                    .and_then(|s| s.trim().parse::<u64>().ok())
+                    .map(|b| b as f64 / (1024.0 * 1024.0 * 1024.0));
+                provenance.push(match gtt_gb {
+                    Some(gtt) => ProbeStep::ok(
+                        "sysfs mem_info_gtt_total",
+                        format!("{:.2} GB ({})", gtt, gtt_path.display()),
+                    ),
+                    None => ProbeStep::failed(
+                        "sysfs mem_info_gtt_total",
+                        format!("missing or unreadable ({})", gtt_path.display()),
+                    ),
+                });
+                let pool =
+                    amd_apu_pool_gb(vram_gb.unwrap_or(0.0), gtt_gb.unwrap_or(0.0), total_ram_gb);
+                provenance.push(ProbeStep::ok(
+                    "APU unified pool",
+                    format!(
+                        "{:.2} GB: carve-out plus GTT, leaving {:.0} GB of RAM to the OS",
+                        pool, APU_OS_RESERVE_GB
+                    ),
+                ));
+                apus.push(GpuInfo {
+                    name,
+                    vram_gb: Some(pool),
+                    backend: apu_backend,
+                    count: 1,
+                    unified_memory: true,
+                    vram_used_gb: None,
+                    provenance,
+                    pcie_link: None,
+                    memory_bandwidth_gb_s: None,
+                });
+                continue;
+            }
 
             // If we still don't have VRAM, try to estimate from name
             if vram_gb.is_none() {
-                let estimated = estimate_vram_from_name(&name);
-                if estimated > 0.0 {
-                    vram_gb = Some(estimated);
-                }
+                let (estimated, step) = estimate_vram_traced(&name);
+                provenance.push(step);
+                vram_gb = estimated;
             }
 
-            // AMD GPU without ROCm — Vulkan is the most likely inference backend
-            return Some(GpuInfo {
+            readings.push(AmdCard {
                 name,
                 vram_gb,
-                backend: GpuBackend::Vulkan,
-                count: 1,
-                unified_memory: false,
+                vram_used_gb,
+                provenance,
             });
         }
-        None
-    }
 
-    /// Extract AMD GPU name from lspci output.
-    fn get_amd_gpu_name_lspci() -> Option<String> {
-        let output = std::process::Command::new("lspci").output().ok()?;
-        if !output.status.success() {
-            return None;
+        if !apus.is_empty() {
+            trail.push(gpus_found_step("sysfs AMD APU", &apus));
         }
-        let text = String::from_utf8(output.stdout).ok()?;
-        for line in text.lines() {
-            let lower = line.to_lowercase();
-            // VGA compatible controller or 3D controller with AMD/ATI
-            if (lower.contains("vga") || lower.contains("3d"))
-                && (lower.contains("amd") || lower.contains("ati"))
-            {
-                // Extract the part after the colon, e.g. "Advanced Micro Devices ... [Radeon RX 5700 XT]"
-                if let Some(desc) = line.split("]:").last() {
-                    let desc: &str = desc.trim();
-                    // Try to extract the bracketed name like "[Radeon RX 5700 XT]"
-                    if let Some(start) = desc.rfind('[')
-                        && let Some(end) = desc.rfind(']')
-                        && start < end
-                    {
-                        return Some(desc[start + 1..end].to_string());
-                    }
-                    return Some(desc.to_string());
+        if readings.is_empty() {
+            trail.push(ProbeStep::failed(
+                "sysfs /sys/class/drm",
+                "no discrete AMD cards (vendor 0x1002)",
+            ));
+            return (Vec::new(), apus);
+        }
+        // AMD GPU without ROCm — Vulkan is the most likely inference backend
+        let gpus = Self::group_amd_cards(readings, GpuBackend::Vulkan);
+        trail.push(gpus_found_step("sysfs /sys/class/drm", &gpus));
+        (gpus, apus)
+    }
+
+    /// AMD APUs (integrated Radeon) by PCI codename or marketing name. A
+    /// sub-1 GB carve-out also marks one: no current discrete card is that small.
+    fn is_amd_apu(name: &str, vram_gb: Option<f64>) -> bool {
+        const APU_NAMES: &[&str] = &[
+            "strix",
+            "krackan",
+            "phoenix",
+            "hawk point",
+            "rembrandt",
+            "raphael",
+            "granite ridge",
+            "renoir",
+            "cezanne",
+            "lucienne",
+            "barcelo",
+            "mendocino",
+            "van gogh",
+            "picasso",
+            "raven",
+            "radeon graphics",
+            "radeon vega mobile",
+            "8060s",
+            "8050s",
+            "890m",
+            "880m",
+            "780m",
+            "760m",
+            "740m",
+            "680m",
+            "660m",
+            "610m",
+        ];
+        let lower = name.to_lowercase();
+        APU_NAMES.iter().any(|n| lower.contains(n)) || vram_gb.is_some_and(|v| v < 1.0)
+    }
+
+    /// Group same-model AMD cards into one `GpuInfo` with a count, keeping
+    /// per-card VRAM (the largest reading) and the busiest card's usage.
+    fn group_amd_cards(cards: Vec<AmdCard>, backend: GpuBackend) -> Vec<GpuInfo> {
+        let mut grouped: BTreeMap<String, GpuInfo> = BTreeMap::new();
+        for card in cards {
+            let entry = grouped.entry(card.name.clone()).or_insert_with(|| GpuInfo {
+                name: card.name.clone(),
+                vram_gb: None,
+                backend,
+                count: 0,
+                unified_memory: false,
+                vram_used_gb: None,
+                provenance: Vec::new(),
+                pcie_link: None,
+                memory_bandwidth_gb_s: None,
+            });
+            entry.count += 1;
+            if card.vram_gb > entry.vram_gb {
+                entry.vram_gb = card.vram_gb;
+            }
+            if card.vram_used_gb > entry.vram_used_gb {
+                entry.vram_used_gb = card.vram_used_gb;
+            }
+            for step in card.provenance {
+                if !entry.provenance.contains(&step) {
+                    entry.provenance.push(step);
                 }
             }
         }
-        None
+        grouped.into_values().collect()
+    }
+
+    /// (PCI slot, bracketed device name) of every AMD VGA/3D controller in
+    /// `lspci` output.
+    fn parse_amd_gpu_names_lspci(text: &str) -> Vec<(String, String)> {
+        text.lines()
+            .filter(|line| {
+                let lower = line.to_lowercase();
+                // VGA, 3D or (on some APUs) Display controller with AMD/ATI
+                (lower.contains("vga") || lower.contains("3d"))
+                    && (lower.contains("amd") || lower.contains("ati"))
+                    || (lower.contains("display controller") && lower.contains("amd"))
+            })
+            .filter_map(Self::lspci_controller)
+            .collect()
+    }
+
+    /// (PCI slot, device name) of every VGA, 3D or display controller in
+    /// `lspci` output, whatever the vendor.
+    fn parse_gpu_names_lspci(text: &str) -> Vec<(String, String)> {
+        text.lines()
+            .filter(|line| {
+                let lower = line.to_lowercase();
+                lower.contains("vga")
+                    || lower.contains("3d")
+                    || lower.contains("display controller")
+            })
+            .filter_map(Self::lspci_controller)
+            .collect()
+    }
+
+    /// Slot and device name of one `lspci` line.
+    fn lspci_controller(line: &str) -> Option<(String, String)> {
+        let slot = line.split_whitespace().next().unwrap_or("").to_string();
+        // Extract the part after the colon, e.g. "Advanced Micro Devices ... [Radeon RX 5700 XT]"
+        let mut desc: &str = line.split("]:").last()?.trim();
+        if let Some(rev) = desc.rfind(" (rev ") {
+            desc = desc[..rev].trim_end();
+        }
+        // Try to extract the bracketed name like "[Radeon RX 5700 XT]";
+        // unbracketed names follow the "[AMD/ATI]" vendor tag.
+        let name = match (desc.rfind('['), desc.rfind(']')) {
+            (Some(start), Some(end)) if start < end && end + 1 == desc.len() => {
+                desc[start + 1..end].to_string()
+            }
+            (_, Some(end)) if end + 1 < desc.len() => desc[end + 1..].trim().to_string(),
+            _ => desc.to_string(),
+        };
+        Some((slot, name))
+    }
+
+    /// Name of the controller in `slot` ("0000:03:00.0"; lspci omits the
+    /// domain), or the first AMD controller when the slot is unknown.
+    fn amd_lspci_name(controllers: &[(String, String)], slot: Option<&str>) -> Option<String> {
+        slot.and_then(|slot| {
+            controllers
+                .iter()
+                .find(|(s, _)| !s.is_empty() && slot.ends_with(s.as_str()))
+        })
+        .or_else(|| controllers.first())
+        .map(|(_, name)| name.clone())
     }
 
     /// Detect GPUs on Windows via WMI (Win32_VideoController).
     /// Returns all discrete GPUs found (AMD, NVIDIA, Intel, etc.).
-    fn detect_gpu_windows_info() -> Vec<GpuInfo> {
-        if !cfg!(target_os = "windows") {
+    fn detect_gpu_windows_info(probe: &dyn Probe, trail: &mut Vec<ProbeStep>) -> Vec<GpuInfo> {
+        if probe.os() != "windows" {
             return Vec::new();
         }
 
         // Use PowerShell to query WMI — more reliable than wmic (deprecated)
-        if let Ok(output) = std::process::Command::new("powershell")
-            .arg("-NoProfile")
-            .arg("-Command")
-            .arg("Get-CimInstance Win32_VideoController | Select-Object Name,AdapterRAM | ForEach-Object { $_.Name + '|' + $_.AdapterRAM }")
-            .output()
-            && output.status.success()
-                && let Ok(text) = String::from_utf8(output.stdout) {
-                    let gpus = Self::parse_windows_gpu_list(&text);
-                    if !gpus.is_empty() {
-                        return gpus;
-                    }
-                }
+        if let Some(text) = run_traced(
+            probe,
+            "powershell",
+            WINDOWS_GPU_QUERY,
+            "WMI (PowerShell)",
+            trail,
+        ) {
+            let gpus = Self::parse_windows_gpu_list(&text);
+            trail.push(gpus_found_step("WMI (PowerShell)", &gpus));
+            if !gpus.is_empty() {
+                return gpus;
+            }
+        }
 
         // Fallback to wmic for older Windows
-        Self::detect_gpu_windows_wmic_list()
+        Self::detect_gpu_windows_wmic_list(probe, trail)
     }
 
     /// Fallback Windows GPU detection via wmic (works on older systems).
-    fn detect_gpu_windows_wmic_list() -> Vec<GpuInfo> {
-        let output = match std::process::Command::new("wmic")
-            .arg("path")
-            .arg("win32_VideoController")
-            .arg("get")
-            .arg("Name,AdapterRAM")
-            .arg("/format:csv")
-            .output()
-        {
-            Ok(o) if o.status.success() => o,
-            _ => return Vec::new(),
-        };
-
-        let text = match String::from_utf8(output.stdout) {
-            Ok(t) => t,
-            Err(_) => return Vec::new(),
+    fn detect_gpu_windows_wmic_list(probe: &dyn Probe, trail: &mut Vec<ProbeStep>) -> Vec<GpuInfo> {
+        let Some(text) = run_traced(
+            probe,
+            "wmic",
+            &[
+                "path",
+                "win32_VideoController",
+                "get",
+                "Name,AdapterRAM",
+                "/format:csv",
+            ],
+            "wmic",
+            trail,
+        ) else {
+            return Vec::new();
         };
 
         let mut gpus = Vec::new();
@@ -497,16 +1582,21 @@ impl SystemSpecs {
                     continue;
                 }
                 let backend = Self::infer_gpu_backend(&name);
-                let vram_gb = Self::resolve_wmi_vram(raw_vram, &name);
+                let (vram_gb, provenance) = Self::resolve_wmi_vram(raw_vram, &name, "wmic");
                 gpus.push(GpuInfo {
                     name,
                     vram_gb,
                     backend,
                     count: 1,
                     unified_memory: false,
+                    vram_used_gb: None,
+                    provenance,
+                    pcie_link: None,
+                    memory_bandwidth_gb_s: None,
                 });
             }
         }
+        trail.push(gpus_found_step("wmic", &gpus));
         gpus
     }
 
@@ -535,13 +1625,17 @@ impl SystemSpecs {
             }
 
             let backend = Self::infer_gpu_backend(&name);
-            let vram_gb = Self::resolve_wmi_vram(raw_vram, &name);
+            let (vram_gb, provenance) = Self::resolve_wmi_vram(raw_vram, &name, "WMI (PowerShell)");
             gpus.push(GpuInfo {
                 name,
                 vram_gb,
                 backend,
                 count: 1,
                 unified_memory: false,
+                vram_used_gb: None,
+                provenance,
+                pcie_link: None,
+                memory_bandwidth_gb_s: None,
             });
         }
         gpus
@@ -549,15 +1643,31 @@ impl SystemSpecs {
 
     /// WMI AdapterRAM is a 32-bit field, capped at ~4 GB.
     /// If reported value is suspiciously low, estimate from GPU name.
-    fn resolve_wmi_vram(raw_bytes: u64, name: &str) -> Option<f64> {
-        let mut vram_gb = raw_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-        if vram_gb < 0.1 || (vram_gb <= 4.1 && estimate_vram_from_name(name) > 4.1) {
-            let estimated = estimate_vram_from_name(name);
-            if estimated > 0.0 {
-                vram_gb = estimated;
+    /// Returns the VRAM and the steps that produced it.
+    fn resolve_wmi_vram(raw_bytes: u64, name: &str, source: &str) -> (Option<f64>, Vec<ProbeStep>) {
+        let raw_gb = raw_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+        let mut steps = Vec::new();
+        if raw_gb < 0.1 || (raw_gb <= 4.1 && estimate_vram_from_name(name) > 4.1) {
+            steps.push(ProbeStep::failed(
+                source,
+                if raw_gb < 0.1 {
+                    "AdapterRAM not reported".to_string()
+                } else {
+                    format!("AdapterRAM {:.2} GB, capped by its 32-bit field", raw_gb)
+                },
+            ));
+            let (estimated, step) = estimate_vram_traced(name);
+            steps.push(step);
+            if estimated.is_some() {
+                return (estimated, steps);
             }
+        } else {
+            steps.push(ProbeStep::ok(
+                source,
+                format!("AdapterRAM {:.2} GB", raw_gb),
+            ));
         }
-        if vram_gb > 0.0 { Some(vram_gb) } else { None }
+        (if raw_gb > 0.0 { Some(raw_gb) } else { None }, steps)
     }
 
     /// Infer the most likely inference backend from a GPU name string.
@@ -585,17 +1695,19 @@ impl SystemSpecs {
     /// Intel Arc GPUs (A370M, A770, etc.) have dedicated VRAM exposed via
     /// the DRM subsystem at /sys/class/drm/card*/device/. Even integrated
     /// Intel GPUs that share system RAM are useful for inference via SYCL/oneAPI.
-    fn detect_intel_gpu() -> Option<f64> {
+    /// Returns the VRAM and the step that found it.
+    fn detect_intel_gpu(probe: &dyn Probe) -> Option<(f64, ProbeStep)> {
         // Try sysfs first: works for Intel discrete (Arc) GPUs on Linux.
         // Walk /sys/class/drm/card*/device/ looking for Intel vendor ID (0x8086).
-        if let Ok(entries) = std::fs::read_dir("/sys/class/drm") {
-            for entry in entries.flatten() {
-                let card_path = entry.path();
+        let drm = Path::new("/sys/class/drm");
+        if let Some(entries) = probe.read_dir(drm) {
+            for entry in entries {
+                let card_path = drm.join(entry);
                 let device_path = card_path.join("device");
 
                 // Check vendor ID matches Intel (0x8086)
                 let vendor_path = device_path.join("vendor");
-                if let Ok(vendor) = std::fs::read_to_string(&vendor_path)
+                if let Some(vendor) = probe.read_file(&vendor_path)
                     && vendor.trim() != "0x8086"
                 {
                     continue;
@@ -604,27 +1716,30 @@ impl SystemSpecs {
                 // Look for total VRAM via DRM memory info
                 // Intel discrete GPUs expose this under drm/card*/device/mem_info_vram_total
                 let vram_path = card_path.join("device/mem_info_vram_total");
-                if let Ok(vram_str) = std::fs::read_to_string(&vram_path)
+                if let Some(vram_str) = probe.read_file(&vram_path)
                     && let Ok(vram_bytes) = vram_str.trim().parse::<u64>()
                     && vram_bytes > 0
                 {
                     let vram_gb = vram_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-                    return Some(vram_gb);
+                    return Some((
+                        vram_gb,
+                        ProbeStep::ok(
+                            "sysfs mem_info_vram_total",
+                            format!("{:.2} GB ({})", vram_gb, vram_path.display()),
+                        ),
+                    ));
                 }
 
                 // For integrated Intel GPUs, check if it's an Arc-class device
                 // by looking for "Arc" in the device name via lspci
-                if let Ok(output) = std::process::Command::new("lspci").output()
-                    && output.status.success()
-                    && let Ok(text) = String::from_utf8(output.stdout)
-                {
+                if let Some(text) = probe.run_stdout("lspci", &[]) {
                     for line in text.lines() {
                         let lower = line.to_lowercase();
                         if lower.contains("intel") && lower.contains("arc") {
                             // Intel Arc integrated (e.g. Arc Graphics in Meteor Lake)
                             // These share system RAM; report None for VRAM and
                             // let the caller know a GPU exists.
-                            return Some(0.0);
+                            return Some((0.0, Self::intel_arc_lspci_step()));
                         }
                     }
                 }
@@ -633,14 +1748,11 @@ impl SystemSpecs {
 
         // Fallback: check lspci directly for Intel Arc devices
         // (covers cases where sysfs isn't available or card dirs don't exist)
-        if let Ok(output) = std::process::Command::new("lspci").output()
-            && output.status.success()
-            && let Ok(text) = String::from_utf8(output.stdout)
-        {
+        if let Some(text) = probe.run_stdout("lspci", &[]) {
             for line in text.lines() {
                 let lower = line.to_lowercase();
                 if lower.contains("intel") && lower.contains("arc") {
-                    return Some(0.0);
+                    return Some((0.0, Self::intel_arc_lspci_step()));
                 }
             }
         }
@@ -648,22 +1760,30 @@ impl SystemSpecs {
         None
     }
 
+    fn intel_arc_lspci_step() -> ProbeStep {
+        ProbeStep::ok("lspci", "integrated Intel Arc, VRAM shared with system RAM")
+    }
+
     /// Detect Apple Silicon GPU via system_profiler.
     /// Returns total system RAM as VRAM since memory is unified.
     /// The unified memory pool capacity is the total RAM -- it doesn't
     /// fluctuate with current usage the way available RAM does.
-    fn detect_apple_gpu(total_ram_gb: f64) -> Option<f64> {
+    fn detect_apple_gpu(
+        probe: &dyn Probe,
+        total_ram_gb: f64,
+        trail: &mut Vec<ProbeStep>,
+    ) -> Option<f64> {
         // system_profiler only exists on macOS
-        let output = std::process::Command::new("system_profiler")
-            .arg("SPDisplaysDataType")
-            .output()
-            .ok()?;
-
-        if !output.status.success() {
+        if probe.os() != "macos" {
             return None;
         }
-
-        let text = String::from_utf8(output.stdout).ok()?;
+        let text = run_traced(
+            probe,
+            "system_profiler",
+            &["SPDisplaysDataType"],
+            "system_profiler",
+            trail,
+        )?;
 
         // Apple Silicon GPUs show "Apple M1/M2/M3/M4" in the chipset line.
         // Discrete AMD/Intel GPUs on older Macs won't match.
@@ -675,38 +1795,56 @@ impl SystemSpecs {
         if is_apple_gpu {
             // Unified memory: GPU and CPU share the same RAM pool.
             // Report total RAM as the VRAM capacity.
+            trail.push(ProbeStep::ok("system_profiler", "found Apple GPU"));
             Some(total_ram_gb)
         } else {
+            trail.push(ProbeStep::failed("system_profiler", "no Apple GPU listed"));
             None
         }
     }
 
     /// Fallback for available RAM when sysinfo returns 0.
     /// Tries total - used first, then macOS vm_stat parsing.
-    fn available_ram_fallback(sys: &System, total_bytes: u64, total_gb: f64) -> f64 {
+    fn available_ram_fallback(
+        probe: &dyn Probe,
+        sys: &SysInfoSnapshot,
+        total_bytes: u64,
+        total_gb: f64,
+        steps: &mut Vec<ProbeStep>,
+    ) -> f64 {
         // Try total - used from sysinfo (may also use vm_statistics64 internally)
-        let used = sys.used_memory();
+        let used = sys.used_memory;
         if used > 0 && used < total_bytes {
-            return (total_bytes - used) as f64 / (1024.0 * 1024.0 * 1024.0);
+            let gb = (total_bytes - used) as f64 / (1024.0 * 1024.0 * 1024.0);
+            steps.push(ProbeStep::ok(
+                "sysinfo total - used",
+                format!("{:.2} GB available", gb),
+            ));
+            return gb;
         }
+        steps.push(ProbeStep::failed(
+            "sysinfo total - used",
+            "used memory not reported",
+        ));
 
         // macOS fallback: parse vm_stat output
-        if let Some(avail) = Self::available_ram_from_vm_stat() {
+        if let Some(avail) = Self::available_ram_from_vm_stat(probe, steps) {
+            steps.push(ProbeStep::ok(
+                "vm_stat",
+                format!("free + inactive + purgeable = {:.2} GB", avail),
+            ));
             return avail;
         }
 
         // Last resort: assume 80% of total is available (conservative)
+        steps.push(ProbeStep::ok("assumed", "80% of total RAM"));
         total_gb * 0.8
     }
 
     /// Parse macOS `vm_stat` to compute available memory.
     /// Available ≈ (free + inactive + purgeable) * page_size
-    fn available_ram_from_vm_stat() -> Option<f64> {
-        let output = std::process::Command::new("vm_stat").output().ok()?;
-        if !output.status.success() {
-            return None;
-        }
-        let text = String::from_utf8(output.stdout).ok()?;
+    fn available_ram_from_vm_stat(probe: &dyn Probe, steps: &mut Vec<ProbeStep>) -> Option<f64> {
+        let text = run_traced(probe, "vm_stat", &[], "vm_stat", steps)?;
 
         // First line: "Mach Virtual Memory Statistics: (page size of NNNNN bytes)"
         let page_size: u64 = text
@@ -740,6 +1878,7 @@ impl SystemSpecs {
         if available_bytes > 0 {
             Some(available_bytes as f64 / (1024.0 * 1024.0 * 1024.0))
         } else {
+            steps.push(ProbeStep::failed("vm_stat", "no free/inactive page counts"));
             None
         }
     }
@@ -757,6 +1896,76 @@ impl SystemSpecs {
             .ok()
     }
 
+    /// Rebuild specs from saved `llmfit --json system` output, so machines
+    /// other than this one can be analyzed. Accepts either the full document
+    /// (`{"system": {...}}`) or the inner system object.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let sys = value.get("system").unwrap_or(value);
+        let f64_field = |key: &str| {
+            sys.get(key)
+                .and_then(|v| v.as_f64())
+                .ok_or_else(|| format!("missing or invalid field '{}'", key))
+        };
+        let backend_field = |v: &serde_json::Value| {
+            let label = v.get("backend").and_then(|b| b.as_str()).unwrap_or("");
+            GpuBackend::from_label(label).ok_or_else(|| format!("unknown backend '{}'", label))
+        };
+
+        let gpus = sys
+            .get("gpus")
+            .and_then(|g| g.as_array())
+            .map(|list| {
+                list.iter()
+                    .map(|g| {
+                        Ok(GpuInfo {
+                            name: json_field(g, "name")?.unwrap_or_else(|| "GPU".to_string()),
+                            vram_gb: json_field(g, "vram_gb")?,
+                            backend: backend_field(g)?,
+                            count: json_field(g, "count")?.unwrap_or(1),
+                            unified_memory: json_field(g, "unified_memory")?.unwrap_or(false),
+                            vram_used_gb: json_field(g, "vram_used_gb")?,
+                            provenance: json_field(g, "provenance")?.unwrap_or_default(),
+                            pcie_link: json_field(g, "pcie_link")?,
+                            memory_bandwidth_gb_s: json_field(g, "memory_bandwidth_gb_s")?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, String>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(SystemSpecs {
+            total_ram_gb: f64_field("total_ram_gb")?,
+            available_ram_gb: f64_field("available_ram_gb")?,
+            total_cpu_cores: json_field(sys, "cpu_cores")?.unwrap_or(1),
+            cpu_name: json_field(sys, "cpu_name")?.unwrap_or_else(|| "Unknown CPU".to_string()),
+            has_gpu: json_field(sys, "has_gpu")?.unwrap_or(!gpus.is_empty()),
+            gpu_vram_gb: json_field(sys, "gpu_vram_gb")?,
+            gpu_name: json_field(sys, "gpu_name")?,
+            gpu_count: json_field(sys, "gpu_count")?.unwrap_or(0),
+            unified_memory: json_field(sys, "unified_memory")?.unwrap_or(false),
+            backend: backend_field(sys)?,
+            gpus,
+            cgroup_limits: json_field(sys, "cgroup_limits")?,
+            storage: json_field(sys, "storage")?,
+            cpu_features: json_field(sys, "cpu_features")?,
+            memory_topology: json_field(sys, "memory_topology")?,
+            detection: json_field(sys, "detection")?.unwrap_or_default(),
+            board: json_field(sys, "board")?,
+            npus: json_field(sys, "npus")?.unwrap_or_default(),
+            power: json_field(sys, "power")?,
+        })
+    }
+
+    /// Load specs from a file written by `llmfit --json system`.
+    pub fn load_json_file(path: &std::path::Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        let value: serde_json::Value = serde_json::from_str(&text)
+            .map_err(|e| format!("invalid JSON in {}: {e}", path.display()))?;
+        Self::from_json(&value).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
     /// Override the primary GPU's VRAM with a user-specified value (in GB).
     /// This is used by the `--memory` CLI flag when GPU autodetection fails.
     /// If no GPU was detected, this creates a synthetic GPU entry.
@@ -776,6 +1985,10 @@ impl SystemSpecs {
                 backend,
                 count: 1,
                 unified_memory: false,
+                vram_used_gb: None,
+                provenance: vec![ProbeStep::ok("--memory", format!("{:.2} GB", vram_gb))],
+                pcie_link: None,
+                memory_bandwidth_gb_s: None,
             });
             self.has_gpu = true;
             self.gpu_vram_gb = Some(vram_gb);
@@ -785,18 +1998,151 @@ impl SystemSpecs {
         } else {
             // Override the primary (first) GPU's VRAM.
             self.gpus[0].vram_gb = Some(vram_gb);
+            self.gpus[0].provenance.push(ProbeStep::ok(
+                "--memory",
+                format!("{:.2} GB, replacing the detected figure", vram_gb),
+            ));
             self.gpu_vram_gb = Some(vram_gb);
             self.has_gpu = true;
         }
         self
     }
 
+    /// Fit against the primary GPU's free VRAM instead of its total, so memory
+    /// held by compositors, browsers or already-loaded models is accounted for.
+    /// No-op when usage is unknown or memory is unified.
+    pub fn with_free_vram(mut self) -> Self {
+        if let Some(primary) = self.gpus.first()
+            && !primary.unified_memory
+            && let Some(free) = primary.vram_free_gb()
+        {
+            self.gpu_vram_gb = Some(free);
+        }
+        self
+    }
+
+    /// Fit against VRAM left after `gb` that another runtime keeps resident
+    /// (models Ollama has loaded), so a new model won't evict it. Their
+    /// RAM share needs no adjustment: available RAM already excludes it.
+    /// `gb` is the total across cards; `gpu_vram_gb` is per card, so each
+    /// card gives up an even share.
+    pub fn with_reserved_vram(mut self, gb: f64) -> Self {
+        if let Some(vram) = self.gpu_vram_gb {
+            let per_card = gb / self.gpu_count.max(1) as f64;
+            self.gpu_vram_gb = Some((vram - per_card).max(0.0));
+        }
+        self
+    }
+
+    /// Replace the detected RAM type, speed and (optionally) channel count.
+    pub fn with_memory_speed_override(
+        mut self,
+        memory_type: String,
+        speed_mt_s: u32,
+        channels: Option<u32>,
+    ) -> Self {
+        let topo = self
+            .memory_topology
+            .get_or_insert_with(MemoryTopology::default);
+        topo.memory_type = Some(memory_type);
+        topo.speed_mt_s = Some(speed_mt_s);
+        if channels.is_some() {
+            topo.channels = channels;
+        }
+        self
+    }
+
+    /// Peak RAM bandwidth: detected or `--ram-speed` topology first, then
+    /// the board profile.
+    pub fn memory_bandwidth_gb_s(&self) -> Option<f64> {
+        self.memory_topology
+            .as_ref()
+            .and_then(|m| m.bandwidth_gb_s())
+            .or_else(|| self.board.as_ref()?.memory_bandwidth_gb_s)
+    }
+
     pub fn display(&self) {
         println!("\n=== System Specifications ===");
-        println!("CPU: {} ({} cores)", self.cpu_name, self.total_cpu_cores);
+        match self.cpu_features.as_ref().and_then(|f| f.physical_cores) {
+            Some(physical) if physical != self.total_cpu_cores => println!(
+                "CPU: {} ({} cores, {} physical)",
+                self.cpu_name, self.total_cpu_cores, physical
+            ),
+            _ => println!("CPU: {} ({} cores)", self.cpu_name, self.total_cpu_cores),
+        }
+        if let Some(features) = &self.cpu_features {
+            let labels = features.labels();
+            if !labels.is_empty() {
+                println!("CPU features: {}", labels.join(" "));
+            }
+        }
+        if let Some(board) = &self.board {
+            match board.memory_bandwidth_gb_s {
+                Some(bw) => println!("Board: {} (~{:.0} GB/s RAM)", board.model, bw),
+                None => println!("Board: {}", board.model),
+            }
+        }
         println!("Total RAM: {:.2} GB", self.total_ram_gb);
         println!("Available RAM: {:.2} GB", self.available_ram_gb);
         println!("Backend: {}", self.backend.label());
+        if let Some(limits) = &self.cgroup_limits {
+            let mut parts = Vec::new();
+            if let Some(mem) = limits.memory_limit_gb {
+                parts.push(format!("memory {:.2} GB", mem));
+            }
+            if let Some(cpu) = limits.cpu_limit {
+                parts.push(format!("CPU quota {:.1} cores", cpu));
+            }
+            if let Some(cpus) = limits.cpuset_cpus {
+                parts.push(format!("cpuset {} CPUs", cpus));
+            }
+            println!(
+                "Container limits: {} ({}, {})",
+                parts.join(", "),
+                limits.version,
+                limits.path
+            );
+        }
+        if let Some(topo) = &self.memory_topology {
+            if let (Some(speed), Some(bw)) = (topo.speed_mt_s, topo.bandwidth_gb_s()) {
+                let channels = match topo.channels {
+                    Some(c) => format!("{} channels", c),
+                    None => "channels unknown, assuming 2".to_string(),
+                };
+                println!(
+                    "Memory: {}-{} ({}, ~{:.0} GB/s)",
+                    topo.memory_type.as_deref().unwrap_or("RAM"),
+                    speed,
+                    channels,
+                    bw
+                );
+            }
+            if topo.numa_nodes.len() > 1 {
+                let nodes: Vec<String> = topo
+                    .numa_nodes
+                    .iter()
+                    .map(|n| format!("node{} {:.1} GB/{} CPUs", n.id, n.total_gb, n.cpus))
+                    .collect();
+                println!(
+                    "NUMA: {} nodes ({})",
+                    topo.numa_nodes.len(),
+                    nodes.join(", ")
+                );
+            }
+        }
+        if let Some(storage) = &self.storage {
+            println!(
+                "Swap: {:.2} GB free / {:.2} GB",
+                storage.swap_free_gb, storage.swap_total_gb
+            );
+            if let (Some(name), Some(kind), Some(read)) = (
+                &storage.disk_name,
+                storage.disk_kind,
+                storage.disk_read_gb_s,
+            ) {
+                println!("Disk: {} ({}, ~{:.2} GB/s read)", name, kind.label(), read);
+            }
+        }
 
         if self.gpus.is_empty() {
             println!("GPU: Not detected");
@@ -807,110 +2153,1092 @@ impl SystemSpecs {
                 } else {
                     "GPU: ".to_string()
                 };
+                let backend = match gpu.pcie_link {
+                    Some(link) => format!("{}, {}", gpu.backend.label(), link.label()),
+                    None => gpu.backend.label().to_string(),
+                };
                 if gpu.unified_memory {
                     println!(
                         "{}{} (unified memory, {:.2} GB shared, {})",
                         prefix,
                         gpu.name,
                         gpu.vram_gb.unwrap_or(0.0),
-                        gpu.backend.label(),
+                        backend,
                     );
                 } else {
                     match gpu.vram_gb {
                         Some(vram) if vram > 0.0 => {
+                            let usage = match gpu.vram_used_gb {
+                                Some(used) => format!(
+                                    ", {:.2} GB used, {:.2} GB free",
+                                    used,
+                                    (vram - used).max(0.0)
+                                ),
+                                None => String::new(),
+                            };
                             if gpu.count > 1 {
                                 println!(
-                                    "{}{} x{} ({:.2} GB VRAM each, {})",
-                                    prefix,
-                                    gpu.name,
-                                    gpu.count,
-                                    vram,
-                                    gpu.backend.label()
+                                    "{}{} x{} ({:.2} GB VRAM each{}, {})",
+                                    prefix, gpu.name, gpu.count, vram, usage, backend
                                 );
                             } else {
                                 println!(
-                                    "{}{} ({:.2} GB VRAM, {})",
-                                    prefix,
-                                    gpu.name,
-                                    vram,
-                                    gpu.backend.label()
+                                    "{}{} ({:.2} GB VRAM{}, {})",
+                                    prefix, gpu.name, vram, usage, backend
                                 );
                             }
                         }
-                        Some(_) => println!(
-                            "{}{} (shared system memory, {})",
-                            prefix,
-                            gpu.name,
-                            gpu.backend.label()
-                        ),
-                        None => println!(
-                            "{}{} (VRAM unknown, {})",
-                            prefix,
-                            gpu.name,
-                            gpu.backend.label()
-                        ),
+                        Some(_) => {
+                            println!("{}{} (shared system memory, {})", prefix, gpu.name, backend)
+                        }
+                        None => println!("{}{} (VRAM unknown, {})", prefix, gpu.name, backend),
+                    }
+                }
+            }
+            if let (Some(primary), Some(fit_vram)) = (self.gpus.first(), self.gpu_vram_gb)
+                && primary.vram_free_gb() == Some(fit_vram)
+                && primary.vram_gb != Some(fit_vram)
+            {
+                println!("Fitting against free VRAM: {:.2} GB", fit_vram);
+            }
+        }
+        for npu in &self.npus {
+            match npu.tops {
+                Some(tops) => println!("NPU: {} (~{:.0} TOPS)", npu.name, tops),
+                None => println!("NPU: {}", npu.name),
+            }
+        }
+        if let Some(power) = &self.power {
+            println!("Power: {}", power.label());
+        }
+        println!();
+    }
+
+    /// Print which probes produced each figure, for `system --verbose`.
+    pub fn display_detection(&self) {
+        fn print_steps(heading: &str, steps: &[ProbeStep]) {
+            println!("{}:", heading);
+            if steps.is_empty() {
+                println!("  (no probes recorded)");
+            }
+            for step in steps {
+                let mark = if step.ok { "ok  " } else { "fail" };
+                println!("  [{}] {}: {}", mark, step.source, step.detail);
+            }
+        }
+
+        println!("=== Detection ===");
+        print_steps("Total RAM", &self.detection.total_ram);
+        print_steps("Available RAM", &self.detection.available_ram);
+        print_steps("GPU probes", &self.detection.gpu_probes);
+        for (i, gpu) in self.gpus.iter().enumerate() {
+            print_steps(&format!("GPU {} ({})", i + 1, gpu.name), &gpu.provenance);
+        }
+        println!();
+    }
+}
+
+/// Parse a human-readable memory size string into gigabytes.
+/// Accepts formats: "32G", "32g", "32GB", "32gb", "32000M", "32000m", "32000MB", etc.
+/// Returns `None` if the input is malformed.
+pub fn parse_memory_size(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+
+    // Split into numeric part and suffix
+    let num_end = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (num_str, suffix) = s.split_at(num_end);
+    let value: f64 = num_str.parse().ok()?;
+    if value < 0.0 {
+        return None;
+    }
+
+    let suffix = suffix.trim().to_lowercase();
+    match suffix.as_str() {
+        "g" | "gb" | "gib" | "" => Some(value),     // already in GB
+        "m" | "mb" | "mib" => Some(value / 1024.0), // MB → GB
+        "t" | "tb" | "tib" => Some(value * 1024.0), // TB → GB
+        _ => None,
+    }
+}
+
+pub fn is_running_in_wsl() -> bool {
+    static IS_WSL: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *IS_WSL.get_or_init(detect_running_in_wsl)
+}
+
+fn detect_running_in_wsl() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+
+    if std::env::var_os("WSL_INTEROP").is_some() || std::env::var_os("WSL_DISTRO_NAME").is_some() {
+        return true;
+    }
+
+    ["/proc/sys/kernel/osrelease", "/proc/version"]
+        .iter()
+        .any(|path| {
+            std::fs::read_to_string(path)
+                .map(|text| text.to_ascii_lowercase().contains("microsoft"))
+                .unwrap_or(false)
+        })
+}
+
+/// Memory and CPU limits imposed by the cgroup this process runs in
+/// (Docker, Kubernetes, systemd slices). `sysinfo` reports host totals, so
+/// these are used to clamp what the process can actually use.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CgroupLimits {
+    /// "cgroup v2" or "cgroup v1".
+    pub version: String,
+    /// Cgroup directory the limits were read from.
+    pub path: String,
+    pub memory_limit_gb: Option<f64>,
+    /// Memory charged to the cgroup, less reclaimable page cache
+    /// (`inactive_file`).
+    pub memory_usage_gb: Option<f64>,
+    /// CPU quota in cores (quota / period), possibly fractional.
+    pub cpu_limit: Option<f64>,
+    /// CPUs allowed by the cpuset controller, when fewer than the host has.
+    pub cpuset_cpus: Option<usize>,
+}
+
+/// v1 reports "unlimited" as a huge page-aligned value near i64::MAX.
+const CGROUP_UNLIMITED_BYTES: u64 = 1 << 60;
+
+impl CgroupLimits {
+    /// Read limits for the current process on a host with `host_cpus` CPUs.
+    /// Returns `None` when no limit applies.
+    pub fn detect(probe: &dyn Probe, host_cpus: usize) -> Option<Self> {
+        if probe.os() != "linux" {
+            return None;
+        }
+        let proc_cgroup = probe.read_file(Path::new("/proc/self/cgroup"))?;
+        let cgroup_root = Path::new("/sys/fs/cgroup");
+
+        let limits = if probe
+            .read_file(&cgroup_root.join("cgroup.controllers"))
+            .is_some()
+        {
+            Self::read_v2(probe, cgroup_root, &proc_cgroup)
+        } else {
+            Self::read_v1(probe, cgroup_root, &proc_cgroup)
+        }?;
+        // A cpuset spanning the whole machine is systemd's default, not a limit.
+        let limits = CgroupLimits {
+            cpuset_cpus: limits.cpuset_cpus.filter(|&n| n < host_cpus),
+            ..limits
+        };
+
+        if limits.memory_limit_gb.is_none()
+            && limits.cpu_limit.is_none()
+            && limits.cpuset_cpus.is_none()
+        {
+            return None;
+        }
+        Some(limits)
+    }
+
+    fn read_v2(probe: &dyn Probe, cgroup_root: &Path, proc_cgroup: &str) -> Option<Self> {
+        // Unified hierarchy: a single "0::/path" line.
+        let rel = proc_cgroup
+            .lines()
+            .find_map(|l| l.strip_prefix("0::"))?
+            .trim()
+            .trim_start_matches('/');
+        // Inside a container namespace the process's own cgroup is usually
+        // mounted at the root, so fall back to it when the path is missing.
+        let dir = if probe.is_dir(&cgroup_root.join(rel)) {
+            cgroup_root.join(rel)
+        } else {
+            cgroup_root.to_path_buf()
+        };
+
+        // The effective limit is the tightest one on the way up to the root.
+        let mut memory_limit: Option<u64> = None;
+        let mut cpu_limit: Option<f64> = None;
+        for ancestor in dir.ancestors() {
+            if !ancestor.starts_with(cgroup_root) {
+                break;
+            }
+            if let Some(bytes) = read_cgroup_u64(probe, &ancestor.join("memory.max")) {
+                memory_limit = Some(memory_limit.map_or(bytes, |m| m.min(bytes)));
+            }
+            if let Some(text) = probe.read_file(&ancestor.join("cpu.max"))
+                && let Some(cores) = parse_cpu_max(&text)
+            {
+                cpu_limit = Some(cpu_limit.map_or(cores, |c: f64| c.min(cores)));
+            }
+        }
+
+        let cpuset_cpus = probe
+            .read_file(&dir.join("cpuset.cpus.effective"))
+            .and_then(|t| parse_cpu_list(&t));
+
+        Some(CgroupLimits {
+            version: "cgroup v2".to_string(),
+            path: dir.display().to_string(),
+            memory_limit_gb: memory_limit.map(bytes_to_gb),
+            memory_usage_gb: read_cgroup_usage(probe, &dir, "memory.current", "inactive_file"),
+            cpu_limit,
+            cpuset_cpus,
+        })
+    }
+
+    fn read_v1(probe: &dyn Probe, cgroup_root: &Path, proc_cgroup: &str) -> Option<Self> {
+        // Lines look like "4:memory:/docker/<id>" or "3:cpu,cpuacct:/...".
+        let controller_dir = |controller: &str| -> Option<PathBuf> {
+            let (mount, rel) = proc_cgroup.lines().find_map(|line| {
+                let mut parts = line.splitn(3, ':');
+                let _id = parts.next()?;
+                let controllers = parts.next()?;
+                let rel = parts.next()?;
+                controllers
+                    .split(',')
+                    .any(|c| c == controller)
+                    .then(|| (controllers.to_string(), rel.trim().trim_start_matches('/')))
+            })?;
+            let base = [mount.as_str(), controller]
+                .iter()
+                .map(|m| cgroup_root.join(m))
+                .find(|p| probe.is_dir(p))?;
+            Some(if probe.is_dir(&base.join(rel)) {
+                base.join(rel)
+            } else {
+                base
+            })
+        };
+
+        let memory_dir = controller_dir("memory");
+        let cpu_dir = controller_dir("cpu");
+        if memory_dir.is_none() && cpu_dir.is_none() {
+            return None;
+        }
+
+        let memory_limit_gb = memory_dir
+            .as_ref()
+            .and_then(|d| read_cgroup_u64(probe, &d.join("memory.limit_in_bytes")))
+            .filter(|&b| b < CGROUP_UNLIMITED_BYTES)
+            .map(bytes_to_gb);
+        let memory_usage_gb = memory_dir.as_ref().and_then(|d| {
+            read_cgroup_usage(probe, d, "memory.usage_in_bytes", "total_inactive_file")
+        });
+        let cpu_limit = cpu_dir.as_ref().and_then(|d| {
+            let quota: i64 = probe
+                .read_file(&d.join("cpu.cfs_quota_us"))?
+                .trim()
+                .parse()
+                .ok()?;
+            let period: i64 = probe
+                .read_file(&d.join("cpu.cfs_period_us"))?
+                .trim()
+                .parse()
+                .ok()?;
+            (quota > 0 && period > 0).then(|| quota as f64 / period as f64)
+        });
+        let cpuset_cpus = controller_dir("cpuset").and_then(|d| {
+            probe
+                .read_file(&d.join("cpuset.cpus"))
+                .and_then(|t| parse_cpu_list(&t))
+        });
+
+        Some(CgroupLimits {
+            version: "cgroup v1".to_string(),
+            path: memory_dir
+                .or(cpu_dir)
+                .map(|d| d.display().to_string())
+                .unwrap_or_default(),
+            memory_limit_gb,
+            memory_usage_gb,
+            cpu_limit,
+            cpuset_cpus,
+        })
+    }
+
+    /// Whole cores usable under the quota and cpuset, if either is set.
+    pub fn usable_cores(&self) -> Option<usize> {
+        let quota = self.cpu_limit.map(|c| (c.ceil() as usize).max(1));
+        match (quota, self.cpuset_cpus) {
+            (Some(q), Some(s)) => Some(q.min(s)),
+            (q, s) => q.or(s),
+        }
+    }
+}
+
+fn bytes_to_gb(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+}
+
+/// Read a numeric cgroup file; "max" (no limit) and missing files are `None`.
+fn read_cgroup_u64(probe: &dyn Probe, path: &Path) -> Option<u64> {
+    probe.read_file(path)?.trim().parse().ok()
+}
+
+/// Memory in use from `usage_file`, less the `inactive_file_key` entry of
+/// `memory.stat`: page cache the kernel drops before it would OOM.
+fn read_cgroup_usage(
+    probe: &dyn Probe,
+    dir: &Path,
+    usage_file: &str,
+    inactive_file_key: &str,
+) -> Option<f64> {
+    let usage = read_cgroup_u64(probe, &dir.join(usage_file))?;
+    let inactive = probe
+        .read_file(&dir.join("memory.stat"))
+        .and_then(|stat| {
+            stat.lines().find_map(|line| {
+                let (key, value) = line.split_once(' ')?;
+                (key == inactive_file_key).then(|| value.trim().parse().ok())?
+            })
+        })
+        .unwrap_or(0);
+    Some(bytes_to_gb(usage.saturating_sub(inactive)))
+}
+
+/// Parse cgroup v2 `cpu.max` ("200000 100000" or "max 100000") into cores.
+fn parse_cpu_max(text: &str) -> Option<f64> {
+    let mut parts = text.split_whitespace();
+    let quota: f64 = parts.next()?.parse().ok()?;
+    let period: f64 = parts.next().unwrap_or("100000").parse().ok()?;
+    (quota > 0.0 && period > 0.0).then(|| quota / period)
+}
+
+/// Count CPUs in a list such as "0-3,8,10-11".
+fn parse_cpu_list(text: &str) -> Option<usize> {
+    let mut count = 0;
+    for range in text.trim().split(',').filter(|r| !r.is_empty()) {
+        match range.split_once('-') {
+            Some((lo, hi)) => {
+                let lo: usize = lo.trim().parse().ok()?;
+                let hi: usize = hi.trim().parse().ok()?;
+                count += hi.checked_sub(lo)? + 1;
+            }
+            None => {
+                range.trim().parse::<usize>().ok()?;
+                count += 1;
+            }
+        }
+    }
+    (count > 0).then_some(count)
+}
+
+/// Storage class of the disk that model weights are paged from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum DiskKind {
+    Nvme,
+    Ssd,
+    Hdd,
+}
+
+impl DiskKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiskKind::Nvme => "NVMe",
+            DiskKind::Ssd => "SSD",
+            DiskKind::Hdd => "HDD",
+        }
+    }
+
+    /// Typical sequential read throughput in GB/s: a fixed estimate per
+    /// class (SATA caps SSDs near 0.5 GB/s). NVMe drives are measured by
+    /// their PCIe link instead when sysfs has it.
+    pub fn typical_read_gb_s(&self) -> f64 {
+        match self {
+            DiskKind::Nvme => 3.0,
+            DiskKind::Ssd => 0.5,
+            DiskKind::Hdd => 0.15,
+        }
+    }
+}
+
+/// Swap and disk figures used to judge whether a model larger than RAM can
+/// still run with llama.cpp paging mmapped weights from disk. Page cache
+/// isn't tracked: available RAM already counts it as reclaimable.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StorageInfo {
+    pub swap_total_gb: f64,
+    pub swap_free_gb: f64,
+    /// Block device assumed to hold model files (the fastest local disk).
+    pub disk_name: Option<String>,
+    pub disk_kind: Option<DiskKind>,
+    pub disk_read_gb_s: Option<f64>,
+}
+
+impl StorageInfo {
+    /// Read `/proc/meminfo` and `/sys/block`.
+    pub fn detect(probe: &dyn Probe) -> Option<Self> {
+        if probe.os() != "linux" {
+            return None;
+        }
+        let meminfo = probe.read_file(Path::new("/proc/meminfo"))?;
+        let field_gb = |key: &str| {
+            meminfo
+                .lines()
+                .find_map(|l| l.strip_prefix(key)?.strip_prefix(':'))
+                .and_then(|v| v.split_whitespace().next()?.parse::<f64>().ok())
+                .map(|kb| kb / (1024.0 * 1024.0))
+                .unwrap_or(0.0)
+        };
+
+        let disk = Self::fastest_disk(probe, Path::new("/sys/block"));
+        Some(StorageInfo {
+            swap_total_gb: field_gb("SwapTotal"),
+            swap_free_gb: field_gb("SwapFree"),
+            disk_read_gb_s: disk.as_ref().map(|&(_, _, read)| read),
+            disk_kind: disk.as_ref().map(|&(_, kind, _)| kind),
+            disk_name: disk.map(|(name, _, _)| name),
+        })
+    }
+
+    /// Pick the fastest physical block device, skipping loop, RAM and
+    /// device-mapper entries, with its read speed in GB/s.
+    fn fastest_disk(probe: &dyn Probe, sys_block: &Path) -> Option<(String, DiskKind, f64)> {
+        let virtual_prefixes = ["loop", "ram", "zram", "dm-", "md", "sr", "fd", "nbd"];
+        let mut disks: Vec<(String, DiskKind, f64)> = probe
+            .read_dir(sys_block)?
+            .into_iter()
+            .filter_map(|name| {
+                if virtual_prefixes.iter().any(|p| name.starts_with(p)) {
+                    return None;
+                }
+                let dev = sys_block.join(&name);
+                let size: u64 = probe.read_file(&dev.join("size"))?.trim().parse().ok()?;
+                if size == 0 {
+                    return None;
+                }
+                let kind = if name.starts_with("nvme") {
+                    DiskKind::Nvme
+                } else {
+                    match probe.read_file(&dev.join("queue/rotational")) {
+                        Some(r) if r.trim() == "1" => DiskKind::Hdd,
+                        _ => DiskKind::Ssd,
+                    }
+                };
+                // `device` is the NVMe controller, `device/device` its PCIe
+                // function. Drives sustain ~80% of their link.
+                let pci = dev.join("device").join("device");
+                let link = (kind == DiskKind::Nvme)
+                    .then(|| {
+                        PcieLink::from_sysfs(
+                            &probe.read_file(&pci.join("max_link_speed"))?,
+                            &probe.read_file(&pci.join("max_link_width"))?,
+                        )
+                    })
+                    .flatten();
+                let read = link.map_or(kind.typical_read_gb_s(), |l| l.bandwidth_gb_s() * 0.8);
+                Some((name, kind, read))
+            })
+            .collect();
+        disks.sort_by(|a, b| {
+            b.2.partial_cmp(&a.2)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        disks.into_iter().next()
+    }
+}
+
+/// CPU instruction-set extensions that matter for quantized matmul kernels,
+/// plus the physical core count (SMT siblings add little inference throughput).
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CpuFeatures {
+    pub physical_cores: Option<usize>,
+    pub avx2: bool,
+    pub avx512: bool,
+    /// AVX-VNNI or AVX512-VNNI int8 dot products.
+    pub avx_vnni: bool,
+    pub amx: bool,
+    pub neon: bool,
+    pub sve: bool,
+    pub i8mm: bool,
+}
+
+impl CpuFeatures {
+    /// Read `/proc/cpuinfo`, or use the runtime-probed features on
+    /// platforms without it.
+    pub fn detect(probe: &dyn Probe, sys: &SysInfoSnapshot) -> Option<Self> {
+        match probe.read_file(Path::new("/proc/cpuinfo")) {
+            Some(text) => Some(Self::from_cpuinfo(&text)),
+            None => sys.cpu_features.clone(),
+        }
+    }
+
+    /// Parse the first `flags` (x86) or `Features` (ARM) line of
+    /// `/proc/cpuinfo`; physical cores are the unique (physical id, core id) pairs.
+    pub fn from_cpuinfo(text: &str) -> Self {
+        let field = |line: &str, key: &str| {
+            let (k, v) = line.split_once(':')?;
+            (k.trim() == key).then(|| v.trim().to_string())
+        };
+        let flags: HashSet<String> = text
+            .lines()
+            .find_map(|l| field(l, "flags").or_else(|| field(l, "Features")))
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(|f| f.to_string())
+            .collect();
+        let has = |f: &str| flags.contains(f);
+
+        let mut cores = HashSet::new();
+        let mut physical_id = None;
+        for line in text.lines() {
+            if let Some(id) = field(line, "physical id") {
+                physical_id = Some(id);
+            } else if let Some(core) = field(line, "core id") {
+                cores.insert((physical_id.clone(), core));
+            }
+        }
+
+        CpuFeatures {
+            physical_cores: (!cores.is_empty()).then_some(cores.len()),
+            avx2: has("avx2"),
+            avx512: has("avx512f"),
+            avx_vnni: has("avx_vnni") || has("avx512_vnni"),
+            amx: has("amx_tile"),
+            neon: has("asimd") || has("neon"),
+            sve: has("sve"),
+            i8mm: has("i8mm"),
+        }
+    }
+
+    /// Features of the running CPU where there is no `/proc/cpuinfo`
+    /// (macOS, Windows). Only what the standard library can probe.
+    pub(crate) fn from_runtime_detection() -> Self {
+        #[cfg(target_arch = "x86_64")]
+        {
+            CpuFeatures {
+                avx2: std::arch::is_x86_feature_detected!("avx2"),
+                avx512: std::arch::is_x86_feature_detected!("avx512f"),
+                avx_vnni: std::arch::is_x86_feature_detected!("avx512vnni"),
+                ..Default::default()
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            CpuFeatures {
+                neon: true,
+                i8mm: std::arch::is_aarch64_feature_detected!("i8mm"),
+                sve: std::arch::is_aarch64_feature_detected!("sve"),
+                ..Default::default()
+            }
+        }
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        {
+            CpuFeatures::default()
+        }
+    }
+
+    /// Short names of the detected extensions, for display.
+    pub fn labels(&self) -> Vec<&'static str> {
+        [
+            (self.avx2, "AVX2"),
+            (self.avx512, "AVX-512"),
+            (self.avx_vnni, "VNNI"),
+            (self.amx, "AMX"),
+            (self.neon, "NEON"),
+            (self.sve, "SVE"),
+            (self.i8mm, "i8mm"),
+        ]
+        .into_iter()
+        .filter_map(|(on, label)| on.then_some(label))
+        .collect()
+    }
+}
+
+/// One NUMA node: its local memory and the CPUs attached to it.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NumaNode {
+    pub id: u32,
+    pub total_gb: f64,
+    pub cpus: usize,
+}
+
+/// RAM topology and speed. CPU inference streams every weight per token,
+/// so RAM bandwidth, not compute, usually sets the ceiling.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MemoryTopology {
+    pub numa_nodes: Vec<NumaNode>,
+    /// e.g. "DDR5", "LPDDR5X".
+    pub memory_type: Option<String>,
+    pub speed_mt_s: Option<u32>,
+    /// Populated memory channels; assumed dual-channel when unknown.
+    pub channels: Option<u32>,
+}
+
+impl MemoryTopology {
+    /// Read NUMA nodes from `/sys/devices/system/node` and memory
+    /// type/speed from `dmidecode` (needs root).
+    pub fn detect(probe: &dyn Probe) -> Option<Self> {
+        let mut topo = MemoryTopology {
+            numa_nodes: Self::read_numa_nodes(probe, Path::new("/sys/devices/system/node")),
+            ..Default::default()
+        };
+        if let Some(text) = probe.run_stdout("dmidecode", &["-t", "memory"]) {
+            let (memory_type, speed, channels) = Self::parse_dmidecode(&text);
+            topo.memory_type = memory_type;
+            topo.speed_mt_s = speed;
+            topo.channels = channels;
+        }
+        (!topo.numa_nodes.is_empty() || topo.speed_mt_s.is_some()).then_some(topo)
+    }
+
+    fn read_numa_nodes(probe: &dyn Probe, node_dir: &Path) -> Vec<NumaNode> {
+        let Some(entries) = probe.read_dir(node_dir) else {
+            return Vec::new();
+        };
+        let mut nodes: Vec<NumaNode> = entries
+            .into_iter()
+            .filter_map(|name| {
+                let id: u32 = name.strip_prefix("node")?.parse().ok()?;
+                let node = node_dir.join(&name);
+                // "Node 0 MemTotal:       65536000 kB"
+                let meminfo = probe.read_file(&node.join("meminfo"))?;
+                let total_kb: f64 = meminfo.lines().find_map(|l| {
+                    let (key, value) = l.split_once(':')?;
+                    key.ends_with("MemTotal")
+                        .then(|| value.split_whitespace().next()?.parse().ok())?
+                })?;
+                let cpus = probe
+                    .read_file(&node.join("cpulist"))
+                    .and_then(|list| parse_cpu_list(&list))
+                    .unwrap_or(0);
+                Some(NumaNode {
+                    id,
+                    total_gb: total_kb / (1024.0 * 1024.0),
+                    cpus,
+                })
+            })
+            .collect();
+        nodes.sort_by_key(|n| n.id);
+        nodes
+    }
+
+    /// Parse `dmidecode -t memory` into (type, speed in MT/s, channels).
+    /// Channels are the distinct channels named by the bank locators ("P0
+    /// CHANNEL A") or DIMM locators ("ChannelA-DIMM0", "DIMM_A1"). Without
+    /// any, channels stay unknown: two DIMMs may share one channel.
+    pub fn parse_dmidecode(text: &str) -> (Option<String>, Option<u32>, Option<u32>) {
+        let mut memory_type = None;
+        let mut speed = None;
+        let mut channels = HashSet::new();
+        for device in text.split("Memory Device").skip(1) {
+            let field = |key: &str| {
+                device.lines().find_map(|l| {
+                    let (k, v) = l.trim().split_once(':')?;
+                    (k == key).then(|| v.trim().to_string())
+                })
+            };
+            let populated = field("Size").is_some_and(|s| s.ends_with("B") && !s.starts_with('0'));
+            if !populated {
+                continue;
+            }
+            if memory_type.is_none() {
+                memory_type = field("Type").filter(|t| t != "Unknown" && t != "Other");
+            }
+            let dimm_speed = field("Configured Memory Speed")
+                .or_else(|| field("Speed"))
+                .and_then(|v| v.split_whitespace().next()?.parse::<u32>().ok());
+            if let Some(mt_s) = dimm_speed {
+                speed = Some(speed.map_or(mt_s, |s: u32| s.min(mt_s)));
+            }
+            if let Some(channel) = field("Bank Locator")
+                .and_then(|l| dimm_channel(&l))
+                .or_else(|| field("Locator").and_then(|l| dimm_channel(&l)))
+            {
+                channels.insert(channel);
+            }
+        }
+        let channels = (!channels.is_empty()).then_some(channels.len() as u32);
+        (memory_type, speed, channels)
+    }
+
+    /// Peak RAM bandwidth in GB/s: MT/s x 8 bytes per 64-bit channel.
+    pub fn bandwidth_gb_s(&self) -> Option<f64> {
+        let speed = self.speed_mt_s? as f64;
+        Some(speed * 8.0 * self.channels.unwrap_or(2) as f64 / 1000.0)
+    }
+
+    /// Local memory of the largest NUMA node, if the system has several.
+    pub fn largest_node_gb(&self) -> Option<f64> {
+        if self.numa_nodes.len() < 2 {
+            return None;
+        }
+        self.numa_nodes
+            .iter()
+            .map(|n| n.total_gb)
+            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+    }
+}
+
+/// Channel named by a DIMM locator: "P0 CHANNEL A" and "ChannelA-DIMM0"
+/// give "A", as does the letter in "DIMM_A1". Plain "DIMM0" or "BANK 0"
+/// names a slot, not a channel.
+fn dimm_channel(locator: &str) -> Option<String> {
+    let upper = locator.to_uppercase();
+    let is_sep = |c: char| matches!(c, ' ' | '_' | '-');
+    if let Some(idx) = upper.find("CHANNEL") {
+        let rest = upper[idx + "CHANNEL".len()..].trim_start_matches(is_sep);
+        let id: String = rest.chars().take_while(|c| !is_sep(*c)).collect();
+        return (!id.is_empty()).then_some(id);
+    }
+    let idx = upper.find("DIMM")?;
+    let letter = upper[idx + "DIMM".len()..]
+        .trim_start_matches(is_sep)
+        .chars()
+        .next()
+        .filter(|c| c.is_ascii_alphabetic())?;
+    Some(letter.to_string())
+}
+
+/// Parse a RAM speed override like "DDR5-5600" or "DDR4-3200x8" (8 channels)
+/// into (type, MT/s, channels).
+pub fn parse_memory_speed(s: &str) -> Option<(String, u32, Option<u32>)> {
+    let (memory_type, rest) = s.trim().rsplit_once('-')?;
+    let (speed, channels) = match rest.to_lowercase().split_once('x') {
+        Some((speed, channels)) => (speed.parse().ok()?, Some(channels.parse().ok()?)),
+        None => (rest.parse().ok()?, None),
+    };
+    if memory_type.is_empty() || speed == 0 || channels == Some(0) {
+        return None;
+    }
+    Some((memory_type.to_uppercase(), speed, channels))
+}
+
+/// Known embedded boards: (match in device-tree model/compatible, peak RAM
+/// bandwidth GB/s, CPU speed relative to a desktop core of the same ISA).
+/// Most lack DMI tables, so `dmidecode` can't supply the bandwidth.
+const BOARD_PROFILES: &[(&str, f64, f64)] = &[
+    ("jetson agx orin", 204.8, 0.8),
+    ("jetson orin nx", 102.4, 0.7),
+    ("jetson orin nano", 68.0, 0.6),
+    ("jetson agx xavier", 136.5, 0.6),
+    ("jetson xavier nx", 59.7, 0.5),
+    ("jetson nano", 25.6, 0.3),
+    ("raspberry pi 5", 17.1, 0.5),
+    ("raspberry pi 4", 12.8, 0.3),
+    ("rockchip,rk3588", 34.1, 0.55),
+];
+
+/// Embedded board or module identified from the device tree (Jetson,
+/// Raspberry Pi, RK3588 boards).
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BoardInfo {
+    /// `/proc/device-tree/model`, e.g. "NVIDIA Jetson AGX Orin Developer Kit".
+    pub model: String,
+    /// NVIDIA Tegra SoC: CUDA on an integrated GPU sharing system RAM.
+    pub tegra: bool,
+    /// Peak RAM bandwidth from the board profile.
+    pub memory_bandwidth_gb_s: Option<f64>,
+    /// CPU speed multiplier from the board profile.
+    pub cpu_speed_factor: Option<f64>,
+}
+
+impl BoardInfo {
+    /// Read the device tree and `/sys/devices/soc0`. `None` on machines
+    /// without a device tree (PCs, servers).
+    pub fn detect(probe: &dyn Probe) -> Option<Self> {
+        if probe.os() != "linux" {
+            return None;
+        }
+        // Device-tree strings are NUL-terminated; compatible is a NUL-separated list.
+        let read = |path: &str| {
+            probe
+                .read_file(Path::new(path))
+                .map(|s| s.replace('\0', " ").trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        let model = read("/proc/device-tree/model");
+        let compatible = read("/proc/device-tree/compatible").unwrap_or_default();
+        let family = read("/sys/devices/soc0/family").unwrap_or_default();
+        if model.is_none() && family.is_empty() {
+            return None;
+        }
+        let model = model.unwrap_or_else(|| family.clone());
+
+        let haystack = format!("{} {}", model, compatible).to_lowercase();
+        let tegra = family.eq_ignore_ascii_case("tegra")
+            || haystack.contains("jetson")
+            || haystack.contains("nvidia,tegra");
+        let profile = BOARD_PROFILES
+            .iter()
+            .find(|(pattern, _, _)| haystack.contains(pattern));
+        Some(BoardInfo {
+            model,
+            tegra,
+            memory_bandwidth_gb_s: profile.map(|p| p.1),
+            cpu_speed_factor: profile.map(|p| p.2),
+        })
+    }
+}
+
+/// Power source and GPU power limits. Laptop GPUs run at a fraction of
+/// their rated power on battery, and sometimes on weak chargers too.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PowerState {
+    /// Running from the battery (no charger online).
+    pub on_battery: bool,
+    pub battery_percent: Option<u8>,
+    /// Power limit the first NVIDIA GPU currently enforces, in watts.
+    pub gpu_power_limit_w: Option<f64>,
+    /// Highest limit that GPU can be set to, in watts.
+    pub gpu_max_power_limit_w: Option<f64>,
+}
+
+impl PowerState {
+    /// Read the power source and GPU power limits. `None` on machines
+    /// without a battery whose GPU runs at its full limit (or has none).
+    pub fn detect(probe: &dyn Probe) -> Option<Self> {
+        let mut state = PowerState::default();
+        let mut has_battery = false;
+        match probe.os() {
+            "linux" => {
+                let mut supplies = probe
+                    .read_dir(Path::new("/sys/class/power_supply"))
+                    .unwrap_or_default();
+                supplies.sort();
+                let mut saw_mains = false;
+                let mut mains_online = false;
+                let mut discharging = false;
+                for supply in supplies {
+                    let read = |file: &str| {
+                        probe
+                            .read_file(Path::new(&format!(
+                                "/sys/class/power_supply/{}/{}",
+                                supply, file
+                            )))
+                            .map(|s| s.trim().to_string())
+                    };
+                    match read("type").as_deref() {
+                        Some("Mains") | Some("USB") => {
+                            saw_mains = true;
+                            mains_online |= read("online").as_deref() == Some("1");
+                        }
+                        // Mice and headsets report scope "Device".
+                        Some("Battery") if read("scope").as_deref() != Some("Device") => {
+                            has_battery = true;
+                            discharging |= read("status").as_deref() == Some("Discharging");
+                            if state.battery_percent.is_none() {
+                                state.battery_percent =
+                                    read("capacity").and_then(|c| c.parse().ok());
+                            }
+                        }
+                        _ => {}
                     }
                 }
+                state.on_battery = has_battery
+                    && if saw_mains {
+                        !mains_online
+                    } else {
+                        discharging
+                    };
+            }
+            "macos" => {
+                if let Some(text) = probe.run_stdout("pmset", PMSET_BATTERY_QUERY)
+                    && let Some((on_battery, percent)) = parse_pmset_battery(&text)
+                {
+                    has_battery = percent.is_some();
+                    state.on_battery = on_battery;
+                    state.battery_percent = percent;
+                }
             }
+            _ => {}
         }
-        println!();
+
+        if let Some((limit, max)) = probe
+            .run_stdout("nvidia-smi", NVIDIA_POWER_QUERY)
+            .as_deref()
+            .and_then(parse_nvidia_power_limits)
+        {
+            state.gpu_power_limit_w = Some(limit);
+            state.gpu_max_power_limit_w = Some(max);
+        }
+
+        let throttled = state.gpu_power_ratio().is_some_and(|r| r < 0.95);
+        (has_battery || throttled).then_some(state)
     }
-}
 
-/// Parse a human-readable memory size string into gigabytes.
-/// Accepts formats: "32G", "32g", "32GB", "32gb", "32000M", "32000m", "32000MB", etc.
-/// Returns `None` if the input is malformed.
-pub fn parse_memory_size(s: &str) -> Option<f64> {
-    let s = s.trim();
-    if s.is_empty() {
-        return None;
+    /// Enforced over maximum GPU power limit.
+    pub fn gpu_power_ratio(&self) -> Option<f64> {
+        match (self.gpu_power_limit_w, self.gpu_max_power_limit_w) {
+            (Some(limit), Some(max)) if max > 0.0 => Some((limit / max).min(1.0)),
+            _ => None,
+        }
     }
 
-    // Split into numeric part and suffix
-    let num_end = s
-        .find(|c: char| !c.is_ascii_digit() && c != '.')
-        .unwrap_or(s.len());
-    let (num_str, suffix) = s.split_at(num_end);
-    let value: f64 = num_str.parse().ok()?;
-    if value < 0.0 {
-        return None;
+    /// Short description for notes and the TUI, e.g. "battery 54%".
+    pub fn label(&self) -> String {
+        let source = match (self.on_battery, self.battery_percent) {
+            (true, Some(pct)) => format!("battery {}%", pct),
+            (true, None) => "battery".to_string(),
+            (false, _) => "AC".to_string(),
+        };
+        match (self.gpu_power_limit_w, self.gpu_max_power_limit_w) {
+            (Some(limit), Some(max)) if limit < max => {
+                format!("{}, GPU limit {:.0} W of {:.0} W", source, limit, max)
+            }
+            _ => source,
+        }
     }
+}
 
-    let suffix = suffix.trim().to_lowercase();
-    match suffix.as_str() {
-        "g" | "gb" | "gib" | "" => Some(value),     // already in GB
-        "m" | "mb" | "mib" => Some(value / 1024.0), // MB → GB
-        "t" | "tb" | "tib" => Some(value * 1024.0), // TB → GB
-        _ => None,
+/// Parse `nvidia-smi --query-gpu=power.limit,power.max_limit` output for
+/// the first GPU: "80.00, 140.00".
+fn parse_nvidia_power_limits(text: &str) -> Option<(f64, f64)> {
+    let line = text.lines().next()?;
+    let mut fields = line.split(',').map(|f| f.trim().parse::<f64>().ok());
+    Some((fields.next()??, fields.next()??))
+}
+
+/// Parse `pmset -g batt`: the power source from the first line and the
+/// charge from the "-InternalBattery-0 ... 54%; discharging" line.
+fn parse_pmset_battery(text: &str) -> Option<(bool, Option<u8>)> {
+    let first = text.lines().next()?;
+    let on_battery = first.contains("'Battery Power'");
+    if !on_battery && !first.contains("'AC Power'") {
+        return None;
     }
+    let percent = text
+        .lines()
+        .filter(|l| l.contains("InternalBattery"))
+        .find_map(|l| {
+            let (before, _) = l.split_once('%')?;
+            let digits = before.rsplit(|c: char| !c.is_ascii_digit()).next()?;
+            digits.parse().ok()
+        });
+    Some((on_battery, percent))
 }
 
-pub fn is_running_in_wsl() -> bool {
-    static IS_WSL: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
-    *IS_WSL.get_or_init(detect_running_in_wsl)
+/// Known NPUs: (kernel driver, PCI device ID or "" for any, name, INT8 TOPS).
+const NPU_PROFILES: &[(&str, &str, &str, f64)] = &[
+    (
+        "intel_vpu",
+        "7d1d",
+        "Intel AI Boost NPU (Meteor Lake)",
+        11.0,
+    ),
+    ("intel_vpu", "ad1d", "Intel AI Boost NPU (Arrow Lake)", 13.0),
+    ("intel_vpu", "643e", "Intel AI Boost NPU (Lunar Lake)", 48.0),
+    (
+        "intel_vpu",
+        "b03e",
+        "Intel AI Boost NPU (Panther Lake)",
+        50.0,
+    ),
+    ("intel_vpu", "", "Intel NPU", 11.0),
+    ("amdxdna", "1502", "AMD Ryzen AI NPU (XDNA)", 16.0),
+    ("amdxdna", "17f0", "AMD Ryzen AI NPU (XDNA 2)", 50.0),
+    ("amdxdna", "", "AMD Ryzen AI NPU", 16.0),
+    ("rocket", "", "Rockchip RKNPU", 6.0),
+];
+
+/// A neural processing unit. NPUs share system RAM and run small models
+/// through their vendor runtime (OpenVINO, Ryzen AI/ONNX Runtime, RKLLM).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NpuInfo {
+    pub name: String,
+    /// Kernel driver: `intel_vpu`, `amdxdna`, `rocket` or `rknpu`.
+    pub driver: String,
+    /// Peak INT8 TOPS from the device profile.
+    pub tops: Option<f64>,
 }
 
-fn detect_running_in_wsl() -> bool {
-    if !cfg!(target_os = "linux") {
-        return false;
+impl NpuInfo {
+    /// Find NPUs through `/sys/class/accel` and, for Rockchip's vendor
+    /// kernels, the `rknpu` module.
+    pub fn detect(probe: &dyn Probe) -> Vec<Self> {
+        if probe.os() != "linux" {
+            return Vec::new();
+        }
+        let mut npus = Vec::new();
+        let mut nodes = probe
+            .read_dir(Path::new("/sys/class/accel"))
+            .unwrap_or_default();
+        nodes.sort();
+        for node in nodes.iter().filter(|n| n.starts_with("accel")) {
+            let Some(uevent) = probe.read_file(Path::new(&format!(
+                "/sys/class/accel/{}/device/uevent",
+                node
+            ))) else {
+                continue;
+            };
+            let field = |key: &str| {
+                uevent
+                    .lines()
+                    .find_map(|l| l.strip_prefix(key))
+                    .map(|v| v.trim().to_lowercase())
+            };
+            let Some(driver) = field("DRIVER=") else {
+                continue;
+            };
+            // PCI_ID=8086:643E
+            let device = field("PCI_ID=")
+                .and_then(|id| id.split(':').nth(1).map(str::to_string))
+                .unwrap_or_default();
+            if let Some(npu) = Self::from_profile(&driver, &device) {
+                npus.push(npu);
+            }
+        }
+        if npus.is_empty() && probe.is_dir(Path::new("/sys/module/rknpu")) {
+            npus.push(NpuInfo {
+                driver: "rknpu".to_string(),
+                ..Self::from_profile("rocket", "").expect("rocket profile")
+            });
+        }
+        npus
     }
 
-    if std::env::var_os("WSL_INTEROP").is_some() || std::env::var_os("WSL_DISTRO_NAME").is_some() {
-        return true;
+    fn from_profile(driver: &str, device: &str) -> Option<Self> {
+        NPU_PROFILES
+            .iter()
+            .find(|(d, id, _, _)| *d == driver && (id.is_empty() || *id == device))
+            .map(|&(driver, _, name, tops)| NpuInfo {
+                name: name.to_string(),
+                driver: driver.to_string(),
+                tops: Some(tops),
+            })
     }
 
-    ["/proc/sys/kernel/osrelease", "/proc/version"]
+    /// Largest model (billions of parameters) worth running on this NPU.
+    /// Vendor runtimes cap context and throughput well below a GPU's.
+    pub fn max_params_b(&self) -> f64 {
+        if self.tops.unwrap_or(0.0) >= 40.0 {
+            8.0
+        } else {
+            4.0
+        }
+    }
+}
+
+/// Free space (GB) on the filesystem holding `path`. The path need not exist
+/// yet; its nearest existing ancestor decides which mount it lands on.
+pub fn free_disk_gb(path: &Path) -> Option<f64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let resolved = existing.canonicalize().ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    let mounts: Vec<(PathBuf, u64)> = disks
         .iter()
-        .any(|path| {
-            std::fs::read_to_string(path)
-                .map(|text| text.to_ascii_lowercase().contains("microsoft"))
-                .unwrap_or(false)
-        })
+        .map(|d| (d.mount_point().to_path_buf(), d.available_space()))
+        .collect();
+    mount_free_gb(&resolved, &mounts)
+}
+
+/// Available bytes of the deepest mount point containing `path`, in GB.
+fn mount_free_gb(path: &Path, mounts: &[(PathBuf, u64)]) -> Option<f64> {
+    mounts
+        .iter()
+        .filter(|(mount, _)| path.starts_with(mount))
+        .max_by_key(|(mount, _)| mount.components().count())
+        .map(|(_, bytes)| bytes_to_gb(*bytes))
 }
 
 /// Fallback VRAM estimation from GPU model name.
 /// Used when nvidia-smi or other tools report 0 VRAM.
-fn estimate_vram_from_name(name: &str) -> f64 {
+pub(crate) fn estimate_vram_from_name(name: &str) -> f64 {
     let lower = name.to_lowercase();
     // NVIDIA RTX 50 series
     if lower.contains("5090") {
@@ -1075,7 +3403,26 @@ fn estimate_vram_from_name(name: &str) -> f64 {
 
 #[cfg(test)]
 mod tests {
-    use super::SystemSpecs;
+    use super::{
+        BoardInfo, CgroupLimits, CpuFeatures, DiskKind, GpuBackend, GpuInfo, MemoryTopology,
+        NpuInfo, PcieLink, PowerState, ProbeStep, StorageInfo, SystemSpecs,
+        estimate_vram_from_name, mount_free_gb, parse_cpu_list, parse_memory_speed,
+        parse_nvidia_power_limits, parse_pmset_battery,
+    };
+    use crate::probe::{CommandOutput, HardwareDump, LiveProbe, ReplayProbe, SysInfoSnapshot};
+    use std::path::{Path, PathBuf};
+
+    /// Build a fake filesystem root from (relative path, contents) pairs.
+    fn fixture_root(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("llmfit-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for (path, contents) in files {
+            let full = root.join(path);
+            std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+            std::fs::write(full, contents).unwrap();
+        }
+        root
+    }
 
     #[test]
     fn test_parse_nvidia_smi_does_not_sum_multi_gpu_vram() {
@@ -1100,4 +3447,957 @@ mod tests {
         assert!(gpus.iter().any(|g| g.name.contains("4090") && g.count == 1));
         assert!(gpus.iter().any(|g| g.name.contains("4080") && g.count == 1));
     }
+
+    #[test]
+    fn test_parse_nvidia_smi_reads_used_memory() {
+        let text = "24564, 3100, NVIDIA GeForce RTX 4090\n24564, 1024, NVIDIA GeForce RTX 4090\n";
+        let gpus = SystemSpecs::parse_nvidia_smi_list(text);
+
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].count, 2);
+        // Grouped cards report the busiest card.
+        let used = gpus[0].vram_used_gb.expect("used VRAM should be parsed");
+        assert!((used - 3100.0 / 1024.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_tolerates_missing_fields() {
+        let text = "[N/A], [N/A], NVIDIA GeForce RTX 3090\n8192, NVIDIA T4, Rev 2\n";
+        let gpus = SystemSpecs::parse_nvidia_smi_list(text);
+
+        let rtx = gpus.iter().find(|g| g.name.contains("3090")).unwrap();
+        assert_eq!(rtx.vram_gb, Some(24.0)); // estimated from the name
+        assert_eq!(rtx.vram_used_gb, None);
+        let t4 = gpus.iter().find(|g| g.name.contains("T4")).unwrap();
+        assert_eq!(t4.name, "NVIDIA T4,Rev 2");
+        assert_eq!(t4.vram_gb, Some(8.0));
+    }
+
+    #[test]
+    fn test_with_free_vram_uses_unused_memory() {
+        let specs = SystemSpecs::parse_nvidia_smi_list("24576, 4096, NVIDIA GeForce RTX 4090\n");
+        let system = SystemSpecs {
+            total_ram_gb: 64.0,
+            available_ram_gb: 48.0,
+            total_cpu_cores: 16,
+            cpu_name: "Test CPU".to_string(),
+            has_gpu: true,
+            gpu_vram_gb: Some(24.0),
+            gpu_name: Some(specs[0].name.clone()),
+            gpu_count: 1,
+            unified_memory: false,
+            backend: GpuBackend::Cuda,
+            gpus: specs,
+            cgroup_limits: None,
+            storage: None,
+            cpu_features: None,
+            memory_topology: None,
+            detection: Default::default(),
+            board: None,
+            npus: Vec::new(),
+            power: None,
+        };
+
+        assert_eq!(system.clone().with_free_vram().gpu_vram_gb, Some(20.0));
+    }
+
+    #[test]
+    fn test_from_json_reads_system_dump() {
+        let dump = serde_json::json!({
+            "system": {
+                "total_ram_gb": 64.0,
+                "available_ram_gb": 48.5,
+                "cpu_cores": 16,
+                "cpu_name": "AMD Ryzen 9 7950X",
+                "has_gpu": true,
+                "gpu_vram_gb": 24.0,
+                "gpu_name": "NVIDIA GeForce RTX 4090",
+                "gpu_count": 2,
+                "unified_memory": false,
+                "backend": "CUDA",
+                "gpus": [{
+                    "name": "NVIDIA GeForce RTX 4090",
+                    "vram_gb": 24.0,
+                    "backend": "CUDA",
+                    "count": 2,
+                    "unified_memory": false,
+                }],
+            }
+        });
+        let specs = SystemSpecs::from_json(&dump).expect("dump should parse");
+
+        assert_eq!(specs.total_cpu_cores, 16);
+        assert_eq!(specs.available_ram_gb, 48.5);
+        assert_eq!(specs.backend, GpuBackend::Cuda);
+        assert_eq!(specs.gpus.len(), 1);
+        assert_eq!(specs.gpus[0].count, 2);
+        assert_eq!(specs.gpu_vram_gb, Some(24.0));
+    }
+
+    #[test]
+    fn test_from_json_rejects_unknown_backend() {
+        let dump = serde_json::json!({
+            "total_ram_gb": 8.0,
+            "available_ram_gb": 4.0,
+            "backend": "Quantum",
+        });
+        assert!(SystemSpecs::from_json(&dump).is_err());
+
+        // Present but malformed nested fields are errors, not dropped.
+        let dump = serde_json::json!({
+            "total_ram_gb": 8.0,
+            "available_ram_gb": 4.0,
+            "backend": "CUDA",
+            "storage": {"swap_total_gb": "lots"},
+            "board": null,
+        });
+        let err = SystemSpecs::from_json(&dump).unwrap_err();
+        assert!(err.contains("'storage'"), "{}", err);
+
+        // So are mistyped scalars.
+        for (key, value) in [
+            ("cpu_cores", serde_json::json!("16")),
+            ("gpu_count", serde_json::json!(1.5)),
+            ("gpu_vram_gb", serde_json::json!("24 GB")),
+            ("has_gpu", serde_json::json!("yes")),
+        ] {
+            let mut dump = serde_json::json!({
+                "total_ram_gb": 8.0,
+                "available_ram_gb": 4.0,
+                "backend": "CUDA",
+            });
+            dump[key] = value;
+            let err = SystemSpecs::from_json(&dump).unwrap_err();
+            assert!(err.contains(&format!("'{}'", key)), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_backend_label_round_trip() {
+        for backend in [
+            GpuBackend::Cuda,
+            GpuBackend::Metal,
+            GpuBackend::Rocm,
+            GpuBackend::Vulkan,
+            GpuBackend::Sycl,
+            GpuBackend::CpuArm,
+            GpuBackend::CpuX86,
+        ] {
+            assert_eq!(GpuBackend::from_label(backend.label()), Some(backend));
+        }
+    }
+
+    #[test]
+    fn test_cgroup_v2_limits_use_tightest_ancestor() {
+        let root = fixture_root(
+            "cgroup-v2",
+            &[
+                ("proc/self/cgroup", "0::/kubepods/pod1/ctr\n"),
+                ("sys/fs/cgroup/cgroup.controllers", "cpu memory"),
+                ("sys/fs/cgroup/kubepods/memory.max", "17179869184\n"),
+                ("sys/fs/cgroup/kubepods/pod1/ctr/memory.max", "max\n"),
+                (
+                    "sys/fs/cgroup/kubepods/pod1/ctr/memory.current",
+                    "6442450944\n",
+                ),
+                (
+                    "sys/fs/cgroup/kubepods/pod1/ctr/memory.stat",
+                    "anon 4294967296\nfile 2147483648\ninactive_file 2147483648\n",
+                ),
+                ("sys/fs/cgroup/kubepods/pod1/ctr/cpu.max", "250000 100000\n"),
+                (
+                    "sys/fs/cgroup/kubepods/pod1/ctr/cpuset.cpus.effective",
+                    "0-7\n",
+                ),
+            ],
+        );
+        let limits =
+            CgroupLimits::detect(&LiveProbe::with_root(&root), 16).expect("limits should be found");
+        std::fs::remove_dir_all(&root).unwrap();
+
+        // Inactive page cache doesn't count against the headroom.
+        assert_eq!(limits.version, "cgroup v2");
+        assert_eq!(limits.memory_limit_gb, Some(16.0));
+        assert_eq!(limits.memory_usage_gb, Some(4.0));
+        assert_eq!(limits.cpu_limit, Some(2.5));
+        assert_eq!(limits.usable_cores(), Some(3));
+    }
+
+    #[test]
+    fn test_cgroup_v2_unlimited_is_none() {
+        let root = fixture_root(
+            "cgroup-v2-unlimited",
+            &[
+                ("proc/self/cgroup", "0::/\n"),
+                ("sys/fs/cgroup/cgroup.controllers", "cpu memory"),
+                ("sys/fs/cgroup/memory.max", "max\n"),
+                ("sys/fs/cgroup/cpu.max", "max 100000\n"),
+                ("sys/fs/cgroup/cpuset.cpus.effective", "0-15\n"),
+            ],
+        );
+        // A cpuset covering every host CPU is no limit either.
+        let limits = CgroupLimits::detect(&LiveProbe::with_root(&root), 16);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(limits, None);
+    }
+
+    #[test]
+    fn test_cgroup_v1_limits() {
+        let root = fixture_root(
+            "cgroup-v1",
+            &[
+                (
+                    "proc/self/cgroup",
+                    "5:memory:/docker/abc\n4:cpu,cpuacct:/docker/abc\n",
+                ),
+                ("sys/fs/cgroup/memory/memory.limit_in_bytes", "8589934592\n"),
+                ("sys/fs/cgroup/memory/memory.usage_in_bytes", "3221225472\n"),
+                (
+                    "sys/fs/cgroup/memory/memory.stat",
+                    "cache 2147483648\ntotal_inactive_file 2147483648\n",
+                ),
+                ("sys/fs/cgroup/cpu,cpuacct/cpu.cfs_quota_us", "200000\n"),
+                ("sys/fs/cgroup/cpu,cpuacct/cpu.cfs_period_us", "100000\n"),
+            ],
+        );
+        let limits =
+            CgroupLimits::detect(&LiveProbe::with_root(&root), 16).expect("limits should be found");
+        std::fs::remove_dir_all(&root).unwrap();
+
+        // The container's own cgroup is mounted at the controller root.
+        assert_eq!(limits.version, "cgroup v1");
+        assert_eq!(limits.memory_limit_gb, Some(8.0));
+        assert_eq!(limits.memory_usage_gb, Some(1.0));
+        assert_eq!(limits.usable_cores(), Some(2));
+    }
+
+    #[test]
+    fn test_cgroup_v1_unlimited_memory_is_ignored() {
+        let root = fixture_root(
+            "cgroup-v1-unlimited",
+            &[
+                ("proc/self/cgroup", "5:memory:/\n"),
+                (
+                    "sys/fs/cgroup/memory/memory.limit_in_bytes",
+                    "9223372036854771712\n",
+                ),
+            ],
+        );
+        let limits = CgroupLimits::detect(&LiveProbe::with_root(&root), 16);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(limits, None);
+    }
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("0-3,8,10-11\n"), Some(7));
+        assert_eq!(parse_cpu_list("5"), Some(1));
+        assert_eq!(parse_cpu_list(""), None);
+    }
+
+    #[test]
+    fn test_storage_info_prefers_nvme_and_reads_swap() {
+        let root = fixture_root(
+            "storage",
+            &[
+                (
+                    "proc/meminfo",
+                    "MemTotal:       32768000 kB\nCached:          4194304 kB\nSwapTotal:       8388608 kB\nSwapFree:        6291456 kB\n",
+                ),
+                ("sys/block/sda/size", "1000000\n"),
+                ("sys/block/sda/queue/rotational", "1\n"),
+                ("sys/block/nvme0n1/size", "2000000\n"),
+                (
+                    "sys/block/nvme0n1/device/device/max_link_speed",
+                    "16.0 GT/s PCIe\n",
+                ),
+                ("sys/block/nvme0n1/device/device/max_link_width", "4\n"),
+                ("sys/block/nvme1n1/size", "2000000\n"),
+                ("sys/block/loop0/size", "100\n"),
+            ],
+        );
+        let storage =
+            StorageInfo::detect(&LiveProbe::with_root(&root)).expect("storage should be read");
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(storage.swap_total_gb, 8.0);
+        assert_eq!(storage.swap_free_gb, 6.0);
+        // The Gen4 x4 drive beats the one without a readable link.
+        assert_eq!(storage.disk_name.as_deref(), Some("nvme0n1"));
+        assert_eq!(storage.disk_kind, Some(DiskKind::Nvme));
+        assert!((storage.disk_read_gb_s.unwrap() - 1.969 * 4.0 * 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mount_free_gb_picks_deepest_mount() {
+        let gb = 1024 * 1024 * 1024;
+        let mounts = vec![
+            (PathBuf::from("/"), 10 * gb),
+            (PathBuf::from("/home"), 200 * gb),
+            (PathBuf::from("/home2"), gb),
+        ];
+        let free = |p: &str| mount_free_gb(Path::new(p), &mounts);
+
+        assert_eq!(free("/home/me/.ollama/models"), Some(200.0));
+        assert_eq!(free("/home2/cache"), Some(1.0));
+        assert_eq!(free("/var/lib"), Some(10.0));
+        assert_eq!(mount_free_gb(Path::new("/x"), &[]), None);
+    }
+
+    #[test]
+    fn test_cpu_features_from_cpuinfo() {
+        let x86 = "processor\t: 0\nphysical id\t: 0\ncore id\t\t: 0\nflags\t\t: fpu sse4_2 avx2 avx512f avx512_vnni amx_tile\n\n\
+                   processor\t: 1\nphysical id\t: 0\ncore id\t\t: 0\nflags\t\t: fpu\n\n\
+                   processor\t: 2\nphysical id\t: 0\ncore id\t\t: 1\nflags\t\t: fpu\n";
+        let f = CpuFeatures::from_cpuinfo(x86);
+        assert_eq!(f.physical_cores, Some(2));
+        assert!(f.avx2 && f.avx512 && f.avx_vnni && f.amx);
+        assert!(!f.neon);
+        assert_eq!(f.labels(), vec!["AVX2", "AVX-512", "VNNI", "AMX"]);
+
+        let arm = "processor\t: 0\nFeatures\t: fp asimd sve i8mm\n";
+        let f = CpuFeatures::from_cpuinfo(arm);
+        assert_eq!(f.physical_cores, None);
+        assert!(f.neon && f.sve && f.i8mm && !f.avx2);
+
+        let atom = "processor\t: 0\nflags\t\t: fpu sse2 ssse3\n";
+        assert!(CpuFeatures::from_cpuinfo(atom).labels().is_empty());
+    }
+
+    #[test]
+    fn test_memory_topology_numa_and_dmidecode() {
+        let root = fixture_root(
+            "numa",
+            &[
+                (
+                    "sys/devices/system/node/node0/meminfo",
+                    "Node 0 MemTotal:       67108864 kB\nNode 0 MemFree:        1024 kB\n",
+                ),
+                ("sys/devices/system/node/node0/cpulist", "0-15\n"),
+                (
+                    "sys/devices/system/node/node1/meminfo",
+                    "Node 1 MemTotal:       33554432 kB\n",
+                ),
+                ("sys/devices/system/node/node1/cpulist", "16-31\n"),
+                ("sys/devices/system/node/possible", "0-1\n"),
+            ],
+        );
+        let topo = MemoryTopology::detect(&LiveProbe::with_root(&root))
+            .expect("NUMA nodes should be read");
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(topo.numa_nodes.len(), 2);
+        assert_eq!(topo.numa_nodes[0].total_gb, 64.0);
+        assert_eq!(topo.numa_nodes[1].cpus, 16);
+        assert_eq!(topo.largest_node_gb(), Some(64.0));
+        assert_eq!(topo.bandwidth_gb_s(), None);
+
+        let dmi = "Memory Device\n\tSize: 32 GB\n\tType: DDR5\n\tSpeed: 5600 MT/s\n\
+                   \tBank Locator: P0 CHANNEL A\n\tConfigured Memory Speed: 4800 MT/s\n\n\
+                   Memory Device\n\tSize: No Module Installed\n\tType: Unknown\n\n\
+                   Memory Device\n\tSize: 32 GB\n\tType: DDR5\n\tSpeed: 5600 MT/s\n\
+                   \tBank Locator: P0 CHANNEL B\n\tConfigured Memory Speed: 4800 MT/s\n";
+        let (memory_type, speed, channels) = MemoryTopology::parse_dmidecode(dmi);
+        assert_eq!(memory_type.as_deref(), Some("DDR5"));
+        assert_eq!(speed, Some(4800));
+        assert_eq!(channels, Some(2));
+
+        // Channels from the DIMM locator when the bank locator is "BANK n".
+        let dimm = |bank: &str, locator: &str| {
+            format!(
+                "Memory Device\n\tSize: 16 GB\n\tType: DDR4\n\tSpeed: 3200 MT/s\n\
+                 \tLocator: {}\n\tBank Locator: {}\n",
+                locator, bank
+            )
+        };
+        let two_per_channel = [
+            dimm("BANK 0", "ChannelA-DIMM0"),
+            dimm("BANK 1", "ChannelA-DIMM1"),
+            dimm("BANK 2", "ChannelB-DIMM0"),
+            dimm("BANK 3", "ChannelB-DIMM1"),
+        ]
+        .concat();
+        assert_eq!(MemoryTopology::parse_dmidecode(&two_per_channel).2, Some(2));
+        let desktop = [dimm("BANK 0", "DIMM_A1"), dimm("BANK 1", "DIMM_A2")].concat();
+        assert_eq!(MemoryTopology::parse_dmidecode(&desktop).2, Some(1));
+        // Slots that don't name a channel leave it unknown (2 by default).
+        let unnamed = [dimm("BANK 0", "DIMM0"), dimm("BANK 1", "DIMM1")].concat();
+        assert_eq!(MemoryTopology::parse_dmidecode(&unnamed).2, None);
+    }
+
+    #[test]
+    fn test_parse_memory_speed() {
+        assert_eq!(
+            parse_memory_speed("DDR5-5600"),
+            Some(("DDR5".to_string(), 5600, None))
+        );
+        assert_eq!(
+            parse_memory_speed("ddr4-3200x8"),
+            Some(("DDR4".to_string(), 3200, Some(8)))
+        );
+        assert_eq!(
+            parse_memory_speed("LPDDR5X-8533"),
+            Some(("LPDDR5X".to_string(), 8533, None))
+        );
+        assert_eq!(parse_memory_speed("5600"), None);
+        assert_eq!(parse_memory_speed("DDR5-fast"), None);
+    }
+
+    #[test]
+    fn test_detect_with_replayed_dump() {
+        let mut dump = HardwareDump {
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            sysinfo: SysInfoSnapshot {
+                total_memory: 64 << 30,
+                available_memory: 48 << 30,
+                cpu_count: 16,
+                cpu_brand: "AMD Ryzen 9 7950X".to_string(),
+                physical_core_count: Some(8),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        dump.commands.insert(
+            "nvidia-smi --query-gpu=memory.total,name --format=csv,noheader,nounits".to_string(),
+            command("24564, NVIDIA GeForce RTX 4090\n"),
+        );
+        dump.commands.insert(
+            "nvidia-smi --query-gpu=memory.total,memory.used,name --format=csv,noheader,nounits"
+                .to_string(),
+            command("24564, 1024, NVIDIA GeForce RTX 4090\n"),
+        );
+        dump.files.insert(
+            "/proc/cpuinfo".to_string(),
+            Some("processor\t: 0\nflags\t\t: fpu avx2 avx512f\n".to_string()),
+        );
+
+        let probe = ReplayProbe::new(dump);
+        let specs = SystemSpecs::detect_with(&probe);
+
+        assert_eq!(specs.total_ram_gb, 64.0);
+        assert_eq!(specs.available_ram_gb, 48.0);
+        assert_eq!(specs.total_cpu_cores, 16);
+        assert_eq!(specs.cpu_name, "AMD Ryzen 9 7950X");
+        assert_eq!(specs.backend, GpuBackend::Cuda);
+        assert_eq!(specs.gpu_name.as_deref(), Some("NVIDIA GeForce RTX 4090"));
+        // Usage is a separate query, never read from the cache.
+        assert_eq!(specs.gpus[0].vram_used_gb, None);
+        let with_usage = specs.clone().with_vram_usage_from(&probe);
+        assert_eq!(with_usage.gpus[0].vram_used_gb, Some(1.0));
+        let features = specs.cpu_features.expect("cpuinfo was recorded");
+        assert!(features.avx512);
+        assert_eq!(features.physical_cores, Some(8));
+        assert!(specs.cgroup_limits.is_none());
+        assert_eq!(
+            specs.gpus[0].provenance,
+            vec![ProbeStep::ok("nvidia-smi", "memory.total 24564 MiB")]
+        );
+        // Nothing else was recorded, so the other probes failed and say why.
+        assert!(specs.detection.gpu_probes.contains(&ProbeStep::failed(
+            "rocm-smi --showmeminfo vram",
+            "not installed or could not be started"
+        )));
+        assert!(specs.detection.available_ram[0].ok);
+    }
+
+    const ROCM_SMI_VRAM_JSON: &str = r#"{"card0": {"VRAM Total Memory (B)": "25753026560", "VRAM Total Used Memory (B)": "1073741824"}, "card1": {"VRAM Total Memory (B)": "25753026560", "VRAM Total Used Memory (B)": "285212672"}, "system": {"Driver version": "6.7.0"}}"#;
+    const ROCM_SMI_NAMES_JSON: &str = r#"{"card0": {"Card Series": "Navi 31 [Radeon RX 7900 XTX]", "Card Model": "0x744c", "Card Vendor": "Advanced Micro Devices, Inc. [AMD/ATI]", "GFX Version": "gfx1100"}, "card1": {"Card Series": "Navi 31 [Radeon RX 7900 XTX]", "Card Model": "0x744c", "Card Vendor": "Advanced Micro Devices, Inc. [AMD/ATI]", "GFX Version": "gfx1100"}}"#;
+
+    #[test]
+    fn test_parse_rocm_smi_json_and_text() {
+        let json = SystemSpecs::parse_rocm_smi_vram_json(ROCM_SMI_VRAM_JSON).unwrap();
+        assert_eq!(json.len(), 2);
+        assert_eq!(json["card1"], (Some(25753026560), Some(285212672)));
+        assert_eq!(SystemSpecs::parse_rocm_smi_vram_json("GPU[0] : VRAM"), None);
+
+        let text = SystemSpecs::parse_rocm_smi_vram_text(
+            "============================ ROCm System Management Interface ============================\n\
+             ================================== Memory Usage (Bytes) ==================================\n\
+             GPU[0]\t\t: VRAM Total Memory (B): 25753026560\n\
+             GPU[0]\t\t: VRAM Total Used Memory (B): 1073741824\n\
+             GPU[1]\t\t: VRAM Total Memory (B): 17163091968\n\
+             GPU[1]\t\t: VRAM Total Used Memory (B): 285212672\n\
+             ================================== End of ROCm SMI Log ===================================\n",
+        );
+        assert_eq!(json["card0"], text["card0"]);
+        assert_eq!(text["card1"], (Some(17163091968), Some(285212672)));
+
+        let names = SystemSpecs::parse_rocm_smi_names_json(ROCM_SMI_NAMES_JSON).unwrap();
+        assert_eq!(names["card0"], "Navi 31 [Radeon RX 7900 XTX]");
+        let names = SystemSpecs::parse_rocm_smi_names_text(
+            "GPU[0]\t\t: Card Series: \t\tNavi 31 [Radeon RX 7900 XTX]\n\
+             GPU[0]\t\t: Card Model: \t\t0x744c\n\
+             GPU[0]\t\t: Card Vendor: \t\tAdvanced Micro Devices, Inc. [AMD/ATI]\n\
+             GPU[1]\t\t: Card Series: \t\tNavi 21 [Radeon RX 6800/6800 XT / 6900 XT]\n",
+        );
+        assert_eq!(names["card0"], "Navi 31 [Radeon RX 7900 XTX]");
+        assert_eq!(names["card1"], "Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]");
+    }
+
+    fn linux_dump() -> HardwareDump {
+        HardwareDump {
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn command(stdout: &str) -> Option<CommandOutput> {
+        Some(CommandOutput {
+            success: true,
+            stdout: stdout.to_string(),
+            timed_out: false,
+        })
+    }
+
+    #[test]
+    fn test_rocm_smi_groups_identical_cards() {
+        let mut dump = linux_dump();
+        dump.commands.insert(
+            "rocm-smi --showmeminfo vram --json".to_string(),
+            command(ROCM_SMI_VRAM_JSON),
+        );
+        dump.commands.insert(
+            "rocm-smi --showproductname --json".to_string(),
+            command(ROCM_SMI_NAMES_JSON),
+        );
+        let mut trail = Vec::new();
+        let gpus = SystemSpecs::detect_amd_gpus_rocm(&ReplayProbe::new(dump), &mut trail);
+
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].name, "Navi 31 [Radeon RX 7900 XTX]");
+        assert_eq!(gpus[0].count, 2);
+        assert!((gpus[0].vram_gb.unwrap() - 23.98).abs() < 0.01);
+        assert_eq!(gpus[0].vram_used_gb, Some(1.0));
+        assert_eq!(gpus[0].backend, GpuBackend::Rocm);
+    }
+
+    #[test]
+    fn test_amd_sysfs_enumerates_every_card() {
+        let mut dump = linux_dump();
+        dump.dirs.insert(
+            "/sys/class/drm".to_string(),
+            Some(
+                [
+                    "card0",
+                    "card0-DP-1",
+                    "card1",
+                    "card2",
+                    "card3",
+                    "renderD128",
+                ]
+                .map(String::from)
+                .to_vec(),
+            ),
+        );
+        let cards = [
+            ("card0", "0x1002", "0000:03:00.0", Some("25753026560")),
+            ("card1", "0x1002", "0000:07:00.0", Some("25753026560")),
+            ("card2", "0x1002", "0000:0e:00.0", Some("536870912")),
+            ("card3", "0x8086", "0000:00:02.0", None),
+        ];
+        for (card, vendor, slot, vram) in cards {
+            let dev = format!("/sys/class/drm/{}/device", card);
+            dump.files
+                .insert(format!("{}/vendor", dev), Some(format!("{}\n", vendor)));
+            dump.files.insert(
+                format!("{}/uevent", dev),
+                Some(format!("DRIVER=amdgpu\nPCI_SLOT_NAME={}\n", slot)),
+            );
+            dump.files.insert(
+                format!("{}/mem_info_vram_total", dev),
+                vram.map(|v| format!("{}\n", v)),
+            );
+        }
+        dump.commands.insert(
+            "lspci".to_string(),
+            command(
+                "00:02.0 VGA compatible controller: Intel Corporation Device a780 (rev 04)\n\
+                 03:00.0 VGA compatible controller: Advanced Micro Devices, Inc. [AMD/ATI] Navi 31 [Radeon RX 7900 XT/7900 XTX] (rev c8)\n\
+                 07:00.0 VGA compatible controller: Advanced Micro Devices, Inc. [AMD/ATI] Navi 31 [Radeon RX 7900 XT/7900 XTX] (rev c8)\n\
+                 0e:00.0 VGA compatible controller: Advanced Micro Devices, Inc. [AMD/ATI] Raphael (rev c5)\n",
+            ),
+        );
+        dump.files.insert(
+            "/sys/class/drm/card2/device/mem_info_gtt_total".to_string(),
+            Some("16777216000\n".to_string()),
+        );
+        let mut trail = Vec::new();
+        let (gpus, apus) = SystemSpecs::detect_amd_gpus_sysfs(
+            &ReplayProbe::new(dump),
+            64.0,
+            GpuBackend::Vulkan,
+            &mut trail,
+        );
+
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].name, "Radeon RX 7900 XT/7900 XTX");
+        assert_eq!(gpus[0].count, 2);
+        assert_eq!(gpus[0].backend, GpuBackend::Vulkan);
+        assert!((gpus[0].vram_gb.unwrap() - 23.98).abs() < 0.01);
+        assert!(trail.last().unwrap().ok);
+
+        // The Raphael iGPU is an APU: its 512 MB carve-out plus GTT.
+        assert_eq!(apus.len(), 1);
+        assert_eq!(apus[0].name, "Raphael");
+        assert!(apus[0].unified_memory);
+        assert!((apus[0].vram_gb.unwrap() - (0.5 + 15.625)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_strix_halo_apu_reports_gtt_pool() {
+        let mut dump = linux_dump();
+        dump.sysinfo.total_memory = 124 << 30;
+        dump.sysinfo.available_memory = 120 << 30;
+        dump.dirs.insert(
+            "/sys/class/drm".to_string(),
+            Some(vec!["card1".to_string()]),
+        );
+        let dev = "/sys/class/drm/card1/device";
+        for (file, text) in [
+            ("vendor", "0x1002\n"),
+            ("uevent", "PCI_SLOT_NAME=0000:c5:00.0\n"),
+            ("mem_info_vram_total", "536870912\n"),
+            ("mem_info_gtt_total", "118111600640\n"),
+        ] {
+            dump.files
+                .insert(format!("{}/{}", dev, file), Some(text.to_string()));
+        }
+        dump.commands.insert(
+            "lspci".to_string(),
+            command(
+                "c5:00.0 Display controller: Advanced Micro Devices, Inc. [AMD/ATI] Strix Halo [Radeon Graphics / Radeon 8050S / 8060S Graphics] (rev c1)\n",
+            ),
+        );
+        let specs = SystemSpecs::detect_with(&ReplayProbe::new(dump));
+
+        assert!(specs.unified_memory);
+        assert_eq!(specs.backend, GpuBackend::Vulkan);
+        // 0.5 GB carve-out + 110 GB GTT, under the 120 GB left after the OS reserve.
+        assert_eq!(specs.gpu_vram_gb, Some(110.5));
+        assert!(
+            specs.gpus[0]
+                .provenance
+                .iter()
+                .any(|p| p.source == "sysfs mem_info_gtt_total" && p.ok)
+        );
+    }
+
+    #[test]
+    fn test_board_detects_jetson_and_sbc_profiles() {
+        let root = fixture_root(
+            "jetson",
+            &[
+                (
+                    "proc/device-tree/model",
+                    "NVIDIA Jetson AGX Orin Developer Kit\0",
+                ),
+                (
+                    "proc/device-tree/compatible",
+                    "nvidia,p3737-0000+p3701-0005\0nvidia,p3701-0005\0nvidia,tegra234\0",
+                ),
+                ("sys/devices/soc0/family", "Tegra\n"),
+            ],
+        );
+        let board = BoardInfo::detect(&LiveProbe::with_root(&root)).expect("board should be read");
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(board.model, "NVIDIA Jetson AGX Orin Developer Kit");
+        assert!(board.tegra);
+        assert_eq!(board.memory_bandwidth_gb_s, Some(204.8));
+
+        let root = fixture_root(
+            "rpi5",
+            &[
+                ("proc/device-tree/model", "Raspberry Pi 5 Model B Rev 1.0\0"),
+                (
+                    "proc/device-tree/compatible",
+                    "raspberrypi,5-model-b\0brcm,bcm2712\0",
+                ),
+            ],
+        );
+        let board = BoardInfo::detect(&LiveProbe::with_root(&root)).expect("board should be read");
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(!board.tegra);
+        assert_eq!(board.memory_bandwidth_gb_s, Some(17.1));
+        assert_eq!(board.cpu_speed_factor, Some(0.5));
+
+        // PCs have no device tree.
+        let root = fixture_root("no-board", &[("proc/meminfo", "MemTotal: 1 kB\n")]);
+        assert_eq!(BoardInfo::detect(&LiveProbe::with_root(&root)), None);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_npu_detected_from_accel_nodes() {
+        let root = fixture_root(
+            "npu",
+            &[
+                (
+                    "sys/class/accel/accel0/device/uevent",
+                    "DRIVER=intel_vpu\nPCI_CLASS=120000\nPCI_ID=8086:643E\nPCI_SLOT_NAME=0000:00:0b.0\n",
+                ),
+                (
+                    "sys/class/accel/accel1/device/uevent",
+                    "DRIVER=amdxdna\nPCI_ID=1022:1502\n",
+                ),
+            ],
+        );
+        let npus = NpuInfo::detect(&LiveProbe::with_root(&root));
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(npus.len(), 2);
+        assert_eq!(npus[0].name, "Intel AI Boost NPU (Lunar Lake)");
+        assert_eq!(npus[0].tops, Some(48.0));
+        assert_eq!(npus[0].max_params_b(), 8.0);
+        assert_eq!(npus[1].driver, "amdxdna");
+        assert_eq!(npus[1].max_params_b(), 4.0);
+
+        // Rockchip vendor kernels expose the NPU as a module, not an accel node.
+        let root = fixture_root("rknpu", &[("sys/module/rknpu/version", "0.9.6\n")]);
+        let npus = NpuInfo::detect(&LiveProbe::with_root(&root));
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(npus.len(), 1);
+        assert_eq!(npus[0].name, "Rockchip RKNPU");
+        assert_eq!(npus[0].driver, "rknpu");
+    }
+
+    #[test]
+    fn test_power_state_from_power_supply() {
+        let root = fixture_root(
+            "power",
+            &[
+                ("sys/class/power_supply/AC/type", "Mains\n"),
+                ("sys/class/power_supply/AC/online", "0\n"),
+                ("sys/class/power_supply/BAT0/type", "Battery\n"),
+                ("sys/class/power_supply/BAT0/status", "Discharging\n"),
+                ("sys/class/power_supply/BAT0/capacity", "54\n"),
+                ("sys/class/power_supply/hidpp_battery_0/type", "Battery\n"),
+                ("sys/class/power_supply/hidpp_battery_0/scope", "Device\n"),
+                ("sys/class/power_supply/hidpp_battery_0/capacity", "90\n"),
+            ],
+        );
+        let power = PowerState::detect(&LiveProbe::with_root(&root)).expect("laptop battery");
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(power.on_battery);
+        assert_eq!(power.battery_percent, Some(54));
+        assert_eq!(power.label(), "battery 54%");
+
+        // Desktops: only a peripheral battery, and no power limit.
+        let root = fixture_root(
+            "no-power",
+            &[
+                ("sys/class/power_supply/hidpp_battery_0/type", "Battery\n"),
+                ("sys/class/power_supply/hidpp_battery_0/scope", "Device\n"),
+            ],
+        );
+        assert_eq!(PowerState::detect(&LiveProbe::with_root(&root)), None);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            parse_nvidia_power_limits("35.00, 140.00\n"),
+            Some((35.0, 140.0))
+        );
+        assert_eq!(parse_nvidia_power_limits("[N/A], [N/A]\n"), None);
+        assert_eq!(
+            parse_pmset_battery(
+                "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=4653155)\t87%; discharging; 5:12 remaining present: true\n"
+            ),
+            Some((true, Some(87)))
+        );
+    }
+
+    #[test]
+    fn test_pcie_links_from_nvidia_smi_and_sysfs() {
+        assert_eq!(
+            PcieLink::from_sysfs("16.0 GT/s PCIe\n", "16\n"),
+            Some(PcieLink {
+                generation: 4,
+                width: 16
+            })
+        );
+        assert_eq!(
+            PcieLink::from_sysfs("8 GT/s", "4").unwrap().label(),
+            "PCIe 3.0 x4"
+        );
+        assert_eq!(PcieLink::from_sysfs("Unknown", "0"), None);
+
+        let mut dump = linux_dump();
+        dump.commands.insert(
+            "nvidia-smi --query-gpu=pcie.link.gen.max,pcie.link.width.max,name --format=csv,noheader,nounits"
+                .to_string(),
+            command("4, 16, NVIDIA GeForce RTX 4090\n3, 4, NVIDIA GeForce RTX 4090\n"),
+        );
+        dump.dirs.insert(
+            "/sys/bus/pci/devices".to_string(),
+            Some(vec!["0000:00:14.0".to_string(), "0000:03:00.0".to_string()]),
+        );
+        for (file, text) in [
+            ("0000:00:14.0/class", "0x0c0330\n"),
+            ("0000:03:00.0/class", "0x030000\n"),
+            ("0000:03:00.0/vendor", "0x1002\n"),
+            // Idle at Gen1; a Gen4 x16 card in a Gen4 x8 slot.
+            ("0000:03:00.0/current_link_speed", "2.5 GT/s PCIe\n"),
+            ("0000:03:00.0/current_link_width", "16\n"),
+            ("0000:03:00.0/max_link_speed", "16.0 GT/s PCIe\n"),
+            ("0000:03:00.0/max_link_width", "16\n"),
+            ("0000:03:00.0/../max_link_speed", "16.0 GT/s PCIe\n"),
+            ("0000:03:00.0/../max_link_width", "8\n"),
+        ] {
+            dump.files.insert(
+                format!("/sys/bus/pci/devices/{}", file),
+                Some(text.to_string()),
+            );
+        }
+        let gpu = |name: &str, backend| GpuInfo {
+            name: name.to_string(),
+            vram_gb: Some(24.0),
+            backend,
+            count: 1,
+            unified_memory: false,
+            vram_used_gb: None,
+            provenance: Vec::new(),
+            pcie_link: None,
+            memory_bandwidth_gb_s: None,
+        };
+        let mut gpus = vec![
+            gpu("NVIDIA GeForce RTX 4090", GpuBackend::Cuda),
+            gpu("Radeon RX 7900 XTX", GpuBackend::Rocm),
+        ];
+        SystemSpecs::attach_pcie_links(&ReplayProbe::new(dump), &mut gpus);
+
+        // Grouped cards report their slowest link.
+        assert_eq!(gpus[0].pcie_link.unwrap().label(), "PCIe 3.0 x4");
+        assert_eq!(gpus[1].pcie_link.unwrap().label(), "PCIe 4.0 x8");
+        assert_eq!(gpus[1].provenance[0].source, "sysfs max_link_speed");
+    }
+
+    #[test]
+    fn test_sysfs_pcie_links_match_each_card() {
+        let mut dump = linux_dump();
+        dump.dirs.insert(
+            "/sys/bus/pci/devices".to_string(),
+            Some(vec!["0000:01:00.0".to_string(), "0000:05:00.0".to_string()]),
+        );
+        for (device, speed, width) in [
+            ("0000:01:00.0", "16.0 GT/s PCIe", "16"),
+            ("0000:05:00.0", "8.0 GT/s PCIe", "4"),
+        ] {
+            for (file, text) in [
+                ("class", "0x030000"),
+                ("vendor", "0x10de"),
+                ("max_link_speed", speed),
+                ("max_link_width", width),
+            ] {
+                dump.files.insert(
+                    format!("/sys/bus/pci/devices/{}/{}", device, file),
+                    Some(format!("{}\n", text)),
+                );
+            }
+        }
+        let gpu = |name: &str| GpuInfo {
+            name: name.to_string(),
+            vram_gb: Some(12.0),
+            backend: GpuBackend::Cuda,
+            count: 1,
+            unified_memory: false,
+            vram_used_gb: None,
+            provenance: Vec::new(),
+            pcie_link: None,
+            memory_bandwidth_gb_s: None,
+        };
+        let mut gpus = vec![
+            gpu("NVIDIA GeForce RTX 4090"),
+            gpu("NVIDIA GeForce RTX 3060"),
+        ];
+
+        // Without lspci the two NVIDIA devices can't be told apart.
+        SystemSpecs::attach_pcie_links(&ReplayProbe::new(dump.clone()), &mut gpus);
+        assert!(gpus.iter().all(|g| g.pcie_link.is_none()));
+
+        dump.commands.insert(
+            "lspci".to_string(),
+            command(
+                "01:00.0 VGA compatible controller: NVIDIA Corporation AD102 [GeForce RTX 4090] (rev a1)\n\
+                 05:00.0 VGA compatible controller: NVIDIA Corporation GA106 [GeForce RTX 3060] (rev a1)\n",
+            ),
+        );
+        SystemSpecs::attach_pcie_links(&ReplayProbe::new(dump), &mut gpus);
+        assert_eq!(gpus[0].pcie_link.unwrap().label(), "PCIe 4.0 x16");
+        assert_eq!(gpus[1].pcie_link.unwrap().label(), "PCIe 3.0 x4");
+    }
+
+    #[test]
+    fn test_memory_bandwidth_from_catalog_name() {
+        let gpu = |name: &str| GpuInfo {
+            name: name.to_string(),
+            vram_gb: Some(12.0),
+            backend: GpuBackend::Cuda,
+            count: 1,
+            unified_memory: false,
+            vram_used_gb: None,
+            provenance: Vec::new(),
+            pcie_link: None,
+            memory_bandwidth_gb_s: None,
+        };
+        let mut gpus = vec![
+            gpu("NVIDIA GeForce RTX 3080 Ti"),
+            gpu("NVIDIA GeForce RTX 3080"),
+            gpu("NVIDIA Quadro P400"),
+        ];
+        SystemSpecs::attach_memory_bandwidth(&mut gpus);
+        assert_eq!(gpus[0].memory_bandwidth_gb_s, Some(912.0));
+        assert_eq!(gpus[1].memory_bandwidth_gb_s, Some(760.0));
+        assert_eq!(gpus[2].memory_bandwidth_gb_s, None);
+    }
+
+    #[test]
+    fn test_jetson_reports_unified_cuda_gpu() {
+        let mut dump = linux_dump();
+        dump.arch = "aarch64".to_string();
+        dump.sysinfo.total_memory = 61 << 30;
+        dump.sysinfo.available_memory = 56 << 30;
+        dump.files.insert(
+            "/proc/device-tree/model".to_string(),
+            Some("NVIDIA Jetson AGX Orin Developer Kit\0".to_string()),
+        );
+        dump.files.insert(
+            "/sys/devices/soc0/family".to_string(),
+            Some("Tegra\n".to_string()),
+        );
+        let specs = SystemSpecs::detect_with(&ReplayProbe::new(dump));
+
+        assert!(specs.has_gpu);
+        assert!(specs.unified_memory);
+        assert_eq!(specs.backend, GpuBackend::Cuda);
+        assert_eq!(specs.gpus.len(), 1);
+        assert!(specs.gpus[0].name.contains("Jetson"));
+        assert_eq!(specs.gpu_vram_gb, Some(56.0));
+        assert_eq!(specs.memory_bandwidth_gb_s(), Some(204.8));
+    }
+
+    #[test]
+    fn test_provenance_records_name_estimates() {
+        let gpus = SystemSpecs::parse_nvidia_smi_list("[N/A], [N/A], NVIDIA GeForce RTX 4090\n");
+        let steps = &gpus[0].provenance;
+        assert!(!steps[0].ok);
+        assert_eq!(steps[1].source, "estimate_vram_from_name");
+        assert!(steps[1].ok);
+
+        // WMI's 32-bit AdapterRAM caps a 24 GB card at 4 GB.
+        let (vram, steps) =
+            SystemSpecs::resolve_wmi_vram(4 << 30, "NVIDIA GeForce RTX 4090", "wmic");
+        assert_eq!(
+            vram,
+            Some(estimate_vram_from_name("NVIDIA GeForce RTX 4090"))
+        );
+        assert_eq!(steps[0].source, "wmic");
+        assert!(steps[0].detail.contains("32-bit"));
+        assert!(steps[1].ok);
+
+        let (vram, steps) = SystemSpecs::resolve_wmi_vram(8 << 30, "Radeon RX 6600", "wmic");
+        assert_eq!(vram, Some(8.0));
+        assert_eq!(steps, vec![ProbeStep::ok("wmic", "AdapterRAM 8.00 GB")]);
+    }
 }
@@ -1,6 +1,9 @@
+pub mod advisor;
 pub mod fit;
+pub mod fleet;
 pub mod hardware;
 pub mod models;
+pub mod probe;
 pub mod providers;
 
 pub use fit::{FitLevel, InferenceRuntime, ModelFit, RunMode, ScoreComponents, SortColumn};
@@ -7,6 +7,13 @@ pub const QUANT_HIERARCHY: &[&str] = &["Q8_0", "Q6_K", "Q5_K_M", "Q4_K_M", "Q3_K
 /// MLX-native quantization hierarchy (best quality to most compressed).
 pub const MLX_QUANT_HIERARCHY: &[&str] = &["mlx-8bit", "mlx-4bit"];
 
+/// NPU runtime weight formats (OpenVINO/ONNX INT8 and group-wise INT4).
+pub const NPU_QUANT_HIERARCHY: &[&str] = &["npu-int8", "npu-int4"];
+
+/// vLLM weight formats: unquantized, FP8, then 4-bit AWQ. GPTQ checkpoints
+/// cost the same as AWQ, so they're priced but never picked over it.
+pub const VLLM_QUANT_HIERARCHY: &[&str] = &["BF16", "FP8", "AWQ-4bit"];
+
 /// Bytes per parameter for each quantization level.
 pub fn quant_bpp(quant: &str) -> f64 {
     match quant {
@@ -20,6 +27,10 @@ pub fn quant_bpp(quant: &str) -> f64 {
         "Q2_K" => 0.37,
         "mlx-4bit" => 0.55,
         "mlx-8bit" => 1.0,
+        "npu-int4" => 0.6,
+        "npu-int8" => 1.05,
+        "FP8" => 1.0,
+        "AWQ-4bit" | "GPTQ-4bit" => 0.56,
         _ => 0.58,
     }
 }
@@ -36,6 +47,10 @@ pub fn quant_speed_multiplier(quant: &str) -> f64 {
         "Q2_K" => 1.35,
         "mlx-4bit" => 1.15,
         "mlx-8bit" => 0.85,
+        "npu-int4" => 1.15,
+        "npu-int8" => 0.8,
+        "FP8" => 0.9,
+        "AWQ-4bit" | "GPTQ-4bit" => 1.1,
         _ => 1.0,
     }
 }
@@ -52,6 +67,10 @@ pub fn quant_quality_penalty(quant: &str) -> f64 {
         "Q2_K" => -12.0,
         "mlx-4bit" => -4.0,
         "mlx-8bit" => 0.0,
+        "npu-int4" => -6.0,
+        "npu-int8" => -1.0,
+        "FP8" => 0.0,
+        "AWQ-4bit" | "GPTQ-4bit" => -4.0,
         _ => -5.0,
     }
 }
@@ -149,19 +168,42 @@ impl LlmModel {
         }
     }
 
+    /// The same model at another quantization, with its memory figures
+    /// scaled by the change in bytes per parameter.
+    pub fn with_quant(&self, quant: &str) -> LlmModel {
+        let ratio = quant_bpp(quant) / self.quant_bpp();
+        LlmModel {
+            quantization: quant.to_string(),
+            min_ram_gb: self.min_ram_gb * ratio,
+            recommended_ram_gb: self.recommended_ram_gb * ratio,
+            min_vram_gb: self.min_vram_gb.map(|gb| gb * ratio),
+            ..self.clone()
+        }
+    }
+
+    /// Approximate on-disk size (GB) of the weights at a given quantization.
+    pub fn download_size_gb(&self, quant: &str) -> f64 {
+        self.params_b() * quant_bpp(quant)
+    }
+
     /// Estimate memory required (GB) at a given quantization and context length.
     /// Formula: model_weights + KV_cache + runtime_overhead
     pub fn estimate_memory_gb(&self, quant: &str, ctx: u32) -> f64 {
         let bpp = quant_bpp(quant);
         let params = self.params_b();
         let model_mem = params * bpp;
-        // KV cache: ~0.000008 GB per billion params per context token
-        let kv_cache = 0.000008 * params * ctx as f64;
+        let kv_cache = self.kv_cache_gb(ctx);
         // Runtime overhead (CUDA/Metal context, buffers)
         let overhead = 0.5;
         model_mem + kv_cache + overhead
     }
 
+    /// KV cache (GB) for one sequence of `ctx` tokens:
+    /// ~0.000008 GB per billion params per context token.
+    pub fn kv_cache_gb(&self, ctx: u32) -> f64 {
+        0.000008 * self.params_b() * ctx as f64
+    }
+
     /// Select the best quantization level that fits within a memory budget.
     /// Returns the quant name and estimated memory in GB, or None if nothing fits.
     pub fn best_quant_for_budget(&self, budget_gb: f64, ctx: u32) -> Option<(&'static str, f64)> {
@@ -0,0 +1,651 @@
+//! Hardware probe layer.
+//!
+//! Detection in `hardware` reads the machine only through [`Probe`]: command
+//! output, file contents, directory listings and the `sysinfo` figures. A
+//! [`RecordingProbe`] captures every answer into a [`HardwareDump`] that can
+//! be saved as one JSON file (`llmfit system --dump`), and a [`ReplayProbe`]
+//! answers from such a file so a misdetection can be reproduced anywhere.
+//!
+//! Commands are the slow part of detection: [`LiveProbe`] runs them with a
+//! timeout, can run a batch of them concurrently, and its results can be kept
+//! in a [`ProbeCache`] so the next start doesn't run them again.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::hardware::CpuFeatures;
+
+/// Dump format version, bumped when the layout changes incompatibly.
+pub const DUMP_VERSION: u32 = 1;
+
+/// How long a single command may run before it is killed. Vendor tools can
+/// hang on a wedged driver; detection should degrade, not stall.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long cached command results are reused before probing again.
+pub const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Output of a command that could be spawned.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CommandOutput {
+    pub success: bool,
+    pub stdout: String,
+    /// Killed after running past the probe timeout.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+}
+
+/// The `sysinfo` figures detection uses.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SysInfoSnapshot {
+    pub total_memory: u64,
+    pub available_memory: u64,
+    pub used_memory: u64,
+    pub cpu_count: usize,
+    pub cpu_brand: String,
+    pub physical_core_count: Option<usize>,
+    /// Instruction sets probed at runtime, for platforms without `/proc/cpuinfo`.
+    pub cpu_features: Option<CpuFeatures>,
+}
+
+/// Read-only view of the machine being detected.
+pub trait Probe: Sync {
+    /// Operating system of the probed machine ("linux", "macos", "windows").
+    fn os(&self) -> &str;
+
+    /// CPU architecture of the probed machine ("x86_64", "aarch64", ...).
+    fn arch(&self) -> &str;
+
+    fn sysinfo(&self) -> SysInfoSnapshot;
+
+    /// Run a command. `None` if it could not be spawned.
+    fn run(&self, program: &str, args: &[&str]) -> Option<CommandOutput>;
+
+    fn read_file(&self, path: &Path) -> Option<String>;
+
+    /// Sorted entry names of a directory; `None` if it isn't one.
+    fn read_dir(&self, path: &Path) -> Option<Vec<String>>;
+
+    /// Hint that these commands will be run, so a probe can start them all
+    /// at once. Later `run` calls return the prefetched results.
+    fn prefetch(&self, _commands: &[(&str, &[&str])]) {}
+
+    /// Stdout of a command that ran successfully.
+    fn run_stdout(&self, program: &str, args: &[&str]) -> Option<String> {
+        self.run(program, args)
+            .filter(|o| o.success)
+            .map(|o| o.stdout)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.read_dir(path).is_some()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Live probe
+// ---------------------------------------------------------------------------
+
+/// Probes the running machine. Each command runs at most once per probe;
+/// repeated calls return the first result.
+pub struct LiveProbe {
+    root: PathBuf,
+    os: String,
+    timeout: Duration,
+    commands: Mutex<BTreeMap<String, Option<CommandOutput>>>,
+}
+
+impl Default for LiveProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveProbe {
+    pub fn new() -> Self {
+        LiveProbe {
+            root: PathBuf::from("/"),
+            os: std::env::consts::OS.to_string(),
+            timeout: PROBE_TIMEOUT,
+            commands: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Probe the running machine, answering commands from earlier results
+    /// (see [`ProbeCache`]) instead of running them.
+    pub fn with_cached_commands(commands: BTreeMap<String, Option<CommandOutput>>) -> Self {
+        LiveProbe {
+            commands: Mutex::new(commands),
+            ..Self::new()
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Read files from a directory laid out like a Linux root filesystem
+    /// (test fixtures). Commands are not run.
+    pub fn with_root(root: &Path) -> Self {
+        LiveProbe {
+            root: root.to_path_buf(),
+            os: "linux".to_string(),
+            ..Self::new()
+        }
+    }
+
+    /// Every command result this probe has produced or was seeded with.
+    pub fn command_results(&self) -> BTreeMap<String, Option<CommandOutput>> {
+        self.commands
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn cached(&self, key: &str) -> Option<Option<CommandOutput>> {
+        let commands = self.commands.lock().unwrap_or_else(|e| e.into_inner());
+        commands.get(key).cloned()
+    }
+
+    fn store(&self, key: String, output: Option<CommandOutput>) {
+        let mut commands = self.commands.lock().unwrap_or_else(|e| e.into_inner());
+        commands.insert(key, output);
+    }
+
+    fn is_real_root(&self) -> bool {
+        self.root == Path::new("/")
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        if self.is_real_root() {
+            path.to_path_buf()
+        } else {
+            self.root.join(path.strip_prefix("/").unwrap_or(path))
+        }
+    }
+}
+
+impl Probe for LiveProbe {
+    fn os(&self) -> &str {
+        &self.os
+    }
+
+    fn arch(&self) -> &str {
+        std::env::consts::ARCH
+    }
+
+    fn sysinfo(&self) -> SysInfoSnapshot {
+        // Memory and CPU only; enumerating processes is slow and unused.
+        let sys = sysinfo::System::new_with_specifics(
+            sysinfo::RefreshKind::new()
+                .with_memory(sysinfo::MemoryRefreshKind::everything())
+                .with_cpu(sysinfo::CpuRefreshKind::new()),
+        );
+        SysInfoSnapshot {
+            total_memory: sys.total_memory(),
+            available_memory: sys.available_memory(),
+            used_memory: sys.used_memory(),
+            cpu_count: sys.cpus().len(),
+            cpu_brand: sys
+                .cpus()
+                .first()
+                .map(|cpu| cpu.brand().to_string())
+                .unwrap_or_default(),
+            physical_core_count: sys.physical_core_count(),
+            cpu_features: Some(CpuFeatures::from_runtime_detection()),
+        }
+    }
+
+    fn run(&self, program: &str, args: &[&str]) -> Option<CommandOutput> {
+        let key = command_key(program, args);
+        if let Some(output) = self.cached(&key) {
+            return output;
+        }
+        if !self.is_real_root() {
+            return None;
+        }
+        let output = run_with_timeout(program, args, self.timeout);
+        self.store(key, output.clone());
+        output
+    }
+
+    fn prefetch(&self, commands: &[(&str, &[&str])]) {
+        std::thread::scope(|s| {
+            for &(program, args) in commands {
+                if self.cached(&command_key(program, args)).is_none() {
+                    s.spawn(move || self.run(program, args));
+                }
+            }
+        });
+    }
+
+    fn read_file(&self, path: &Path) -> Option<String> {
+        std::fs::read_to_string(self.resolve(path)).ok()
+    }
+
+    fn read_dir(&self, path: &Path) -> Option<Vec<String>> {
+        let mut names: Vec<String> = std::fs::read_dir(self.resolve(path))
+            .ok()?
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        Some(names)
+    }
+}
+
+/// Run a command, killing it if it hasn't exited within `timeout`.
+fn run_with_timeout(program: &str, args: &[&str], timeout: Duration) -> Option<CommandOutput> {
+    let mut child = std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // Drain stdout on a thread so a chatty command can't block on a full pipe.
+    let mut pipe = child.stdout.take()?;
+    let reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+        }
+    };
+
+    let Some(status) = status else {
+        // The reader may still be blocked if the command left children
+        // holding the pipe; leave it behind rather than wait on it.
+        return Some(CommandOutput {
+            success: false,
+            stdout: String::new(),
+            timed_out: true,
+        });
+    };
+    let stdout = reader.join().unwrap_or_default();
+    Some(CommandOutput {
+        success: status.success(),
+        stdout: String::from_utf8_lossy(&stdout).into_owned(),
+        timed_out: false,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Result cache
+// ---------------------------------------------------------------------------
+
+/// Command results from an earlier detection, saved in the config directory.
+/// Only commands are cached: files and `sysinfo` are cheap to read and carry
+/// the figures that change between runs (available RAM, cgroup usage).
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProbeCache {
+    pub version: u32,
+    pub llmfit_version: String,
+    pub os: String,
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+    pub commands: BTreeMap<String, Option<CommandOutput>>,
+}
+
+impl ProbeCache {
+    /// Keeps only commands that ran and succeeded in time: a failure or a
+    /// slow cold start must not hide a tool for a whole [`CACHE_TTL`].
+    pub fn new(mut commands: BTreeMap<String, Option<CommandOutput>>) -> Self {
+        commands.retain(|_, output| output.as_ref().is_some_and(|o| o.success && !o.timed_out));
+        ProbeCache {
+            version: DUMP_VERSION,
+            llmfit_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            saved_at: unix_now(),
+            commands,
+        }
+    }
+
+    /// `<config dir>/llmfit/hardware-cache.json`.
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("llmfit").join("hardware-cache.json"))
+    }
+
+    /// Load a cache written by this llmfit version on this OS no more than
+    /// `ttl` ago. Anything else (missing, stale, unreadable) is `None`.
+    pub fn load_fresh(path: &Path, ttl: Duration) -> Option<Self> {
+        let text = std::fs::read_to_string(path).ok()?;
+        let cache: ProbeCache = serde_json::from_str(&text).ok()?;
+        let age = unix_now().checked_sub(cache.saved_at)?;
+        let current = cache.version == DUMP_VERSION
+            && cache.llmfit_version == env!("CARGO_PKG_VERSION")
+            && cache.os == std::env::consts::OS;
+        (current && age < ttl.as_secs()).then_some(cache)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+        }
+        let text = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Per-user config directory: `$XDG_CONFIG_HOME`, `%APPDATA%`, or
+/// `~/Library/Application Support` / `~/.config`.
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(target_os = "windows") {
+        return std::env::var_os("APPDATA").map(PathBuf::from);
+    }
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+    if cfg!(target_os = "macos") {
+        Some(home.join("Library").join("Application Support"))
+    } else {
+        Some(home.join(".config"))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Dump, recording and replay
+// ---------------------------------------------------------------------------
+
+/// Every answer a probe gave during one detection run.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HardwareDump {
+    pub version: u32,
+    pub llmfit_version: String,
+    pub os: String,
+    pub arch: String,
+    pub sysinfo: SysInfoSnapshot,
+    /// Keyed by the command line, program and arguments joined by spaces.
+    pub commands: BTreeMap<String, Option<CommandOutput>>,
+    pub files: BTreeMap<String, Option<String>>,
+    pub dirs: BTreeMap<String, Option<Vec<String>>>,
+}
+
+impl HardwareDump {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let dump: HardwareDump = serde_json::from_str(&text)
+            .map_err(|e| format!("invalid hardware dump {}: {}", path.display(), e))?;
+        if dump.version > DUMP_VERSION {
+            return Err(format!(
+                "hardware dump {} is version {}, this llmfit reads up to {}",
+                path.display(),
+                dump.version,
+                DUMP_VERSION
+            ));
+        }
+        Ok(dump)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+    }
+}
+
+pub(crate) fn command_key(program: &str, args: &[&str]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().copied())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn path_key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Wraps a live probe and records every answer it gives.
+pub struct RecordingProbe {
+    inner: LiveProbe,
+    dump: Mutex<HardwareDump>,
+}
+
+impl Default for RecordingProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecordingProbe {
+    pub fn new() -> Self {
+        Self::wrapping(LiveProbe::new())
+    }
+
+    pub fn wrapping(inner: LiveProbe) -> Self {
+        let dump = HardwareDump {
+            version: DUMP_VERSION,
+            llmfit_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: inner.os().to_string(),
+            arch: inner.arch().to_string(),
+            ..Default::default()
+        };
+        RecordingProbe {
+            inner,
+            dump: Mutex::new(dump),
+        }
+    }
+
+    pub fn into_dump(self) -> HardwareDump {
+        self.dump.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record(&self, f: impl FnOnce(&mut HardwareDump)) {
+        let mut dump = self.dump.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut dump);
+    }
+}
+
+impl Probe for RecordingProbe {
+    fn os(&self) -> &str {
+        self.inner.os()
+    }
+
+    fn arch(&self) -> &str {
+        self.inner.arch()
+    }
+
+    fn sysinfo(&self) -> SysInfoSnapshot {
+        let snapshot = self.inner.sysinfo();
+        self.record(|d| d.sysinfo = snapshot.clone());
+        snapshot
+    }
+
+    fn run(&self, program: &str, args: &[&str]) -> Option<CommandOutput> {
+        let output = self.inner.run(program, args);
+        self.record(|d| {
+            d.commands
+                .insert(command_key(program, args), output.clone());
+        });
+        output
+    }
+
+    fn prefetch(&self, commands: &[(&str, &[&str])]) {
+        // Recorded when detection asks for them through `run`.
+        self.inner.prefetch(commands);
+    }
+
+    fn read_file(&self, path: &Path) -> Option<String> {
+        let text = self.inner.read_file(path);
+        self.record(|d| {
+            d.files.insert(path_key(path), text.clone());
+        });
+        text
+    }
+
+    fn read_dir(&self, path: &Path) -> Option<Vec<String>> {
+        let names = self.inner.read_dir(path);
+        self.record(|d| {
+            d.dirs.insert(path_key(path), names.clone());
+        });
+        names
+    }
+}
+
+/// Answers from a saved dump. Anything the dump didn't record is treated as
+/// missing (command not found, file absent).
+pub struct ReplayProbe {
+    dump: HardwareDump,
+}
+
+impl ReplayProbe {
+    pub fn new(dump: HardwareDump) -> Self {
+        ReplayProbe { dump }
+    }
+}
+
+impl Probe for ReplayProbe {
+    fn os(&self) -> &str {
+        &self.dump.os
+    }
+
+    fn arch(&self) -> &str {
+        &self.dump.arch
+    }
+
+    fn sysinfo(&self) -> SysInfoSnapshot {
+        self.dump.sysinfo.clone()
+    }
+
+    fn run(&self, program: &str, args: &[&str]) -> Option<CommandOutput> {
+        self.dump
+            .commands
+            .get(&command_key(program, args))
+            .cloned()
+            .flatten()
+    }
+
+    fn read_file(&self, path: &Path) -> Option<String> {
+        self.dump.files.get(&path_key(path)).cloned().flatten()
+    }
+
+    fn read_dir(&self, path: &Path) -> Option<Vec<String>> {
+        self.dump.dirs.get(&path_key(path)).cloned().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorded_dump_replays_identically() {
+        let root = std::env::temp_dir().join(format!("llmfit-probe-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("sys/block/nvme0n1")).unwrap();
+        std::fs::write(root.join("sys/block/nvme0n1/size"), "100\n").unwrap();
+
+        let recorder = RecordingProbe::wrapping(LiveProbe::with_root(&root));
+        let size = recorder.read_file(Path::new("/sys/block/nvme0n1/size"));
+        let listing = recorder.read_dir(Path::new("/sys/block"));
+        let missing = recorder.read_file(Path::new("/proc/nope"));
+        let command = recorder.run("nvidia-smi", &["-L"]);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let json = serde_json::to_string(&recorder.into_dump()).unwrap();
+        let replay = ReplayProbe::new(serde_json::from_str(&json).unwrap());
+
+        assert_eq!(replay.os(), "linux");
+        assert_eq!(size.as_deref(), Some("100\n"));
+        assert_eq!(replay.read_file(Path::new("/sys/block/nvme0n1/size")), size);
+        assert_eq!(replay.read_dir(Path::new("/sys/block")), listing);
+        assert_eq!(replay.read_file(Path::new("/proc/nope")), missing);
+        assert_eq!(replay.run("nvidia-smi", &["-L"]), command);
+        // Never recorded: treated as absent.
+        assert_eq!(replay.run_stdout("rocm-smi", &[]), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_live_probe_times_out_and_memoizes() {
+        let probe = LiveProbe::new().with_timeout(Duration::from_millis(100));
+        let started = Instant::now();
+        probe.prefetch(&[("sleep", &["10"]), ("echo", &["hi"])]);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let slept = probe.run("sleep", &["10"]).unwrap();
+        assert!(slept.timed_out && !slept.success);
+        assert_eq!(probe.run_stdout("echo", &["hi"]).as_deref(), Some("hi\n"));
+        assert_eq!(probe.command_results().len(), 2);
+    }
+
+    #[test]
+    fn test_cached_commands_are_not_rerun() {
+        let cached = CommandOutput {
+            success: true,
+            stdout: "GPU 0: cached\n".to_string(),
+            timed_out: false,
+        };
+        let mut commands = BTreeMap::new();
+        commands.insert("nvidia-smi -L".to_string(), Some(cached.clone()));
+        commands.insert("rocm-smi".to_string(), None);
+        let probe = LiveProbe::with_cached_commands(commands);
+
+        assert_eq!(probe.run("nvidia-smi", &["-L"]), Some(cached));
+        assert_eq!(probe.run("rocm-smi", &[]), None);
+    }
+
+    #[test]
+    fn test_probe_cache_keeps_only_successes() {
+        let output = |success, timed_out| CommandOutput {
+            success,
+            stdout: String::new(),
+            timed_out,
+        };
+        let mut commands = BTreeMap::new();
+        commands.insert("lspci".to_string(), Some(output(true, false)));
+        commands.insert("nvidia-smi -L".to_string(), Some(output(false, true)));
+        commands.insert("rocm-smi".to_string(), Some(output(false, false)));
+        commands.insert("missing".to_string(), None);
+        let cache = ProbeCache::new(commands);
+        assert_eq!(cache.commands.keys().collect::<Vec<_>>(), vec!["lspci"]);
+    }
+
+    #[test]
+    fn test_probe_cache_expires() {
+        let path = std::env::temp_dir().join(format!("llmfit-cache-{}.json", std::process::id()));
+        let mut cache = ProbeCache::new(BTreeMap::new());
+        cache.save(&path).unwrap();
+        assert_eq!(
+            ProbeCache::load_fresh(&path, CACHE_TTL),
+            Some(cache.clone())
+        );
+
+        cache.saved_at -= 2 * CACHE_TTL.as_secs();
+        cache.save(&path).unwrap();
+        assert_eq!(ProbeCache::load_fresh(&path, CACHE_TTL), None);
+
+        cache.saved_at = unix_now();
+        cache.llmfit_version = "0.0.0-other".to_string();
+        cache.save(&path).unwrap();
+        assert_eq!(ProbeCache::load_fresh(&path, CACHE_TTL), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
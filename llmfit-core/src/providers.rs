@@ -1,8 +1,13 @@
-//! Runtime model providers (Ollama, etc.).
+//! Runtime model providers (Ollama, MLX, llama.cpp's llama-server, LM Studio,
+//! and any configured OpenAI-compatible server).
 //!
-//! Each provider can list locally installed models and pull new ones.
-//! The trait is designed to be extended for llama.cpp, vLLM, etc.
+//! Each provider can list locally installed models, pull new ones and
+//! remove them.
 
+use crate::fit::{InferenceRuntime, ModelFit};
+use crate::hardware::{SystemSpecs, free_disk_gb};
+use crate::models::LlmModel;
+use crate::probe::config_dir;
 use std::collections::HashSet;
 
 // ---------------------------------------------------------------------------
@@ -24,6 +29,18 @@ pub trait ModelProvider {
     /// Start pulling a model. Returns immediately; progress is polled
     /// via `pull_progress()`.
     fn start_pull(&self, model_tag: &str) -> Result<PullHandle, String>;
+
+    /// Names under which `hf_name` can be removed, shortest first, given
+    /// `installed` from `installed_models()`. None by default.
+    fn installed_tags(&self, _hf_name: &str, _installed: &HashSet<String>) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Delete an installed model, named as `installed_tags` returns it.
+    /// Returns the bytes it took on disk.
+    fn remove(&self, _model_tag: &str) -> Result<u64, String> {
+        Err(format!("{} can't remove models", self.name()))
+    }
 }
 
 /// Handle returned by `start_pull`. The TUI polls this in a background
@@ -65,10 +82,281 @@ impl OllamaProvider {
         Self::default()
     }
 
+    /// Provider for an Ollama server at `base_url`.
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+        }
+    }
+
     /// Build the full API URL for a given endpoint path.
     fn api_url(&self, path: &str) -> String {
         format!("{}/api/{}", self.base_url.trim_end_matches('/'), path)
     }
+
+    /// Installed models with the metadata `/api/tags` lists, filled in
+    /// from `/api/show` (parameter count, context length, experts). Models
+    /// whose `/api/show` fails keep just the tag details.
+    pub fn installed_details(&self) -> Vec<OllamaModelInfo> {
+        let Some(tags) = ureq::get(&self.api_url("tags"))
+            .timeout(std::time::Duration::from_secs(5))
+            .call()
+            .ok()
+            .and_then(|resp| resp.into_json::<TagsResponse>().ok())
+        else {
+            return Vec::new();
+        };
+        tags.models
+            .into_iter()
+            .map(|m| {
+                let mut info = OllamaModelInfo {
+                    name: m.name,
+                    size_bytes: m.size,
+                    parameter_size: m.details.parameter_size,
+                    quantization: m.details.quantization_level,
+                    family: m.details.family,
+                    ..Default::default()
+                };
+                if let Some(show) = ureq::post(&self.api_url("show"))
+                    .timeout(std::time::Duration::from_secs(5))
+                    .send_json(serde_json::json!({ "model": info.name }))
+                    .ok()
+                    .and_then(|resp| resp.into_json::<serde_json::Value>().ok())
+                {
+                    info.apply_show(&show);
+                }
+                info
+            })
+            .collect()
+    }
+}
+
+impl OllamaProvider {
+    /// Models Ollama has in memory right now, from `/api/ps`. `None` when
+    /// Ollama is unreachable.
+    pub fn loaded_models(&self) -> Option<Vec<OllamaLoadedModel>> {
+        let ps: PsResponse = ureq::get(&self.api_url("ps"))
+            .timeout(std::time::Duration::from_secs(2))
+            .call()
+            .ok()?
+            .into_json()
+            .ok()?;
+        Some(
+            ps.models
+                .into_iter()
+                .map(|m| OllamaLoadedModel {
+                    name: m.name,
+                    size_bytes: m.size,
+                    size_vram_bytes: m.size_vram,
+                    context_length: m.context_length,
+                    expires_at: m.expires_at,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// A model loaded in Ollama and where its memory actually lives.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct OllamaLoadedModel {
+    pub name: String,
+    /// Whole footprint: weights, KV cache and compute buffers.
+    pub size_bytes: u64,
+    /// The part of `size_bytes` resident in VRAM; the rest is in system RAM.
+    pub size_vram_bytes: u64,
+    /// Context the runner was started with (Ollama 0.6+).
+    pub context_length: Option<u32>,
+    /// When Ollama will unload it (RFC 3339).
+    pub expires_at: Option<String>,
+}
+
+impl OllamaLoadedModel {
+    pub fn size_gb(&self) -> f64 {
+        self.size_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+    }
+
+    pub fn vram_gb(&self) -> f64 {
+        self.size_vram_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+    }
+
+    pub fn ram_gb(&self) -> f64 {
+        self.size_bytes.saturating_sub(self.size_vram_bytes) as f64 / (1024.0 * 1024.0 * 1024.0)
+    }
+
+    /// Share of the model on the GPU, as `ollama ps` shows it.
+    pub fn gpu_percent(&self) -> f64 {
+        if self.size_bytes == 0 {
+            return 0.0;
+        }
+        (self.size_vram_bytes as f64 / self.size_bytes as f64 * 100.0).min(100.0)
+    }
+}
+
+/// The fit `apply_ollama_installed` made for an Ollama tag: the database
+/// model it's a build of, or the one synthesized for it.
+pub fn fit_for_ollama_tag<'a>(
+    fits: &'a [ModelFit],
+    details: &[OllamaModelInfo],
+    tag: &str,
+) -> Option<&'a ModelFit> {
+    let info = details.iter().find(|info| info.name == tag);
+    fits.iter().find(|f| f.model.name == tag).or_else(|| {
+        fits.iter()
+            .find(|f| info.is_some_and(|i| i.matches(&f.model.name)))
+    })
+}
+
+/// An installed Ollama model, as `/api/tags` and `/api/show` describe it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OllamaModelInfo {
+    /// Tag, e.g. "llama3.1:8b".
+    pub name: String,
+    pub size_bytes: u64,
+    pub parameter_count: Option<u64>,
+    /// Rounded size Ollama displays, e.g. "8.0B".
+    pub parameter_size: Option<String>,
+    /// GGUF quant, e.g. "Q4_K_M".
+    pub quantization: Option<String>,
+    pub family: Option<String>,
+    /// GGUF `general.architecture`, e.g. "llama", "qwen2moe".
+    pub architecture: Option<String>,
+    pub context_length: Option<u32>,
+    pub expert_count: Option<u32>,
+    pub expert_used_count: Option<u32>,
+    /// "completion", "vision", "embedding", ... (Ollama 0.6+).
+    pub capabilities: Vec<String>,
+}
+
+impl OllamaModelInfo {
+    /// Fill in fields from an `/api/show` response.
+    pub fn apply_show(&mut self, show: &serde_json::Value) {
+        let text = |v: Option<&serde_json::Value>| v?.as_str().map(str::to_string);
+        if let Some(details) = show.get("details") {
+            self.family = text(details.get("family")).or(self.family.take());
+            self.parameter_size =
+                text(details.get("parameter_size")).or(self.parameter_size.take());
+            self.quantization =
+                text(details.get("quantization_level")).or(self.quantization.take());
+        }
+        if let Some(info) = show.get("model_info") {
+            let arch = text(info.get("general.architecture"));
+            let arch_u32 = |key: &str| {
+                let arch = arch.as_deref()?;
+                info.get(format!("{}.{}", arch, key))?
+                    .as_u64()
+                    .map(|n| n as u32)
+            };
+            self.parameter_count = info
+                .get("general.parameter_count")
+                .and_then(|n| n.as_u64())
+                .or(self.parameter_count);
+            self.context_length = arch_u32("context_length").or(self.context_length);
+            self.expert_count = arch_u32("expert_count").or(self.expert_count);
+            self.expert_used_count = arch_u32("expert_used_count").or(self.expert_used_count);
+            self.architecture = arch.or(self.architecture.take());
+        }
+        if let Some(caps) = show.get("capabilities").and_then(|c| c.as_array()) {
+            self.capabilities = caps
+                .iter()
+                .filter_map(|c| c.as_str().map(str::to_string))
+                .collect();
+        }
+    }
+
+    /// Whether this is the Ollama build of a database model.
+    pub fn matches(&self, hf_name: &str) -> bool {
+        let lower = self.name.to_lowercase();
+        let family = lower.split(':').next().unwrap_or(&lower).to_string();
+        is_model_installed(hf_name, &HashSet::from([lower.clone(), family]))
+    }
+
+    /// A database entry for a model llmfit doesn't know, sized like the
+    /// scraper sizes catalogue models but from the installed file.
+    pub fn to_llm_model(&self) -> LlmModel {
+        let quantization = self
+            .quantization
+            .clone()
+            .unwrap_or_else(|| "Q4_K_M".to_string());
+        let parameter_count = self
+            .parameter_size
+            .clone()
+            .or_else(|| {
+                self.parameter_count
+                    .map(|n| format!("{:.1}B", n as f64 / 1_000_000_000.0))
+            })
+            .unwrap_or_else(|| "7B".to_string());
+        let mut model = LlmModel {
+            name: self.name.clone(),
+            provider: "Ollama".to_string(),
+            parameter_count,
+            parameters_raw: self.parameter_count,
+            min_ram_gb: 0.0,
+            recommended_ram_gb: 0.0,
+            min_vram_gb: None,
+            quantization,
+            context_length: self.context_length.unwrap_or(4096),
+            use_case: self.use_case().to_string(),
+            is_moe: self.expert_count.is_some_and(|n| n > 1),
+            num_experts: self.expert_count.filter(|&n| n > 1),
+            active_experts: self
+                .expert_used_count
+                .filter(|_| self.expert_count > Some(1)),
+            active_parameters: None,
+        };
+        let size_gb = if self.size_bytes > 0 {
+            self.size_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+        } else {
+            model.download_size_gb(&model.quantization)
+        };
+        model.min_ram_gb = (size_gb * 1.2).max(1.0);
+        model.recommended_ram_gb = (size_gb * 2.0).max(2.0);
+        model.min_vram_gb = Some((size_gb * 1.1).max(0.5));
+        model
+    }
+
+    fn use_case(&self) -> &'static str {
+        let family = self.family.as_deref().unwrap_or("").to_lowercase();
+        if self.capabilities.iter().any(|c| c == "embedding") || family.contains("bert") {
+            "Text embeddings"
+        } else if self.capabilities.iter().any(|c| c == "vision") {
+            "Multimodal, vision and text"
+        } else {
+            "General purpose text generation"
+        }
+    }
+}
+
+/// Fit what's installed in Ollama as installed: database models with an
+/// installed build are re-analyzed at its exact quant, and tags llmfit has
+/// no entry for are added with a model synthesized from their metadata.
+pub fn apply_ollama_installed(
+    fits: &mut Vec<ModelFit>,
+    installed: &[OllamaModelInfo],
+    system: &SystemSpecs,
+) {
+    let mut matched = vec![false; installed.len()];
+    for fit in fits.iter_mut() {
+        let Some(i) = installed
+            .iter()
+            .position(|info| info.matches(&fit.model.name))
+        else {
+            continue;
+        };
+        matched[i] = true;
+        if let Some(quant) = &installed[i].quantization {
+            *fit = ModelFit::analyze_installed(&fit.model, system, quant);
+        }
+        fit.installed = true;
+    }
+    for (info, _) in installed.iter().zip(matched).filter(|(_, m)| !m) {
+        let model = info.to_llm_model();
+        let mut fit = ModelFit::analyze_installed(&model, system, &model.quantization);
+        fit.installed = true;
+        match fits.iter_mut().find(|f| f.model.name == info.name) {
+            Some(existing) => *existing = fit,
+            None => fits.push(fit),
+        }
+    }
 }
 
 // -- JSON response types for Ollama API --
@@ -82,6 +370,38 @@ struct TagsResponse {
 struct OllamaModel {
     /// e.g. "llama3.1:8b-instruct-q4_K_M"
     name: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    details: OllamaDetails,
+}
+
+#[derive(serde::Deserialize)]
+struct PsResponse {
+    models: Vec<PsModel>,
+}
+
+#[derive(serde::Deserialize)]
+struct PsModel {
+    name: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    size_vram: u64,
+    #[serde(default)]
+    context_length: Option<u32>,
+    #[serde(default)]
+    expires_at: Option<String>,
+}
+
+#[derive(Default, serde::Deserialize)]
+struct OllamaDetails {
+    #[serde(default)]
+    family: Option<String>,
+    #[serde(default)]
+    parameter_size: Option<String>,
+    #[serde(default)]
+    quantization_level: Option<String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -196,6 +516,56 @@ impl ModelProvider for OllamaProvider {
             receiver: rx,
         })
     }
+
+    /// Only full `family:tag` names: the set also holds bare family names,
+    /// which `/api/delete` can't take.
+    fn installed_tags(&self, hf_name: &str, installed: &HashSet<String>) -> Vec<String> {
+        let mut tags: Vec<String> = installed
+            .iter()
+            .filter(|name| name.contains(':'))
+            .filter(|name| {
+                OllamaModelInfo {
+                    name: (*name).clone(),
+                    ..Default::default()
+                }
+                .matches(hf_name)
+            })
+            .cloned()
+            .collect();
+        tags.sort_by_key(|t| (t.len(), t.clone()));
+        tags
+    }
+
+    /// `DELETE /api/delete`. Blobs shared with another tag stay on disk, so
+    /// the size `/api/tags` lists is an upper bound on what's freed.
+    fn remove(&self, model_tag: &str) -> Result<u64, String> {
+        let size = ureq::get(&self.api_url("tags"))
+            .timeout(std::time::Duration::from_secs(5))
+            .call()
+            .ok()
+            .and_then(|resp| resp.into_json::<TagsResponse>().ok())
+            .and_then(|tags| {
+                tags.models
+                    .into_iter()
+                    .find(|m| {
+                        m.name.eq_ignore_ascii_case(model_tag)
+                            || m.name
+                                .eq_ignore_ascii_case(&format!("{}:latest", model_tag))
+                    })
+                    .map(|m| m.size)
+            })
+            .unwrap_or(0);
+        match ureq::delete(&self.api_url("delete"))
+            .timeout(std::time::Duration::from_secs(30))
+            .send_json(serde_json::json!({ "model": model_tag }))
+        {
+            Ok(_) => Ok(size),
+            Err(ureq::Error::Status(404, _)) => {
+                Err(format!("{} is not installed in Ollama", model_tag))
+            }
+            Err(e) => Err(format!("{}: {}", model_tag, e)),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -238,25 +608,43 @@ fn check_mlx_python() -> bool {
 
 /// Scan ~/.cache/huggingface/hub/ for MLX model directories.
 fn scan_hf_cache_for_mlx() -> HashSet<String> {
-    let mut set = HashSet::new();
-    let cache_dir = dirs_hf_cache();
-    let Ok(entries) = std::fs::read_dir(&cache_dir) else {
-        return set;
+    mlx_cache_entries(&hf_cache_dir())
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// MLX repo dirs in a Hugging Face cache, with their normalised names.
+fn mlx_cache_entries(cache_dir: &std::path::Path) -> Vec<(String, std::path::PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return Vec::new();
     };
-    for entry in entries.flatten() {
-        let name = entry.file_name();
-        let name_str = name.to_string_lossy();
-        if let Some(rest) = name_str.strip_prefix("models--mlx-community--") {
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let name_str = name.to_string_lossy();
             // Directory name: models--mlx-community--Llama-3.1-8B-Instruct-4bit
             // Normalize to lowercase
-            let model_name = rest.replace("--", "/").to_lowercase();
-            set.insert(model_name);
-        }
-    }
-    set
+            let rest = name_str.strip_prefix("models--mlx-community--")?;
+            Some((rest.replace("--", "/").to_lowercase(), entry.path()))
+        })
+        .collect()
 }
 
-fn dirs_hf_cache() -> std::path::PathBuf {
+/// Delete an MLX model's repo dir (snapshots and blobs) from `cache_dir`.
+fn remove_mlx_from(cache_dir: &std::path::Path, model_tag: &str) -> Result<u64, String> {
+    let lower = model_tag.to_lowercase();
+    let name = lower.strip_prefix("mlx-community/").unwrap_or(&lower);
+    let (_, dir) = mlx_cache_entries(cache_dir)
+        .into_iter()
+        .find(|(entry, _)| entry == name)
+        .ok_or_else(|| format!("{} is not in the Hugging Face cache", model_tag))?;
+    remove_path(&dir)
+}
+
+/// Hugging Face hub cache that MLX pulls download into.
+pub fn hf_cache_dir() -> std::path::PathBuf {
     if let Ok(cache) = std::env::var("HF_HOME") {
         std::path::PathBuf::from(cache).join("hub")
     } else if let Ok(home) = std::env::var("HOME") {
@@ -344,6 +732,14 @@ impl ModelProvider for MlxProvider {
             receiver: rx,
         })
     }
+
+    fn installed_tags(&self, hf_name: &str, installed: &HashSet<String>) -> Vec<String> {
+        matching_tags(hf_name, installed, is_model_installed_mlx)
+    }
+
+    fn remove(&self, model_tag: &str) -> Result<u64, String> {
+        remove_mlx_from(&hf_cache_dir(), model_tag)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -449,167 +845,1822 @@ pub fn mlx_pull_tag(hf_name: &str) -> String {
 }
 
 // ---------------------------------------------------------------------------
-// Ollama name-matching helpers
+// llama.cpp server provider
 // ---------------------------------------------------------------------------
 
-/// Authoritative mapping from HF repo name (lowercased, after slash) to Ollama tag.
-/// Only models with a known Ollama registry entry are listed here.
-/// If a model is not in this table, it cannot be pulled from Ollama.
-const OLLAMA_MAPPINGS: &[(&str, &str)] = &[
-    // Meta Llama family
-    ("llama-3.3-70b-instruct", "llama3.3:70b"),
-    ("llama-3.2-11b-vision-instruct", "llama3.2-vision:11b"),
-    ("llama-3.2-3b-instruct", "llama3.2:3b"),
-    ("llama-3.2-3b", "llama3.2:3b"),
-    ("llama-3.2-1b-instruct", "llama3.2:1b"),
-    ("llama-3.2-1b", "llama3.2:1b"),
-    ("llama-3.1-405b-instruct", "llama3.1:405b"),
-    ("llama-3.1-405b", "llama3.1:405b"),
-    ("llama-3.1-70b-instruct", "llama3.1:70b"),
-    ("llama-3.1-8b-instruct", "llama3.1:8b"),
-    ("llama-3.1-8b", "llama3.1:8b"),
-    ("meta-llama-3-8b-instruct", "llama3:8b"),
-    ("meta-llama-3-8b", "llama3:8b"),
-    ("llama-2-7b-hf", "llama2:7b"),
-    ("codellama-34b-instruct-hf", "codellama:34b"),
-    ("codellama-13b-instruct-hf", "codellama:13b"),
-    ("codellama-7b-instruct-hf", "codellama:7b"),
-    // Google Gemma
-    ("gemma-3-12b-it", "gemma3:12b"),
-    ("gemma-2-27b-it", "gemma2:27b"),
-    ("gemma-2-9b-it", "gemma2:9b"),
-    ("gemma-2-2b-it", "gemma2:2b"),
-    // Microsoft Phi
-    ("phi-4", "phi4"),
-    ("phi-4-mini-instruct", "phi4-mini"),
-    ("phi-3.5-mini-instruct", "phi3.5"),
-    ("phi-3-mini-4k-instruct", "phi3"),
-    ("phi-3-medium-14b-instruct", "phi3:14b"),
-    ("phi-2", "phi"),
-    ("orca-2-7b", "orca2:7b"),
-    ("orca-2-13b", "orca2:13b"),
-    // Mistral
-    ("mistral-7b-instruct-v0.3", "mistral:7b"),
-    ("mistral-7b-instruct-v0.2", "mistral:7b"),
-    ("mistral-nemo-instruct-2407", "mistral-nemo"),
-    ("mistral-small-24b-instruct-2501", "mistral-small:24b"),
-    ("mistral-large-instruct-2407", "mistral-large"),
-    ("mixtral-8x7b-instruct-v0.1", "mixtral:8x7b"),
-    ("mixtral-8x22b-instruct-v0.1", "mixtral:8x22b"),
-    // Qwen 2 / 2.5
-    ("qwen2-1.5b-instruct", "qwen2:1.5b"),
-    ("qwen2.5-72b-instruct", "qwen2.5:72b"),
-    ("qwen2.5-32b-instruct", "qwen2.5:32b"),
-    ("qwen2.5-14b-instruct", "qwen2.5:14b"),
-    ("qwen2.5-7b-instruct", "qwen2.5:7b"),
-    ("qwen2.5-7b", "qwen2.5:7b"),
-    ("qwen2.5-3b-instruct", "qwen2.5:3b"),
-    ("qwen2.5-1.5b-instruct", "qwen2.5:1.5b"),
-    ("qwen2.5-1.5b", "qwen2.5:1.5b"),
-    ("qwen2.5-0.5b-instruct", "qwen2.5:0.5b"),
-    ("qwen2.5-0.5b", "qwen2.5:0.5b"),
-    ("qwen2.5-coder-32b-instruct", "qwen2.5-coder:32b"),
-    ("qwen2.5-coder-14b-instruct", "qwen2.5-coder:14b"),
-    ("qwen2.5-coder-7b-instruct", "qwen2.5-coder:7b"),
-    ("qwen2.5-coder-1.5b-instruct", "qwen2.5-coder:1.5b"),
-    ("qwen2.5-coder-0.5b-instruct", "qwen2.5-coder:0.5b"),
-    ("qwen2.5-vl-7b-instruct", "qwen2.5vl:7b"),
-    ("qwen2.5-vl-3b-instruct", "qwen2.5vl:3b"),
-    // Qwen 3
-    ("qwen3-235b-a22b", "qwen3:235b"),
-    ("qwen3-32b", "qwen3:32b"),
-    ("qwen3-30b-a3b", "qwen3:30b-a3b"),
-    ("qwen3-30b-a3b-instruct-2507", "qwen3:30b-a3b"),
-    ("qwen3-14b", "qwen3:14b"),
-    ("qwen3-8b", "qwen3:8b"),
-    ("qwen3-4b", "qwen3:4b"),
-    ("qwen3-4b-instruct-2507", "qwen3:4b"),
-    ("qwen3-1.7b-base", "qwen3:1.7b"),
-    ("qwen3-0.6b", "qwen3:0.6b"),
-    ("qwen3-coder-30b-a3b-instruct", "qwen3-coder"),
-    // DeepSeek
-    ("deepseek-v3", "deepseek-v3"),
-    ("deepseek-v3.2", "deepseek-v3"),
-    ("deepseek-r1", "deepseek-r1"),
-    ("deepseek-r1-0528", "deepseek-r1"),
-    ("deepseek-r1-distill-qwen-32b", "deepseek-r1:32b"),
-    ("deepseek-r1-distill-qwen-14b", "deepseek-r1:14b"),
-    ("deepseek-r1-distill-qwen-7b", "deepseek-r1:7b"),
-    ("deepseek-coder-v2-lite-instruct", "deepseek-coder-v2:16b"),
-    // Community / other
-    ("tinyllama-1.1b-chat-v1.0", "tinyllama"),
-    ("stablelm-2-1_6b-chat", "stablelm2:1.6b"),
-    ("yi-6b-chat", "yi:6b"),
-    ("yi-34b-chat", "yi:34b"),
-    ("starcoder2-7b", "starcoder2:7b"),
-    ("starcoder2-15b", "starcoder2:15b"),
-    ("falcon-7b-instruct", "falcon:7b"),
-    ("falcon-40b-instruct", "falcon:40b"),
-    ("falcon-180b-chat", "falcon:180b"),
-    ("falcon3-7b-instruct", "falcon3:7b"),
-    ("openchat-3.5-0106", "openchat:7b"),
-    ("vicuna-7b-v1.5", "vicuna:7b"),
-    ("vicuna-13b-v1.5", "vicuna:13b"),
-    ("glm-4-9b-chat", "glm4:9b"),
-    ("solar-10.7b-instruct-v1.0", "solar:10.7b"),
-    ("zephyr-7b-beta", "zephyr:7b"),
-    ("c4ai-command-r-v01", "command-r"),
-    (
-        "nous-hermes-2-mixtral-8x7b-dpo",
-        "nous-hermes2-mixtral:8x7b",
-    ),
-    ("hermes-3-llama-3.1-8b", "hermes3:8b"),
-    ("nomic-embed-text-v1.5", "nomic-embed-text"),
-    ("bge-large-en-v1.5", "bge-large"),
-    ("smollm2-135m-instruct", "smollm2:135m"),
-    ("smollm2-135m", "smollm2:135m"),
-];
+/// Quant pulled when a GGUF tag doesn't name one.
+const DEFAULT_GGUF_QUANT: &str = "Q4_K_M";
 
-/// Look up the Ollama tag for an HF repo name. Returns the first match
-/// from `OLLAMA_MAPPINGS`, or `None` if the model has no known Ollama equivalent.
-fn lookup_ollama_tag(hf_name: &str) -> Option<&'static str> {
-    let repo = hf_name
-        .split('/')
-        .next_back()
-        .unwrap_or(hf_name)
-        .to_lowercase();
-    OLLAMA_MAPPINGS
-        .iter()
-        .find(|&&(hf_suffix, _)| repo == hf_suffix)
-        .map(|&(_, tag)| tag)
+/// `llama-server` from llama.cpp. Loaded models come from its HTTP API;
+/// GGUFs are downloaded from a Hugging Face–compatible endpoint into the
+/// llama.cpp cache, where `llama-server -m` (or `-hf`) can load them.
+pub struct LlamaServerProvider {
+    base_url: String,
+    hf_endpoint: String,
+    models_dir: std::path::PathBuf,
 }
 
-/// Map a HuggingFace model name to Ollama candidate tags for install checking.
-/// Returns candidates from the authoritative mapping table only.
-pub fn hf_name_to_ollama_candidates(hf_name: &str) -> Vec<String> {
-    match lookup_ollama_tag(hf_name) {
-        Some(tag) => vec![tag.to_string()],
-        None => vec![],
+impl Default for LlamaServerProvider {
+    fn default() -> Self {
+        Self {
+            base_url: std::env::var("LLAMA_SERVER_HOST")
+                .unwrap_or_else(|_| "http://localhost:8080".to_string()),
+            hf_endpoint: std::env::var("HF_ENDPOINT")
+                .unwrap_or_else(|_| "https://huggingface.co".to_string()),
+            models_dir: llama_cpp_cache_dir(),
+        }
     }
 }
 
-/// Returns `true` if this HF model has a known Ollama registry entry
-/// and can be pulled.
-pub fn has_ollama_mapping(hf_name: &str) -> bool {
-    lookup_ollama_tag(hf_name).is_some()
+/// What a running `llama-server` reports about itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LlamaServerStatus {
+    /// Model IDs from `/v1/models` (an alias or the GGUF path).
+    pub models: Vec<String>,
+    /// Context size per slot, from `/props`.
+    pub n_ctx: Option<u32>,
+    /// Parallel request slots (`--parallel`), from `/props`.
+    pub total_slots: Option<u32>,
 }
 
-/// Check if any of the Ollama candidates for an HF model appear in the
-/// installed set.
-pub fn is_model_installed(hf_name: &str, installed: &HashSet<String>) -> bool {
-    let candidates = hf_name_to_ollama_candidates(hf_name);
-    candidates.iter().any(|c| installed.contains(c))
-}
+impl LlamaServerProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-/// Given an HF model name, return the Ollama tag to use for pulling.
-/// Returns `None` if the model has no known Ollama mapping.
-pub fn ollama_pull_tag(hf_name: &str) -> Option<String> {
-    lookup_ollama_tag(hf_name).map(|s| s.to_string())
-}
+    /// Provider for a server at `base_url`, downloading from `hf_endpoint`
+    /// into `models_dir`.
+    pub fn with_config(base_url: &str, hf_endpoint: &str, models_dir: std::path::PathBuf) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            hf_endpoint: hf_endpoint.to_string(),
+            models_dir,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url.trim_end_matches('/'), path)
+    }
+
+    fn get_json(&self, path: &str) -> Option<serde_json::Value> {
+        ureq::get(&self.url(path))
+            .timeout(std::time::Duration::from_secs(2))
+            .call()
+            .ok()?
+            .into_json()
+            .ok()
+    }
+
+    /// Query `/v1/models` and `/props`. `None` when the server is down.
+    pub fn status(&self) -> Option<LlamaServerStatus> {
+        let models = self.get_json("/v1/models")?;
+        let props = self.get_json("/props");
+        let as_u32 = |v: Option<&serde_json::Value>| v?.as_u64().map(|n| n as u32);
+        Some(LlamaServerStatus {
+            models: models
+                .get("data")
+                .and_then(|d| d.as_array())
+                .map(|data| {
+                    data.iter()
+                        .filter_map(|m| m.get("id")?.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            n_ctx: props.as_ref().and_then(|p| {
+                as_u32(p.pointer("/default_generation_settings/n_ctx"))
+                    .or_else(|| as_u32(p.get("n_ctx")))
+            }),
+            total_slots: props.as_ref().and_then(|p| as_u32(p.get("total_slots"))),
+        })
+    }
+
+    /// GGUF files already in the download dir (and one level below it).
+    fn local_gguf_names(&self) -> HashSet<String> {
+        self.local_gguf_files()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Paths of those GGUF files, with their normalised names.
+    fn local_gguf_files(&self) -> Vec<(String, std::path::PathBuf)> {
+        let mut files = Vec::new();
+        let mut dirs = vec![self.models_dir.clone()];
+        if let Ok(entries) = std::fs::read_dir(&self.models_dir) {
+            dirs.extend(entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()));
+        }
+        for dir in dirs {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                if name.ends_with(".gguf") {
+                    files.push((gguf_model_name(&name), entry.path()));
+                }
+            }
+        }
+        files
+    }
+}
+
+/// Normalise a llama-server model ID or GGUF file name for matching:
+/// "/models/Llama-3.2-3B-Instruct-Q4_K_M.gguf" → "llama-3.2-3b-instruct-q4_k_m",
+/// "bartowski/Llama-3.2-3B-Instruct-GGUF:Q4_K_M" → "llama-3.2-3b-instruct".
+/// Every part of a split GGUF ("...-00002-of-00003.gguf") gets the same name.
+pub fn gguf_model_name(id: &str) -> String {
+    let file = id.rsplit(['/', '\\']).next().unwrap_or(id);
+    let file = file.split(':').next().unwrap_or(file).to_lowercase();
+    let file = file.strip_suffix(".gguf").unwrap_or(&file);
+    let file = strip_split_suffix(file);
+    file.strip_suffix("-gguf").unwrap_or(file).to_string()
+}
+
+/// "model-q4_k_m-00001-of-00002" → "model-q4_k_m".
+fn strip_split_suffix(name: &str) -> &str {
+    let is_index = |s: &str| s.len() == 5 && s.bytes().all(|b| b.is_ascii_digit());
+    let Some((head, total)) = name.rsplit_once("-of-") else {
+        return name;
+    };
+    match head.rsplit_once('-') {
+        Some((stem, part)) if is_index(part) && is_index(total) => stem,
+        _ => name,
+    }
+}
+
+/// llama.cpp's download cache: `LLAMA_CACHE` if set, else the platform
+/// cache dir's `llama.cpp` folder.
+pub fn llama_cpp_cache_dir() -> std::path::PathBuf {
+    if let Ok(dir) = std::env::var("LLAMA_CACHE") {
+        return std::path::PathBuf::from(dir);
+    }
+    if let Ok(dir) = std::env::var("XDG_CACHE_HOME") {
+        return std::path::PathBuf::from(dir).join("llama.cpp");
+    }
+    if cfg!(windows)
+        && let Ok(dir) = std::env::var("LOCALAPPDATA")
+    {
+        return std::path::PathBuf::from(dir).join("llama.cpp");
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+    let cache = if cfg!(target_os = "macos") {
+        std::path::PathBuf::from(home)
+            .join("Library")
+            .join("Caches")
+    } else {
+        std::path::PathBuf::from(home).join(".cache")
+    };
+    cache.join("llama.cpp")
+}
+
+/// Pick the GGUF in a repo's file list for `quant`, preferring a single
+/// file over a split one. A split GGUF comes back as all of its parts, in
+/// order; one with parts missing from the list is an error.
+fn pick_gguf_files(files: &[String], quant: &str) -> Result<Vec<String>, String> {
+    let quant = quant.to_lowercase();
+    let mut matches: Vec<&String> = files
+        .iter()
+        .filter(|f| {
+            let lower = f.to_lowercase();
+            lower.ends_with(".gguf") && lower.contains(&quant)
+        })
+        .filter(|f| !f.contains("-of-") || f.contains("-00001-of-"))
+        .collect();
+    matches.sort_by_key(|f| (f.contains("-of-"), f.len()));
+    let first = matches
+        .first()
+        .ok_or_else(|| format!("no {} GGUF", quant.to_uppercase()))?;
+    let Some((prefix, rest)) = first.split_once("-00001-of-") else {
+        return Ok(vec![first.to_string()]);
+    };
+    // "-00001-of-00003.gguf": parts 00001..=00003 share the prefix.
+    let count: usize = rest
+        .strip_suffix(".gguf")
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(|| format!("unrecognised split GGUF name {}", first))?;
+    let parts: Vec<String> = (1..=count)
+        .map(|i| format!("{}-{:05}-of-{}", prefix, i, rest))
+        .collect();
+    match parts.iter().find(|part| !files.contains(part)) {
+        Some(missing) => Err(format!("split GGUF is missing {}", missing)),
+        None => Ok(parts),
+    }
+}
+
+/// File names in a Hugging Face model repo.
+fn hf_repo_files(endpoint: &str, repo: &str) -> Result<Vec<String>, String> {
+    let info: serde_json::Value = ureq::get(&format!("{}/api/models/{}", endpoint, repo))
+        .timeout(std::time::Duration::from_secs(30))
+        .call()
+        .map_err(|e| format!("{}: {}", repo, e))?
+        .into_json()
+        .map_err(|e| format!("{}: {}", repo, e))?;
+    Ok(info
+        .get("siblings")
+        .and_then(|s| s.as_array())
+        .map(|s| {
+            s.iter()
+                .filter_map(|f| f.get("rfilename")?.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Most-downloaded GGUF repo whose name contains `name`, for when the
+/// guessed repo doesn't exist.
+fn search_gguf_repo(endpoint: &str, name: &str) -> Option<String> {
+    let results: serde_json::Value = ureq::get(&format!("{}/api/models", endpoint))
+        .query("search", name)
+        .query("filter", "gguf")
+        .query("sort", "downloads")
+        .query("direction", "-1")
+        .query("limit", "10")
+        .timeout(std::time::Duration::from_secs(30))
+        .call()
+        .ok()?
+        .into_json()
+        .ok()?;
+    let name = name.to_lowercase();
+    results.as_array()?.iter().find_map(|m| {
+        let id = m.get("id").or_else(|| m.get("modelId"))?.as_str()?;
+        let lower = id.to_lowercase();
+        (lower.contains(&name) && lower.ends_with("gguf")).then(|| id.to_string())
+    })
+}
+
+/// Download `repo`'s GGUF for `quant` into `dest_dir`, reporting progress.
+/// Every part of a split GGUF is fetched; if any fails, none is kept.
+fn download_gguf(
+    hf_endpoint: &str,
+    repo: &str,
+    quant: &str,
+    dest_dir: &std::path::Path,
+    tx: &std::sync::mpsc::Sender<PullEvent>,
+) -> Result<(), String> {
+    let endpoint = hf_endpoint.trim_end_matches('/');
+    let (repo, files) = match hf_repo_files(endpoint, repo) {
+        Ok(files) => (repo.to_string(), files),
+        Err(e) => {
+            let base = repo.rsplit('/').next().unwrap_or(repo);
+            let base = base.strip_suffix("-GGUF").unwrap_or(base);
+            let found = search_gguf_repo(endpoint, base).ok_or(e)?;
+            let files = hf_repo_files(endpoint, &found)?;
+            (found, files)
+        }
+    };
+    let parts = pick_gguf_files(&files, quant).map_err(|e| format!("{} in {}", e, repo))?;
+
+    std::fs::create_dir_all(dest_dir)
+        .map_err(|e| format!("failed to create {}: {}", dest_dir.display(), e))?;
+    let mut done = Vec::new();
+    for (i, file) in parts.iter().enumerate() {
+        let label = if parts.len() > 1 {
+            format!("{} ({}/{})", file, i + 1, parts.len())
+        } else {
+            file.clone()
+        };
+        // Same flat naming as `llama-server -hf`: owner_repo_file. Parts
+        // keep their shared prefix, so llama.cpp finds them from the first.
+        let dest = dest_dir.join(format!(
+            "{}_{}",
+            repo.replace('/', "_"),
+            file.replace('/', "_")
+        ));
+        let url = format!("{}/{}/resolve/main/{}", endpoint, repo, file);
+        if let Err(e) = download_file(&url, &dest, &label, tx) {
+            for path in &done {
+                let _ = std::fs::remove_file(path);
+            }
+            return Err(e);
+        }
+        done.push(dest);
+    }
+    Ok(())
+}
+
+/// Stream `url` to `dest` through a `.part` file, which is removed if the
+/// download fails.
+fn download_file(
+    url: &str,
+    dest: &std::path::Path,
+    label: &str,
+    tx: &std::sync::mpsc::Sender<PullEvent>,
+) -> Result<(), String> {
+    let resp = ureq::get(url)
+        .timeout(std::time::Duration::from_secs(3600))
+        .call()
+        .map_err(|e| format!("{}: {}", label, e))?;
+    let total: Option<u64> = resp
+        .header("Content-Length")
+        .and_then(|len| len.parse().ok());
+
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(".part");
+    let partial = std::path::PathBuf::from(partial);
+    let result = (|| {
+        let mut out = std::fs::File::create(&partial)
+            .map_err(|e| format!("failed to create {}: {}", partial.display(), e))?;
+        let mut reader = resp.into_reader();
+        let mut buf = vec![0u8; 1 << 20];
+        let mut done: u64 = 0;
+        let mut reported = None;
+        loop {
+            use std::io::{Read, Write};
+            let n = reader
+                .read(&mut buf)
+                .map_err(|e| format!("download interrupted: {}", e))?;
+            if n == 0 {
+                break;
+            }
+            out.write_all(&buf[..n])
+                .map_err(|e| format!("failed to write {}: {}", partial.display(), e))?;
+            done += n as u64;
+            // One event per whole percent, or per 100 MB when the size is unknown.
+            let step = match total {
+                Some(t) => done * 100 / t.max(1),
+                None => done / 100_000_000,
+            };
+            if reported != Some(step) {
+                reported = Some(step);
+                let _ = tx.send(PullEvent::Progress {
+                    status: format!("Downloading {}", label),
+                    percent: total.map(|_| step as f64),
+                });
+            }
+        }
+        drop(out);
+        std::fs::rename(&partial, dest)
+            .map_err(|e| format!("failed to move {} into place: {}", dest.display(), e))
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+impl ModelProvider for LlamaServerProvider {
+    fn name(&self) -> &str {
+        "llama.cpp"
+    }
+
+    fn is_available(&self) -> bool {
+        // 503 while a model is still loading still means the server is up.
+        match ureq::get(&self.url("/health"))
+            .timeout(std::time::Duration::from_secs(2))
+            .call()
+        {
+            Ok(_) => true,
+            Err(ureq::Error::Status(503, _)) => true,
+            Err(_) => false,
+        }
+    }
+
+    fn installed_models(&self) -> HashSet<String> {
+        let mut set = self.local_gguf_names();
+        if let Some(status) = self.status() {
+            set.extend(status.models.iter().map(|id| gguf_model_name(id)));
+        }
+        set
+    }
+
+    /// `model_tag` is a GGUF repo with an optional quant, as `llama-server
+    /// -hf` takes it: "bartowski/Llama-3.2-3B-Instruct-GGUF:Q4_K_M".
+    fn start_pull(&self, model_tag: &str) -> Result<PullHandle, String> {
+        let (repo, quant) = match model_tag.split_once(':') {
+            Some((repo, quant)) => (repo.to_string(), quant.to_string()),
+            None => (model_tag.to_string(), DEFAULT_GGUF_QUANT.to_string()),
+        };
+        if repo.split('/').count() != 2 {
+            return Err(format!("expected owner/repo[:quant], got {}", model_tag));
+        }
+        let hf_endpoint = self.hf_endpoint.clone();
+        let dest_dir = self.models_dir.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            let _ = tx.send(PullEvent::Progress {
+                status: format!("Resolving {} {}...", repo, quant),
+                percent: None,
+            });
+            match download_gguf(&hf_endpoint, &repo, &quant, &dest_dir, &tx) {
+                Ok(()) => {
+                    let _ = tx.send(PullEvent::Done);
+                }
+                Err(e) => {
+                    let _ = tx.send(PullEvent::Error(e));
+                }
+            }
+        });
+
+        Ok(PullHandle {
+            model_tag: model_tag.to_string(),
+            receiver: rx,
+        })
+    }
+
+    fn installed_tags(&self, hf_name: &str, installed: &HashSet<String>) -> Vec<String> {
+        matching_tags(hf_name, installed, is_model_installed_gguf)
+    }
+
+    /// Deletes every cached GGUF file with this name. Models the server
+    /// loaded from elsewhere aren't touched.
+    fn remove(&self, model_tag: &str) -> Result<u64, String> {
+        let name = gguf_model_name(model_tag);
+        let files: Vec<std::path::PathBuf> = self
+            .local_gguf_files()
+            .into_iter()
+            .filter(|(file, _)| *file == name)
+            .map(|(_, path)| path)
+            .collect();
+        if files.is_empty() {
+            return Err(format!("{} is not in the llama.cpp cache", model_tag));
+        }
+        files.iter().map(|path| remove_path(path)).sum()
+    }
+}
+
+/// Suffixes that may follow a repo name in a GGUF file or model ID.
+const GGUF_NAME_SUFFIXES: &[&str] = &["q", "iq", "f16", "bf16", "f32", "gguf"];
+
+/// Check if a GGUF for an HF model is loaded in llama-server or cached.
+/// The repo name must be followed by nothing but a quant or "GGUF", so a
+/// base model doesn't match its "-Instruct" sibling.
+pub fn is_model_installed_gguf(hf_name: &str, installed: &HashSet<String>) -> bool {
+    installed_with_suffix(hf_name, installed, GGUF_NAME_SUFFIXES)
+}
+
+/// Whether some installed name contains `hf_name`'s repo followed by
+/// nothing, or by a separator and one of `suffixes`.
+fn installed_with_suffix(hf_name: &str, installed: &HashSet<String>, suffixes: &[&str]) -> bool {
+    let repo = hf_name.rsplit('/').next().unwrap_or(hf_name).to_lowercase();
+    installed.iter().any(|name| {
+        name.match_indices(&repo).any(|(i, _)| {
+            let rest = &name[i + repo.len()..];
+            let Some(tail) = rest.strip_prefix(['-', '.', '_']) else {
+                return rest.is_empty();
+            };
+            suffixes.iter().any(|suffix| tail.starts_with(suffix))
+        })
+    })
+}
+
+/// GGUF tag to pull for an HF model: the community `-GGUF` conversion of
+/// the repo at `quant`, e.g. "bartowski/Llama-3.1-8B-Instruct-GGUF:Q4_K_M".
+pub fn gguf_pull_tag(hf_name: &str, quant: &str) -> String {
+    let repo = hf_name.rsplit('/').next().unwrap_or(hf_name);
+    format!("bartowski/{}-GGUF:{}", repo, quant)
+}
+
+// ---------------------------------------------------------------------------
+// LM Studio provider
+// ---------------------------------------------------------------------------
+
+/// LM Studio's local server. Models come from its REST API and from the
+/// models directory on disk, so downloads show up even with the server off.
+pub struct LmStudioProvider {
+    base_url: String,
+    models_dir: std::path::PathBuf,
+}
+
+impl Default for LmStudioProvider {
+    fn default() -> Self {
+        Self {
+            base_url: std::env::var("LMSTUDIO_HOST")
+                .unwrap_or_else(|_| "http://localhost:1234".to_string()),
+            models_dir: lm_studio_models_dir(),
+        }
+    }
+}
+
+/// A model the LM Studio server knows about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LmStudioModel {
+    pub id: String,
+    /// In memory right now ("loaded") rather than just downloaded.
+    pub loaded: bool,
+    /// "gguf" or "mlx", when the server says.
+    pub format: Option<String>,
+    pub quantization: Option<String>,
+    pub max_context_length: Option<u32>,
+}
+
+impl LmStudioProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Provider for a server at `base_url` with models under `models_dir`.
+    pub fn with_config(base_url: &str, models_dir: std::path::PathBuf) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            models_dir,
+        }
+    }
+
+    fn get_json(&self, path: &str) -> Option<serde_json::Value> {
+        ureq::get(&format!("{}{}", self.base_url.trim_end_matches('/'), path))
+            .timeout(std::time::Duration::from_secs(2))
+            .call()
+            .ok()?
+            .into_json()
+            .ok()
+    }
+
+    /// Models from `/api/v0/models`, which carries load state, plus any
+    /// `/v1/models` entries it lacks (older servers only have `/v1`).
+    /// `None` when the server is down.
+    pub fn models(&self) -> Option<Vec<LmStudioModel>> {
+        let data = |json: &serde_json::Value| -> Vec<serde_json::Value> {
+            json.get("data")
+                .and_then(|d| d.as_array())
+                .cloned()
+                .unwrap_or_default()
+        };
+        let v0 = self.get_json("/api/v0/models");
+        let v1 = self.get_json("/v1/models");
+        if v0.is_none() && v1.is_none() {
+            return None;
+        }
+
+        let text = |m: &serde_json::Value, key: &str| m.get(key)?.as_str().map(str::to_string);
+        let mut models: Vec<LmStudioModel> = v0
+            .iter()
+            .flat_map(data)
+            .filter_map(|m| {
+                Some(LmStudioModel {
+                    id: text(&m, "id")?,
+                    loaded: m.get("state").and_then(|s| s.as_str()) == Some("loaded"),
+                    format: text(&m, "compatibility_type"),
+                    quantization: text(&m, "quantization"),
+                    max_context_length: m
+                        .get("max_context_length")
+                        .and_then(|n| n.as_u64())
+                        .map(|n| n as u32),
+                })
+            })
+            .collect();
+        for m in v1.iter().flat_map(data) {
+            let Some(id) = text(&m, "id") else { continue };
+            if !models.iter().any(|known| known.id == id) {
+                models.push(LmStudioModel {
+                    id,
+                    loaded: false,
+                    format: None,
+                    quantization: None,
+                    max_context_length: None,
+                });
+            }
+        }
+        Some(models)
+    }
+
+    /// Models on disk, laid out as `<publisher>/<repo>/<files>`. Each repo
+    /// dir counts, as does each GGUF inside it.
+    fn local_model_names(&self) -> HashSet<String> {
+        self.local_model_paths()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// A path from `local_model_paths` as a tag relative to the models
+    /// dir, e.g. "lmstudio-community/Qwen2.5-7B-Instruct-GGUF".
+    fn model_tag(&self, path: &std::path::Path) -> Option<String> {
+        let rel = path.strip_prefix(&self.models_dir).ok()?;
+        let parts: Vec<_> = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        Some(parts.join("/"))
+    }
+
+    /// Those repo dirs and GGUF files, with their normalised names. Each
+    /// repo dir comes before the files inside it.
+    fn local_model_paths(&self) -> Vec<(String, std::path::PathBuf)> {
+        let mut paths = Vec::new();
+        let Ok(publishers) = std::fs::read_dir(&self.models_dir) else {
+            return paths;
+        };
+        for publisher in publishers.flatten().filter(|e| e.path().is_dir()) {
+            let Ok(repos) = std::fs::read_dir(publisher.path()) else {
+                continue;
+            };
+            for repo in repos.flatten().filter(|e| e.path().is_dir()) {
+                paths.push((
+                    lm_studio_model_name(&repo.file_name().to_string_lossy()),
+                    repo.path(),
+                ));
+                let Ok(files) = std::fs::read_dir(repo.path()) else {
+                    continue;
+                };
+                for file in files.flatten() {
+                    let name = file.file_name().to_string_lossy().to_lowercase();
+                    if name.ends_with(".gguf") {
+                        paths.push((gguf_model_name(&name), file.path()));
+                    }
+                }
+            }
+        }
+        paths
+    }
+}
+
+/// Normalise an LM Studio model ID or repo dir for matching:
+/// "qwen2.5-7b-instruct@q4_k_m" → "qwen2.5-7b-instruct",
+/// "lmstudio-community/Qwen2.5-7B-Instruct-GGUF" → "qwen2.5-7b-instruct".
+pub fn lm_studio_model_name(id: &str) -> String {
+    gguf_model_name(id.split('@').next().unwrap_or(id))
+}
+
+/// LM Studio's models directory: `LMSTUDIO_MODELS_DIR` if set, else the
+/// `downloadsFolder` from its settings, else `~/.lmstudio/models` (or the
+/// pre-0.3 `~/.cache/lm-studio/models` when only that exists).
+pub fn lm_studio_models_dir() -> std::path::PathBuf {
+    if let Ok(dir) = std::env::var("LMSTUDIO_MODELS_DIR") {
+        return std::path::PathBuf::from(dir);
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| "/tmp".to_string());
+    let home = std::path::PathBuf::from(home);
+    let configured = std::fs::read_to_string(home.join(".lmstudio").join("settings.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v.get("downloadsFolder")?.as_str().map(str::to_string));
+    if let Some(dir) = configured {
+        return std::path::PathBuf::from(dir);
+    }
+    let current = home.join(".lmstudio").join("models");
+    let legacy = home.join(".cache").join("lm-studio").join("models");
+    if !current.exists() && legacy.exists() {
+        legacy
+    } else {
+        current
+    }
+}
+
+impl ModelProvider for LmStudioProvider {
+    fn name(&self) -> &str {
+        "LM Studio"
+    }
+
+    fn is_available(&self) -> bool {
+        self.get_json("/v1/models").is_some()
+    }
+
+    fn installed_models(&self) -> HashSet<String> {
+        let mut set = self.local_model_names();
+        if let Some(models) = self.models() {
+            set.extend(models.iter().map(|m| lm_studio_model_name(&m.id)));
+        }
+        set
+    }
+
+    /// Downloads go through LM Studio's own `lms get`, so they land where
+    /// the app expects them.
+    fn start_pull(&self, model_tag: &str) -> Result<PullHandle, String> {
+        let tag = model_tag.to_string();
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            let _ = tx.send(PullEvent::Progress {
+                status: format!("Downloading {} with lms...", tag),
+                percent: None,
+            });
+            let result = std::process::Command::new("lms")
+                .args(["get", "--yes", &tag])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status();
+            match result {
+                Ok(status) if status.success() => {
+                    let _ = tx.send(PullEvent::Done);
+                }
+                Ok(status) => {
+                    let _ = tx.send(PullEvent::Error(format!("lms get failed ({})", status)));
+                }
+                Err(_) => {
+                    let _ = tx.send(PullEvent::Error(
+                        "lms not found. Run `lms bootstrap` from LM Studio to install it"
+                            .to_string(),
+                    ));
+                }
+            }
+        });
+
+        Ok(PullHandle {
+            model_tag: model_tag.to_string(),
+            receiver: rx,
+        })
+    }
+
+    /// Paths under the models dir, "<publisher>/<repo>" for a whole repo or
+    /// "<publisher>/<repo>/<file>.gguf" for one quant, so two publishers'
+    /// builds of the same model stay apart.
+    fn installed_tags(&self, hf_name: &str, installed: &HashSet<String>) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .local_model_paths()
+            .into_iter()
+            .filter(|(name, _)| {
+                installed.contains(name)
+                    && is_model_installed_lmstudio(hf_name, &HashSet::from([name.clone()]))
+            })
+            .filter_map(|(_, path)| self.model_tag(&path))
+            .collect();
+        tags.sort_by_key(|t| (t.len(), t.clone()));
+        tags
+    }
+
+    /// A repo tag deletes the whole dir; a GGUF tag deletes just that
+    /// quant. A bare model name works only while a single path matches it.
+    fn remove(&self, model_tag: &str) -> Result<u64, String> {
+        let path = if model_tag.contains('/') {
+            let rel = std::path::Path::new(model_tag);
+            let depth = rel.components().count();
+            let path = self.models_dir.join(rel);
+            if !(2..=3).contains(&depth)
+                || !rel
+                    .components()
+                    .all(|c| matches!(c, std::path::Component::Normal(_)))
+                || !path.exists()
+            {
+                return Err(format!("{} is not in the LM Studio models dir", model_tag));
+            }
+            path
+        } else {
+            let name = lm_studio_model_name(model_tag);
+            let mut matches: Vec<std::path::PathBuf> = Vec::new();
+            for (entry, path) in self.local_model_paths() {
+                if entry == name && !matches.iter().any(|dir| path.starts_with(dir)) {
+                    matches.push(path);
+                }
+            }
+            match matches.len() {
+                0 => return Err(format!("{} is not in the LM Studio models dir", model_tag)),
+                1 => matches.remove(0),
+                _ => {
+                    let tags: Vec<String> =
+                        matches.iter().filter_map(|p| self.model_tag(p)).collect();
+                    return Err(format!(
+                        "{} matches several models; pass one of: {}",
+                        model_tag,
+                        tags.join(", ")
+                    ));
+                }
+            }
+        };
+        remove_path(&path)
+    }
+}
+
+/// Check if an HF model is in LM Studio, as a GGUF or an MLX conversion
+/// ("qwen2.5-7b-instruct-4bit").
+pub fn is_model_installed_lmstudio(hf_name: &str, installed: &HashSet<String>) -> bool {
+    let suffixes = [GGUF_NAME_SUFFIXES, &["mlx", "3bit", "4bit", "6bit", "8bit"]].concat();
+    installed_with_suffix(hf_name, installed, &suffixes)
+}
+
+// ---------------------------------------------------------------------------
+// OpenAI-compatible endpoints (LocalAI, TabbyAPI, KoboldCpp, llama-swap, vLLM)
+// ---------------------------------------------------------------------------
+
+/// One server from the endpoints config.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct OpenAiEndpoint {
+    /// Shown in the UI in place of a provider name.
+    pub name: String,
+    /// Server root, with or without a trailing `/v1`.
+    pub base_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Environment variable holding the API key, to keep it out of the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+}
+
+impl OpenAiEndpoint {
+    fn key(&self) -> Option<String> {
+        self.api_key_env
+            .as_ref()
+            .and_then(|var| std::env::var(var).ok())
+            .or_else(|| self.api_key.clone())
+            .filter(|key| !key.is_empty())
+    }
+}
+
+/// `LLMFIT_ENDPOINTS` if set, else `<config dir>/llmfit/endpoints.json`.
+pub fn endpoints_config_path() -> Option<std::path::PathBuf> {
+    if let Some(path) = std::env::var_os("LLMFIT_ENDPOINTS").filter(|p| !p.is_empty()) {
+        return Some(std::path::PathBuf::from(path));
+    }
+    config_dir().map(|dir| dir.join("llmfit").join("endpoints.json"))
+}
+
+/// Load an endpoints config (JSON array of `OpenAiEndpoint`).
+pub fn load_endpoints(path: &std::path::Path) -> Result<Vec<OpenAiEndpoint>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let endpoints: Vec<OpenAiEndpoint> = serde_json::from_str(&text)
+        .map_err(|e| format!("invalid endpoints config {}: {e}", path.display()))?;
+    let mut names = HashSet::new();
+    for endpoint in &endpoints {
+        if endpoint.name.trim().is_empty() {
+            return Err(format!("{}: endpoint without a name", path.display()));
+        }
+        if !names.insert(endpoint.name.to_lowercase()) {
+            return Err(format!(
+                "{}: duplicate endpoint name {}",
+                path.display(),
+                endpoint.name
+            ));
+        }
+        if !endpoint.base_url.starts_with("http://") && !endpoint.base_url.starts_with("https://") {
+            return Err(format!(
+                "{}: {} base_url must start with http:// or https://",
+                path.display(),
+                endpoint.name
+            ));
+        }
+    }
+    Ok(endpoints)
+}
+
+/// A server that only speaks the OpenAI API. It can report what it serves
+/// but has no standard way to download models.
+pub struct OpenAiCompatProvider {
+    endpoint: OpenAiEndpoint,
+}
+
+impl OpenAiCompatProvider {
+    pub fn new(endpoint: OpenAiEndpoint) -> Self {
+        Self { endpoint }
+    }
+
+    /// One provider per entry in the endpoints config. No config file
+    /// means no endpoints; an unreadable one is an error.
+    pub fn from_config() -> Result<Vec<Self>, String> {
+        let Some(path) = endpoints_config_path().filter(|p| p.exists()) else {
+            return Ok(Vec::new());
+        };
+        Ok(load_endpoints(&path)?.into_iter().map(Self::new).collect())
+    }
+
+    pub fn endpoint(&self) -> &OpenAiEndpoint {
+        &self.endpoint
+    }
+
+    /// Model IDs from `/v1/models`. `None` when the server is down or
+    /// rejects the key.
+    pub fn served_models(&self) -> Option<Vec<String>> {
+        let base = self.endpoint.base_url.trim_end_matches('/');
+        let url = if base.ends_with("/v1") {
+            format!("{}/models", base)
+        } else {
+            format!("{}/v1/models", base)
+        };
+        let mut req = ureq::get(&url).timeout(std::time::Duration::from_secs(2));
+        if let Some(key) = self.endpoint.key() {
+            req = req.set("Authorization", &format!("Bearer {}", key));
+        }
+        let json: serde_json::Value = req.call().ok()?.into_json().ok()?;
+        Some(
+            json.get("data")?
+                .as_array()?
+                .iter()
+                .filter_map(|m| m.get("id")?.as_str().map(str::to_string))
+                .collect(),
+        )
+    }
+}
+
+impl ModelProvider for OpenAiCompatProvider {
+    fn name(&self) -> &str {
+        &self.endpoint.name
+    }
+
+    fn is_available(&self) -> bool {
+        self.served_models().is_some()
+    }
+
+    fn installed_models(&self) -> HashSet<String> {
+        served_model_names(&self.served_models().unwrap_or_default())
+    }
+
+    fn start_pull(&self, _model_tag: &str) -> Result<PullHandle, String> {
+        Err(format!(
+            "{} has no pull API; add the model on the server",
+            self.endpoint.name
+        ))
+    }
+
+    /// Nothing here is removable, so `installed_tags` stays empty.
+    fn remove(&self, _model_tag: &str) -> Result<u64, String> {
+        Err(format!(
+            "{} has no delete API; remove the model on the server",
+            self.endpoint.name
+        ))
+    }
+}
+
+/// Normalised names for the IDs an endpoint's `served_models` returns.
+pub fn served_model_names(ids: &[String]) -> HashSet<String> {
+    ids.iter().map(|id| lm_studio_model_name(id)).collect()
+}
+
+/// Check if an HF model is served by an OpenAI-compatible endpoint. IDs
+/// are whatever the server uses: HF repos (vLLM), GGUF files (LocalAI,
+/// KoboldCpp), or quantized dirs (TabbyAPI's EXL2).
+pub fn is_model_served(hf_name: &str, served: &HashSet<String>) -> bool {
+    let suffixes = [
+        GGUF_NAME_SUFFIXES,
+        &["mlx", "3bit", "4bit", "6bit", "8bit"],
+        &["awq", "gptq", "fp8", "exl2", "int4", "int8"],
+    ]
+    .concat();
+    installed_with_suffix(hf_name, served, &suffixes)
+}
+
+// ---------------------------------------------------------------------------
+// Download storage
+// ---------------------------------------------------------------------------
+
+/// Directory Ollama stores model blobs in: `OLLAMA_MODELS` if set, else
+/// `~/.ollama/models`, else the Linux service install's
+/// `/usr/share/ollama/.ollama/models`.
+pub fn ollama_models_dir() -> std::path::PathBuf {
+    if let Ok(dir) = std::env::var("OLLAMA_MODELS") {
+        return std::path::PathBuf::from(dir);
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map(|h| std::path::PathBuf::from(h).join(".ollama").join("models"));
+    let service = std::path::Path::new("/usr/share/ollama/.ollama/models");
+    match home {
+        Ok(dir) if dir.exists() || !service.exists() => dir,
+        _ => service.to_path_buf(),
+    }
+}
+
+/// Directory the provider for `runtime` writes pulled models into.
+pub fn download_dir(runtime: InferenceRuntime) -> std::path::PathBuf {
+    match runtime {
+        // OpenVINO/ONNX NPU builds and vLLM checkpoints come from Hugging Face too.
+        InferenceRuntime::Mlx | InferenceRuntime::Npu | InferenceRuntime::Vllm => hf_cache_dir(),
+        InferenceRuntime::LlamaCpp => ollama_models_dir(),
+    }
+}
+
+/// Probe free space in each runtime's download dir once and set
+/// `disk_fit` on every fit.
+pub fn apply_disk_fit(fits: &mut [ModelFit]) {
+    apply_disk_fit_with(fits, |fit| Some(download_dir(fit.runtime)));
+}
+
+/// Like [`apply_disk_fit`], against the dir `dir_for` says a fit's pull
+/// writes to (`None`: it can't be pulled). Each dir is probed once.
+pub fn apply_disk_fit_with(
+    fits: &mut [ModelFit],
+    dir_for: impl Fn(&ModelFit) -> Option<std::path::PathBuf>,
+) {
+    let mut probed: Vec<(std::path::PathBuf, Option<f64>)> = Vec::new();
+    for fit in fits {
+        let free_gb = dir_for(fit).and_then(|dir| {
+            if let Some((_, free)) = probed.iter().find(|(d, _)| *d == dir) {
+                return *free;
+            }
+            let free = free_disk_gb(&dir);
+            probed.push((dir, free));
+            free
+        });
+        fit.set_disk_free(free_gb);
+    }
+}
+
+/// Delete a file or a whole directory, returning the bytes it held.
+fn remove_path(path: &std::path::Path) -> Result<u64, String> {
+    let size = disk_usage(path);
+    let result = if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    };
+    result
+        .map(|()| size)
+        .map_err(|e| format!("failed to remove {}: {}", path.display(), e))
+}
+
+/// Bytes under `path`. Symlinks aren't followed, so a Hugging Face
+/// snapshot's links into `blobs/` aren't counted twice.
+fn disk_usage(path: &std::path::Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return if meta.is_file() { meta.len() } else { 0 };
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| disk_usage(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Entries of an installed set that are builds of `hf_name` by
+/// `is_installed` (one of the `is_model_installed_*` checks), shortest
+/// first.
+fn matching_tags(
+    hf_name: &str,
+    installed: &HashSet<String>,
+    is_installed: fn(&str, &HashSet<String>) -> bool,
+) -> Vec<String> {
+    let mut tags: Vec<String> = installed
+        .iter()
+        .filter(|name| is_installed(hf_name, &HashSet::from([(*name).clone()])))
+        .cloned()
+        .collect();
+    tags.sort_by_key(|t| (t.len(), t.clone()));
+    tags
+}
+
+// ---------------------------------------------------------------------------
+// Ollama name-matching helpers
+// ---------------------------------------------------------------------------
+
+/// Authoritative mapping from HF repo name (lowercased, after slash) to Ollama tag.
+/// Only models with a known Ollama registry entry are listed here.
+/// If a model is not in this table, it cannot be pulled from Ollama.
+const OLLAMA_MAPPINGS: &[(&str, &str)] = &[
+    // Meta Llama family
+    ("llama-3.3-70b-instruct", "llama3.3:70b"),
+    ("llama-3.2-11b-vision-instruct", "llama3.2-vision:11b"),
+    ("llama-3.2-3b-instruct", "llama3.2:3b"),
+    ("llama-3.2-3b", "llama3.2:3b"),
+    ("llama-3.2-1b-instruct", "llama3.2:1b"),
+    ("llama-3.2-1b", "llama3.2:1b"),
+    ("llama-3.1-405b-instruct", "llama3.1:405b"),
+    ("llama-3.1-405b", "llama3.1:405b"),
+    ("llama-3.1-70b-instruct", "llama3.1:70b"),
+    ("llama-3.1-8b-instruct", "llama3.1:8b"),
+    ("llama-3.1-8b", "llama3.1:8b"),
+    ("meta-llama-3-8b-instruct", "llama3:8b"),
+    ("meta-llama-3-8b", "llama3:8b"),
+    ("llama-2-7b-hf", "llama2:7b"),
+    ("codellama-34b-instruct-hf", "codellama:34b"),
+    ("codellama-13b-instruct-hf", "codellama:13b"),
+    ("codellama-7b-instruct-hf", "codellama:7b"),
+    // Google Gemma
+    ("gemma-3-12b-it", "gemma3:12b"),
+    ("gemma-2-27b-it", "gemma2:27b"),
+    ("gemma-2-9b-it", "gemma2:9b"),
+    ("gemma-2-2b-it", "gemma2:2b"),
+    // Microsoft Phi
+    ("phi-4", "phi4"),
+    ("phi-4-mini-instruct", "phi4-mini"),
+    ("phi-3.5-mini-instruct", "phi3.5"),
+    ("phi-3-mini-4k-instruct", "phi3"),
+    ("phi-3-medium-14b-instruct", "phi3:14b"),
+    ("phi-2", "phi"),
+    ("orca-2-7b", "orca2:7b"),
+    ("orca-2-13b", "orca2:13b"),
+    // Mistral
+    ("mistral-7b-instruct-v0.3", "mistral:7b"),
+    ("mistral-7b-instruct-v0.2", "mistral:7b"),
+    ("mistral-nemo-instruct-2407", "mistral-nemo"),
+    ("mistral-small-24b-instruct-2501", "mistral-small:24b"),
+    ("mistral-large-instruct-2407", "mistral-large"),
+    ("mixtral-8x7b-instruct-v0.1", "mixtral:8x7b"),
+    ("mixtral-8x22b-instruct-v0.1", "mixtral:8x22b"),
+    // Qwen 2 / 2.5
+    ("qwen2-1.5b-instruct", "qwen2:1.5b"),
+    ("qwen2.5-72b-instruct", "qwen2.5:72b"),
+    ("qwen2.5-32b-instruct", "qwen2.5:32b"),
+    ("qwen2.5-14b-instruct", "qwen2.5:14b"),
+    ("qwen2.5-7b-instruct", "qwen2.5:7b"),
+    ("qwen2.5-7b", "qwen2.5:7b"),
+    ("qwen2.5-3b-instruct", "qwen2.5:3b"),
+    ("qwen2.5-1.5b-instruct", "qwen2.5:1.5b"),
+    ("qwen2.5-1.5b", "qwen2.5:1.5b"),
+    ("qwen2.5-0.5b-instruct", "qwen2.5:0.5b"),
+    ("qwen2.5-0.5b", "qwen2.5:0.5b"),
+    ("qwen2.5-coder-32b-instruct", "qwen2.5-coder:32b"),
+    ("qwen2.5-coder-14b-instruct", "qwen2.5-coder:14b"),
+    ("qwen2.5-coder-7b-instruct", "qwen2.5-coder:7b"),
+    ("qwen2.5-coder-1.5b-instruct", "qwen2.5-coder:1.5b"),
+    ("qwen2.5-coder-0.5b-instruct", "qwen2.5-coder:0.5b"),
+    ("qwen2.5-vl-7b-instruct", "qwen2.5vl:7b"),
+    ("qwen2.5-vl-3b-instruct", "qwen2.5vl:3b"),
+    // Qwen 3
+    ("qwen3-235b-a22b", "qwen3:235b"),
+    ("qwen3-32b", "qwen3:32b"),
+    ("qwen3-30b-a3b", "qwen3:30b-a3b"),
+    ("qwen3-30b-a3b-instruct-2507", "qwen3:30b-a3b"),
+    ("qwen3-14b", "qwen3:14b"),
+    ("qwen3-8b", "qwen3:8b"),
+    ("qwen3-4b", "qwen3:4b"),
+    ("qwen3-4b-instruct-2507", "qwen3:4b"),
+    ("qwen3-1.7b-base", "qwen3:1.7b"),
+    ("qwen3-0.6b", "qwen3:0.6b"),
+    ("qwen3-coder-30b-a3b-instruct", "qwen3-coder"),
+    // DeepSeek
+    ("deepseek-v3", "deepseek-v3"),
+    ("deepseek-v3.2", "deepseek-v3"),
+    ("deepseek-r1", "deepseek-r1"),
+    ("deepseek-r1-0528", "deepseek-r1"),
+    ("deepseek-r1-distill-qwen-32b", "deepseek-r1:32b"),
+    ("deepseek-r1-distill-qwen-14b", "deepseek-r1:14b"),
+    ("deepseek-r1-distill-qwen-7b", "deepseek-r1:7b"),
+    ("deepseek-coder-v2-lite-instruct", "deepseek-coder-v2:16b"),
+    // Community / other
+    ("tinyllama-1.1b-chat-v1.0", "tinyllama"),
+    ("stablelm-2-1_6b-chat", "stablelm2:1.6b"),
+    ("yi-6b-chat", "yi:6b"),
+    ("yi-34b-chat", "yi:34b"),
+    ("starcoder2-7b", "starcoder2:7b"),
+    ("starcoder2-15b", "starcoder2:15b"),
+    ("falcon-7b-instruct", "falcon:7b"),
+    ("falcon-40b-instruct", "falcon:40b"),
+    ("falcon-180b-chat", "falcon:180b"),
+    ("falcon3-7b-instruct", "falcon3:7b"),
+    ("openchat-3.5-0106", "openchat:7b"),
+    ("vicuna-7b-v1.5", "vicuna:7b"),
+    ("vicuna-13b-v1.5", "vicuna:13b"),
+    ("glm-4-9b-chat", "glm4:9b"),
+    ("solar-10.7b-instruct-v1.0", "solar:10.7b"),
+    ("zephyr-7b-beta", "zephyr:7b"),
+    ("c4ai-command-r-v01", "command-r"),
+    (
+        "nous-hermes-2-mixtral-8x7b-dpo",
+        "nous-hermes2-mixtral:8x7b",
+    ),
+    ("hermes-3-llama-3.1-8b", "hermes3:8b"),
+    ("nomic-embed-text-v1.5", "nomic-embed-text"),
+    ("bge-large-en-v1.5", "bge-large"),
+    ("smollm2-135m-instruct", "smollm2:135m"),
+    ("smollm2-135m", "smollm2:135m"),
+];
+
+/// Look up the Ollama tag for an HF repo name. Returns the first match
+/// from `OLLAMA_MAPPINGS`, or `None` if the model has no known Ollama equivalent.
+fn lookup_ollama_tag(hf_name: &str) -> Option<&'static str> {
+    let repo = hf_name
+        .split('/')
+        .next_back()
+        .unwrap_or(hf_name)
+        .to_lowercase();
+    OLLAMA_MAPPINGS
+        .iter()
+        .find(|&&(hf_suffix, _)| repo == hf_suffix)
+        .map(|&(_, tag)| tag)
+}
+
+/// Map a HuggingFace model name to Ollama candidate tags for install checking.
+/// Returns candidates from the authoritative mapping table only.
+pub fn hf_name_to_ollama_candidates(hf_name: &str) -> Vec<String> {
+    match lookup_ollama_tag(hf_name) {
+        Some(tag) => vec![tag.to_string()],
+        None => vec![],
+    }
+}
+
+/// Returns `true` if this HF model has a known Ollama registry entry
+/// and can be pulled.
+pub fn has_ollama_mapping(hf_name: &str) -> bool {
+    lookup_ollama_tag(hf_name).is_some()
+}
+
+/// Check if any of the Ollama candidates for an HF model appear in the
+/// installed set.
+pub fn is_model_installed(hf_name: &str, installed: &HashSet<String>) -> bool {
+    let candidates = hf_name_to_ollama_candidates(hf_name);
+    candidates.iter().any(|c| installed.contains(c))
+}
+
+/// Given an HF model name, return the Ollama tag to use for pulling.
+/// Returns `None` if the model has no known Ollama mapping.
+pub fn ollama_pull_tag(hf_name: &str) -> Option<String> {
+    lookup_ollama_tag(hf_name).map(|s| s.to_string())
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::{BufRead, BufReader, Write};
+
+    /// Serve canned `(path, status, body)` responses on a local port until
+    /// the test exits. Paths match without their query string.
+    fn stub_server(routes: Vec<(&'static str, u16, String)>) -> String {
+        stub_server_logged(routes).0
+    }
+
+    /// `stub_server` that also records each request's line, headers and
+    /// body as one string.
+    fn stub_server_logged(
+        routes: Vec<(&'static str, u16, String)>,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = log.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                let _ = reader.read_line(&mut request_line);
+                let mut content_length = 0;
+                let mut head = request_line.clone();
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap_or(0) <= 2 {
+                        break;
+                    }
+                    head.push_str(&header);
+                    if let Some((name, value)) = header.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+                let mut body = vec![0u8; content_length];
+                let _ = std::io::Read::read_exact(&mut reader, &mut body);
+                head.push_str(&String::from_utf8_lossy(&body));
+                requests.lock().unwrap().push(head);
+
+                let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+                let path = target.split('?').next().unwrap_or(target);
+                let (status, body) = routes
+                    .iter()
+                    .find(|(p, _, _)| *p == path)
+                    .map(|(_, status, body)| (*status, body.as_str()))
+                    .unwrap_or((404, "not found"));
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        (url, log)
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("llmfit-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Wait for a pull to finish, returning the error if it failed.
+    fn finish_pull(handle: PullHandle) -> Result<(), String> {
+        loop {
+            match handle
+                .receiver
+                .recv_timeout(std::time::Duration::from_secs(10))
+            {
+                Ok(PullEvent::Progress { .. }) => continue,
+                Ok(PullEvent::Done) => return Ok(()),
+                Ok(PullEvent::Error(e)) => return Err(e),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_llama_server_status_and_installed_models() {
+        let url = stub_server(vec![
+            ("/health", 200, r#"{"status":"ok"}"#.to_string()),
+            (
+                "/v1/models",
+                200,
+                r#"{"object":"list","data":[{"id":"/models/Qwen2.5-7B-Instruct-Q4_K_M.gguf","object":"model"}]}"#
+                    .to_string(),
+            ),
+            (
+                "/props",
+                200,
+                r#"{"default_generation_settings":{"n_ctx":8192},"total_slots":4}"#.to_string(),
+            ),
+        ]);
+        let dir = temp_dir("llama-cache");
+        std::fs::write(
+            dir.join("bartowski_Llama-3.2-3B-Instruct-GGUF_Llama-3.2-3B-Instruct-Q4_K_M.gguf"),
+            b"GGUF",
+        )
+        .unwrap();
+        let provider = LlamaServerProvider::with_config(&url, &url, dir.clone());
+
+        assert!(provider.is_available());
+        let status = provider.status().expect("server is up");
+        assert_eq!(status.n_ctx, Some(8192));
+        assert_eq!(status.total_slots, Some(4));
+        assert_eq!(status.models.len(), 1);
+
+        let installed = provider.installed_models();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(installed.contains("qwen2.5-7b-instruct-q4_k_m"));
+        assert!(is_model_installed_gguf(
+            "Qwen/Qwen2.5-7B-Instruct",
+            &installed
+        ));
+        assert!(is_model_installed_gguf(
+            "meta-llama/Llama-3.2-3B-Instruct",
+            &installed
+        ));
+        assert!(!is_model_installed_gguf(
+            "Qwen/Qwen2.5-Coder-7B-Instruct",
+            &installed
+        ));
+        assert!(!is_model_installed_gguf(
+            "meta-llama/Llama-3.2-3B",
+            &installed
+        ));
+    }
+
+    #[test]
+    fn test_llama_server_availability() {
+        let loading = stub_server(vec![(
+            "/health",
+            503,
+            r#"{"error":{"message":"Loading model"}}"#.to_string(),
+        )]);
+        let dir = std::env::temp_dir();
+        assert!(LlamaServerProvider::with_config(&loading, &loading, dir.clone()).is_available());
+
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let provider = LlamaServerProvider::with_config(&closed, &closed, dir);
+        assert!(!provider.is_available());
+        assert_eq!(provider.status(), None);
+    }
+
+    #[test]
+    fn test_llama_server_pull_downloads_gguf() {
+        let hub = stub_server(vec![
+            (
+                "/api/models/bartowski/Tiny-GGUF",
+                200,
+                r#"{"siblings":[{"rfilename":"README.md"},{"rfilename":"Tiny-Q8_0.gguf"},{"rfilename":"Tiny-Q4_K_M.gguf"}]}"#
+                    .to_string(),
+            ),
+            (
+                "/bartowski/Tiny-GGUF/resolve/main/Tiny-Q4_K_M.gguf",
+                200,
+                "GGUF-weights".to_string(),
+            ),
+        ]);
+        let dir = temp_dir("llama-pull");
+        let provider = LlamaServerProvider::with_config(&hub, &hub, dir.clone());
+
+        let handle = provider.start_pull("bartowski/Tiny-GGUF:Q4_K_M").unwrap();
+        assert_eq!(finish_pull(handle), Ok(()));
+        let path = dir.join("bartowski_Tiny-GGUF_Tiny-Q4_K_M.gguf");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "GGUF-weights");
+
+        let handle = provider.start_pull("bartowski/Tiny-GGUF:Q2_K").unwrap();
+        assert_eq!(
+            finish_pull(handle),
+            Err("no Q2_K GGUF in bartowski/Tiny-GGUF".to_string())
+        );
+        assert!(provider.start_pull("Tiny").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let split = [
+            "Big-Q4_K_M/Big-Q4_K_M-00002-of-00002.gguf".to_string(),
+            "Big-Q4_K_M/Big-Q4_K_M-00001-of-00002.gguf".to_string(),
+            "Big-Q8_0/Big-Q8_0-00001-of-00003.gguf".to_string(),
+        ];
+        assert_eq!(
+            pick_gguf_files(&split, "q4_k_m"),
+            Ok(vec![
+                "Big-Q4_K_M/Big-Q4_K_M-00001-of-00002.gguf".to_string(),
+                "Big-Q4_K_M/Big-Q4_K_M-00002-of-00002.gguf".to_string(),
+            ])
+        );
+        assert!(pick_gguf_files(&split, "q8_0").is_err());
+    }
+
+    #[test]
+    fn test_llama_server_pull_downloads_split_gguf() {
+        let hub = stub_server(vec![
+            (
+                "/api/models/bartowski/Big-GGUF",
+                200,
+                r#"{"siblings":[{"rfilename":"Big-Q4_K_M/Big-Q4_K_M-00001-of-00002.gguf"},{"rfilename":"Big-Q4_K_M/Big-Q4_K_M-00002-of-00002.gguf"}]}"#
+                    .to_string(),
+            ),
+            (
+                "/bartowski/Big-GGUF/resolve/main/Big-Q4_K_M/Big-Q4_K_M-00001-of-00002.gguf",
+                200,
+                "part-one".to_string(),
+            ),
+        ]);
+        let dir = temp_dir("llama-pull-split");
+        let provider = LlamaServerProvider::with_config(&hub, &hub, dir.clone());
+
+        // The second part 404s: nothing, not even a .part file, is left.
+        let handle = provider.start_pull("bartowski/Big-GGUF:Q4_K_M").unwrap();
+        assert!(finish_pull(handle).is_err());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        assert!(provider.installed_models().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_lm_studio_models_and_installed() {
+        let url = stub_server(vec![
+            (
+                "/api/v0/models",
+                200,
+                r#"{"object":"list","data":[
+                    {"id":"qwen2.5-7b-instruct@q4_k_m","type":"llm","compatibility_type":"gguf","quantization":"Q4_K_M","state":"loaded","max_context_length":32768},
+                    {"id":"text-embedding-nomic-embed-text-v1.5","type":"embeddings","compatibility_type":"gguf","state":"not-loaded"}
+                ]}"#
+                    .to_string(),
+            ),
+            (
+                "/v1/models",
+                200,
+                r#"{"data":[{"id":"qwen2.5-7b-instruct@q4_k_m"},{"id":"phi-4"}]}"#.to_string(),
+            ),
+        ]);
+        let dir = temp_dir("lmstudio");
+        let gguf = dir
+            .join("lmstudio-community")
+            .join("Llama-3.2-3B-Instruct-GGUF");
+        std::fs::create_dir_all(&gguf).unwrap();
+        std::fs::write(gguf.join("Llama-3.2-3B-Instruct-Q4_K_M.gguf"), b"GGUF").unwrap();
+        let mlx = dir.join("mlx-community").join("gemma-2-9b-it-4bit");
+        std::fs::create_dir_all(&mlx).unwrap();
+        let provider = LmStudioProvider::with_config(&url, dir.clone());
+
+        assert!(provider.is_available());
+        let models = provider.models().expect("server is up");
+        assert_eq!(models.len(), 3);
+        assert!(models[0].loaded);
+        assert_eq!(models[0].quantization.as_deref(), Some("Q4_K_M"));
+        assert_eq!(models[0].max_context_length, Some(32768));
+        assert!(!models[1].loaded);
+        assert_eq!(models[2].id, "phi-4");
+
+        let installed = provider.installed_models();
+        std::fs::remove_dir_all(&dir).unwrap();
+        for hf_name in [
+            "Qwen/Qwen2.5-7B-Instruct",
+            "meta-llama/Llama-3.2-3B-Instruct",
+            "google/gemma-2-9b-it",
+            "microsoft/phi-4",
+        ] {
+            assert!(
+                is_model_installed_lmstudio(hf_name, &installed),
+                "{}",
+                hf_name
+            );
+        }
+        assert!(!is_model_installed_lmstudio(
+            "Qwen/Qwen2.5-Coder-7B-Instruct",
+            &installed
+        ));
+        assert!(!is_model_installed_lmstudio(
+            "meta-llama/Llama-3.2-3B",
+            &installed
+        ));
+
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let offline = LmStudioProvider::with_config(&closed, std::env::temp_dir());
+        assert!(!offline.is_available());
+        assert_eq!(offline.models(), None);
+    }
+
+    #[test]
+    fn test_openai_endpoints_from_config() {
+        let (tabby, requests) = stub_server_logged(vec![(
+            "/v1/models",
+            200,
+            r#"{"object":"list","data":[{"id":"Qwen2.5-7B-Instruct-exl2-4.0bpw","object":"model"}]}"#
+                .to_string(),
+        )]);
+        let vllm = stub_server(vec![(
+            "/v1/models",
+            200,
+            r#"{"data":[{"id":"meta-llama/Llama-3.1-8B-Instruct"}]}"#.to_string(),
+        )]);
+        let dir = temp_dir("endpoints");
+        let path = dir.join("endpoints.json");
+        std::fs::write(
+            &path,
+            format!(
+                r#"[
+                    {{"name": "TabbyAPI", "base_url": "{}", "api_key": "secret"}},
+                    {{"name": "vLLM box", "base_url": "{}/v1/"}},
+                    {{"name": "KoboldCpp", "base_url": "http://127.0.0.1:1"}}
+                ]"#,
+                tabby, vllm
+            ),
+        )
+        .unwrap();
+        let providers: Vec<OpenAiCompatProvider> = load_endpoints(&path)
+            .unwrap()
+            .into_iter()
+            .map(OpenAiCompatProvider::new)
+            .collect();
+        assert_eq!(providers.len(), 3);
+        assert_eq!(providers[0].name(), "TabbyAPI");
+
+        let tabby_models = providers[0].installed_models();
+        assert!(
+            requests.lock().unwrap()[0]
+                .to_lowercase()
+                .contains("authorization: bearer secret")
+        );
+        assert!(is_model_served("Qwen/Qwen2.5-7B-Instruct", &tabby_models));
+        assert!(!is_model_served(
+            "Qwen/Qwen2.5-Coder-7B-Instruct",
+            &tabby_models
+        ));
+
+        assert!(providers[1].is_available());
+        let vllm_models = providers[1].installed_models();
+        assert!(is_model_served(
+            "meta-llama/Llama-3.1-8B-Instruct",
+            &vllm_models
+        ));
+        assert!(!is_model_served("meta-llama/Llama-3.1-8B", &vllm_models));
+
+        assert!(!providers[2].is_available());
+        assert!(providers[2].start_pull("anything").is_err());
+
+        std::fs::write(
+            &path,
+            r#"[{"name": "a", "base_url": "http://x"}, {"name": "A", "base_url": "http://y"}]"#,
+        )
+        .unwrap();
+        assert!(load_endpoints(&path).unwrap_err().contains("duplicate"));
+        std::fs::write(&path, r#"[{"name": "a", "base_url": "localhost:8000"}]"#).unwrap();
+        assert!(load_endpoints(&path).unwrap_err().contains("http://"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_ollama_details_refit_installed_models() {
+        let (url, requests) = stub_server_logged(vec![
+            (
+                "/api/tags",
+                200,
+                r#"{"models":[
+                    {"name":"qwen2.5-coder:14b","size":8988124069,"details":{"family":"qwen2","parameter_size":"14.8B","quantization_level":"Q4_K_M"}},
+                    {"name":"mystery:3b","size":2019393189,"details":{"family":"llama","parameter_size":"3.2B","quantization_level":"Q4_K_M"}}
+                ]}"#
+                    .to_string(),
+            ),
+            (
+                "/api/show",
+                200,
+                r#"{"details":{"format":"gguf","quantization_level":"Q4_K_M"},
+                    "model_info":{"general.architecture":"llama","general.parameter_count":3212749888,"llama.context_length":131072},
+                    "capabilities":["completion","tools"]}"#
+                    .to_string(),
+            ),
+        ]);
+        let details = OllamaProvider::with_base_url(&url).installed_details();
+        assert_eq!(details.len(), 2);
+        let mystery = &details[1];
+        assert_eq!(mystery.parameter_count, Some(3212749888));
+        assert_eq!(mystery.parameter_size.as_deref(), Some("3.2B"));
+        assert_eq!(mystery.context_length, Some(131072));
+        assert_eq!(mystery.architecture.as_deref(), Some("llama"));
+        assert!(
+            requests
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|r| r.starts_with("POST /api/show") && r.contains(r#""mystery:3b""#))
+        );
+
+        let system = SystemSpecs::from_json(&serde_json::json!({
+            "total_ram_gb": 64.0, "available_ram_gb": 48.0, "cpu_cores": 16,
+            "backend": "CUDA", "has_gpu": true, "gpu_vram_gb": 24.0, "gpu_count": 1
+        }))
+        .unwrap();
+        let db = crate::models::ModelDatabase::new();
+        let coder = db
+            .get_all_models()
+            .iter()
+            .find(|m| m.name == "Qwen/Qwen2.5-Coder-14B-Instruct")
+            .unwrap();
+        let mut fits = vec![ModelFit::analyze(coder, &system)];
+        assert_ne!(fits[0].best_quant, "Q4_K_M");
+
+        apply_ollama_installed(&mut fits, &details, &system);
+        apply_ollama_installed(&mut fits, &details, &system);
+        assert_eq!(fits.len(), 2);
+        assert!(fits.iter().all(|f| f.installed));
+        assert_eq!(fits[0].best_quant, "Q4_K_M");
+        assert_eq!(fits[0].runtime, InferenceRuntime::LlamaCpp);
+        assert_eq!(fits[1].model.name, "mystery:3b");
+        assert_eq!(fits[1].model.provider, "Ollama");
+        assert_eq!(fits[1].model.context_length, 131072);
+        assert!((fits[1].model.min_ram_gb - 2.26).abs() < 0.01);
+
+        let mut embed = OllamaModelInfo {
+            name: "nomic-embed-text:latest".to_string(),
+            ..Default::default()
+        };
+        embed.apply_show(&serde_json::json!({
+            "details": {"family": "nomic-bert", "parameter_size": "137M", "quantization_level": "F16"},
+            "model_info": {"general.architecture": "nomic-bert", "nomic-bert.context_length": 2048},
+            "capabilities": ["embedding"]
+        }));
+        let model = embed.to_llm_model();
+        assert_eq!(
+            crate::models::UseCase::from_model(&model),
+            crate::models::UseCase::Embedding
+        );
+        assert_eq!(model.quantization, "F16");
+        assert_eq!(model.context_length, 2048);
+    }
+
+    #[test]
+    fn test_ollama_loaded_models_residency() {
+        let url = stub_server(vec![(
+            "/api/ps",
+            200,
+            r#"{"models":[
+                {"name":"qwen2.5-coder:14b","size":12884901888,"size_vram":9663676416,"context_length":8192,"expires_at":"2026-10-18T17:05:00Z"},
+                {"name":"mystery:3b","size":3221225472,"size_vram":3221225472}
+            ]}"#
+            .to_string(),
+        )]);
+        let loaded = OllamaProvider::with_base_url(&url).loaded_models().unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].size_gb(), 12.0);
+        assert_eq!(loaded[0].vram_gb(), 9.0);
+        assert_eq!(loaded[0].ram_gb(), 3.0);
+        assert_eq!(loaded[0].gpu_percent(), 75.0);
+        assert_eq!(loaded[0].context_length, Some(8192));
+        assert_eq!(loaded[1].gpu_percent(), 100.0);
+        assert_eq!(loaded[1].context_length, None);
+
+        assert!(
+            OllamaProvider::with_base_url("http://127.0.0.1:9")
+                .loaded_models()
+                .is_none()
+        );
+
+        let details = vec![OllamaModelInfo {
+            name: "mystery:3b".to_string(),
+            ..Default::default()
+        }];
+        let system = SystemSpecs::from_json(&serde_json::json!({
+            "total_ram_gb": 64.0, "available_ram_gb": 48.0, "cpu_cores": 16,
+            "backend": "CUDA", "has_gpu": true, "gpu_vram_gb": 24.0, "gpu_count": 1
+        }))
+        .unwrap();
+        let mut fits = Vec::new();
+        apply_ollama_installed(&mut fits, &details, &system);
+        let fit = fit_for_ollama_tag(&fits, &details, "mystery:3b").unwrap();
+        assert_eq!(fit.model.name, "mystery:3b");
+        assert!(fit_for_ollama_tag(&fits, &details, "qwen2.5-coder:14b").is_none());
+
+        let reserved = system.clone().with_reserved_vram(loaded[0].vram_gb());
+        assert_eq!(reserved.gpu_vram_gb, Some(15.0));
+        let mut pair = system;
+        pair.gpu_count = 2;
+        let reserved = pair.with_reserved_vram(loaded[0].vram_gb());
+        assert_eq!(reserved.gpu_vram_gb, Some(19.5));
+    }
+
+    #[test]
+    fn test_remove_installed_models() {
+        let (url, requests) = stub_server_logged(vec![
+            (
+                "/api/tags",
+                200,
+                r#"{"models":[{"name":"llama3.1:8b","size":4920753328,"details":{}}]}"#.to_string(),
+            ),
+            ("/api/delete", 200, String::new()),
+        ]);
+        let ollama = OllamaProvider::with_base_url(&url);
+        assert_eq!(ollama.remove("llama3.1:8b"), Ok(4920753328));
+        assert!(
+            requests
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|r| r.starts_with("DELETE /api/delete") && r.contains(r#""llama3.1:8b""#))
+        );
+        let missing = stub_server(vec![("/api/tags", 200, r#"{"models":[]}"#.to_string())]);
+        assert!(
+            OllamaProvider::with_base_url(&missing)
+                .remove("llama3.1:8b")
+                .is_err()
+        );
+
+        let installed = HashSet::from(["llama3.1:8b".to_string(), "llama3.1".to_string()]);
+        assert_eq!(
+            ollama.installed_tags("meta-llama/Llama-3.1-8B-Instruct", &installed),
+            vec!["llama3.1:8b".to_string()]
+        );
+
+        let dir = temp_dir("remove");
+        let cache = dir.join("llama.cpp");
+        std::fs::create_dir_all(&cache).unwrap();
+        std::fs::write(cache.join("Llama-3.2-3B-Instruct-Q4_K_M.gguf"), b"GGUF0123").unwrap();
+        std::fs::write(cache.join("Llama-3.2-3B-Instruct-Q8_0.gguf"), b"GGUF").unwrap();
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let llamacpp = LlamaServerProvider::with_config(&closed, &closed, cache.clone());
+        let tags = llamacpp.installed_tags(
+            "meta-llama/Llama-3.2-3B-Instruct",
+            &llamacpp.installed_models(),
+        );
+        assert_eq!(
+            tags,
+            vec![
+                "llama-3.2-3b-instruct-q8_0".to_string(),
+                "llama-3.2-3b-instruct-q4_k_m".to_string()
+            ]
+        );
+        assert_eq!(llamacpp.remove(&tags[1]), Ok(8));
+        assert!(llamacpp.remove(&tags[1]).is_err());
+        assert_eq!(llamacpp.installed_models().len(), 1);
+
+        // The parts of a split GGUF are one model, removed together.
+        for part in 1..=2 {
+            let file = format!("Big-Model-Q4_K_M-{:05}-of-00002.gguf", part);
+            std::fs::write(cache.join(file), b"GGUF").unwrap();
+        }
+        let tags = llamacpp.installed_tags("someone/Big-Model", &llamacpp.installed_models());
+        assert_eq!(tags, vec!["big-model-q4_k_m".to_string()]);
+        assert_eq!(llamacpp.remove(&tags[0]), Ok(8));
+        assert_eq!(llamacpp.installed_models().len(), 1);
+
+        let lmstudio_dir = dir.join("lmstudio");
+        let repo = lmstudio_dir
+            .join("lmstudio-community")
+            .join("Qwen2.5-7B-Instruct-GGUF");
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(repo.join("Qwen2.5-7B-Instruct-Q4_K_M.gguf"), b"GGUF01").unwrap();
+        std::fs::write(repo.join("Qwen2.5-7B-Instruct-Q8_0.gguf"), b"GGUF0123").unwrap();
+        let other = lmstudio_dir
+            .join("bartowski")
+            .join("Qwen2.5-7B-Instruct-GGUF");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join("Qwen2.5-7B-Instruct-Q6_K.gguf"), b"GGUF012").unwrap();
+        let lmstudio = LmStudioProvider::with_config(&closed, lmstudio_dir.clone());
+        let tags =
+            lmstudio.installed_tags("Qwen/Qwen2.5-7B-Instruct", &lmstudio.installed_models());
+        assert_eq!(
+            &tags[..2],
+            [
+                "bartowski/Qwen2.5-7B-Instruct-GGUF".to_string(),
+                "lmstudio-community/Qwen2.5-7B-Instruct-GGUF".to_string()
+            ]
+        );
+        assert_eq!(
+            lmstudio.remove(
+                "lmstudio-community/Qwen2.5-7B-Instruct-GGUF/Qwen2.5-7B-Instruct-Q8_0.gguf"
+            ),
+            Ok(8)
+        );
+        assert!(repo.exists());
+        // Both publishers ship the repo: a bare name is ambiguous.
+        assert!(lmstudio.remove("qwen2.5-7b-instruct").is_err());
+        assert!(lmstudio.remove("../lmstudio/bartowski").is_err());
+        assert_eq!(lmstudio.remove(&tags[1]), Ok(6));
+        assert!(!repo.exists());
+        assert!(other.exists());
+        assert_eq!(lmstudio.remove("qwen2.5-7b-instruct"), Ok(7));
+        assert!(lmstudio.remove("qwen2.5-7b-instruct").is_err());
+
+        let hf = dir.join("hub");
+        let snapshot = hf
+            .join("models--mlx-community--Llama-3.2-3B-Instruct-4bit")
+            .join("snapshots")
+            .join("abc123");
+        let blobs = hf
+            .join("models--mlx-community--Llama-3.2-3B-Instruct-4bit")
+            .join("blobs");
+        std::fs::create_dir_all(&snapshot).unwrap();
+        std::fs::create_dir_all(&blobs).unwrap();
+        std::fs::write(blobs.join("f00d"), b"weights").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(blobs.join("f00d"), snapshot.join("model.safetensors")).unwrap();
+        assert_eq!(
+            remove_mlx_from(&hf, "mlx-community/Llama-3.2-3B-Instruct-4bit"),
+            Ok(7)
+        );
+        assert!(mlx_cache_entries(&hf).is_empty());
+        assert!(remove_mlx_from(&hf, "Llama-3.2-3B-Instruct-4bit").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 
     #[test]
     fn test_hf_name_to_mlx_candidates() {
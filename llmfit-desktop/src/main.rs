@@ -1,7 +1,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use llmfit_core::fit::{FitLevel, InferenceRuntime, ModelFit, RunMode};
-use llmfit_core::hardware::SystemSpecs;
+use llmfit_core::fit::{DiskFit, FitLevel, InferenceRuntime, ModelFit, RunMode};
+use llmfit_core::hardware::{SystemSpecs, free_disk_gb};
 use llmfit_core::models::ModelDatabase;
 use llmfit_core::providers::{ModelProvider, OllamaProvider, PullEvent};
 use serde::Serialize;
@@ -15,6 +15,7 @@ struct GpuInfoJs {
     backend: String,
     count: u32,
     unified_memory: bool,
+    vram_used_gb: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -42,6 +43,8 @@ struct ModelFitInfo {
     use_case: String,
     runtime: String,
     installed: bool,
+    download_gb: f64,
+    disk_fit: String,
     notes: Vec<String>,
 }
 
@@ -70,6 +73,7 @@ fn get_system_specs() -> Result<SystemInfo, String> {
             backend: format!("{:?}", g.backend),
             count: g.count,
             unified_memory: g.unified_memory,
+            vram_used_gb: g.vram_used_gb,
         })
         .collect();
     Ok(SystemInfo {
@@ -93,6 +97,10 @@ fn get_model_fits() -> Result<Vec<ModelFitInfo>, String> {
         .map(|m| ModelFit::analyze(m, &specs))
         .collect();
 
+    // Every pull here goes through Ollama, whatever the fit's runtime.
+    llmfit_core::providers::apply_disk_fit_with(&mut fits, |_| {
+        Some(llmfit_core::providers::ollama_models_dir())
+    });
     fits = llmfit_core::fit::rank_models_by_fit(fits);
 
     Ok(fits
@@ -111,7 +119,9 @@ fn get_model_fits() -> Result<Vec<ModelFitInfo>, String> {
                 RunMode::Gpu => "GPU".to_string(),
                 RunMode::CpuOffload => "CPU Offload".to_string(),
                 RunMode::CpuOnly => "CPU Only".to_string(),
+                RunMode::Npu => "NPU".to_string(),
                 RunMode::MoeOffload => "MoE Offload".to_string(),
+                RunMode::DiskStream => "Disk Stream".to_string(),
             },
             score: f.score,
             memory_required_gb: f.memory_required_gb,
@@ -122,8 +132,12 @@ fn get_model_fits() -> Result<Vec<ModelFitInfo>, String> {
             runtime: match f.runtime {
                 InferenceRuntime::LlamaCpp => "llama.cpp".to_string(),
                 InferenceRuntime::Mlx => "MLX".to_string(),
+                InferenceRuntime::Npu => "NPU".to_string(),
+                InferenceRuntime::Vllm => "vLLM".to_string(),
             },
             installed: f.installed,
+            download_gb: f.download_gb,
+            disk_fit: f.disk_fit.label().to_string(),
             notes: f.notes.clone(),
         })
         .collect())
@@ -131,10 +145,35 @@ fn get_model_fits() -> Result<Vec<ModelFitInfo>, String> {
 
 #[tauri::command]
 fn start_pull(model_tag: String, state: State<'_, AppState>) -> Result<String, String> {
+    let mut status = "Starting download...".to_string();
+    let db = ModelDatabase::new();
+    if let Some(model) = db.get_all_models().iter().find(|m| m.name == model_tag) {
+        let size_gb = model.download_size_gb(&model.quantization);
+        let dir = llmfit_core::providers::ollama_models_dir();
+        let free_gb = free_disk_gb(&dir);
+        match DiskFit::from_sizes(size_gb, free_gb) {
+            DiskFit::NoSpace => {
+                return Err(format!(
+                    "Not enough disk space: ~{:.1} GB needed, {:.1} GB free in {}",
+                    size_gb,
+                    free_gb.unwrap_or(0.0),
+                    dir.display()
+                ));
+            }
+            DiskFit::Tight => {
+                status = format!(
+                    "Starting download (low disk: {:.1} GB left after download)...",
+                    free_gb.unwrap_or(0.0) - size_gb
+                );
+            }
+            DiskFit::Fits | DiskFit::Unknown => {}
+        }
+    }
+
     let handle = state.ollama.start_pull(&model_tag)?;
     let mut pull = state.pull_handle.lock().map_err(|e| e.to_string())?;
     *pull = Some(handle);
-    Ok("started".to_string())
+    Ok(status)
 }
 
 #[tauri::command]
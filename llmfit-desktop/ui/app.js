@@ -67,6 +67,15 @@ function fitClass(level) {
   }
 }
 
+function diskClass(diskFit) {
+  switch (diskFit) {
+    case 'Fits': return 'fit-perfect';
+    case 'Tight': return 'fit-marginal';
+    case 'No Space': return 'fit-tight';
+    default: return '';
+  }
+}
+
 function modeClass(mode) {
   switch (mode) {
     case 'GPU': return 'mode-gpu';
@@ -94,7 +103,8 @@ function showModal(fit) {
     ? '<span class="badge badge-installed">Installed</span>'
     : '<span class="badge badge-not-installed">Not Installed</span>';
 
-  const downloadBtn = (!fit.installed && ollamaAvailable)
+  const noSpace = fit.disk_fit === 'No Space';
+  const downloadBtn = (!fit.installed && ollamaAvailable && !noSpace)
     ? '<button class="btn-download" onclick="pullModel(\'' + esc(fit.name) + '\')">⬇ Download via Ollama</button>'
     : '';
 
@@ -125,6 +135,10 @@ function showModal(fit) {
         <span class="stat-label">Est. Speed</span>
         <span class="stat-value">${esc(fit.estimated_tps.toFixed(1))} tok/s</span>
       </div>
+      <div class="modal-stat">
+        <span class="stat-label">Download</span>
+        <span class="stat-value ${diskClass(fit.disk_fit)}">~${esc(fit.download_gb.toFixed(1))} GB (${esc(fit.disk_fit)})</span>
+      </div>
       <div class="modal-stat">
         <span class="stat-label">Use Case</span>
         <span class="stat-value">${esc(fit.use_case)}</span>
@@ -185,7 +199,7 @@ async function pullModel(name) {
   textEl.textContent = 'Starting download...';
 
   try {
-    await invoke('start_pull', { modelTag: name });
+    textEl.textContent = await invoke('start_pull', { modelTag: name });
 
     pullInterval = setInterval(async () => {
       try {
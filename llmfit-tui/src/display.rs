@@ -1,8 +1,11 @@
 use colored::*;
-use llmfit_core::fit::{FitLevel, ModelFit};
+use llmfit_core::advisor::{AdvisorRequest, AdvisorResult};
+use llmfit_core::fit::{DiskFit, FitLevel, ModelFit};
+use llmfit_core::fleet::{FleetPlan, HostUsage};
 use llmfit_core::hardware::SystemSpecs;
 use llmfit_core::models::LlmModel;
-use tabled::{Table, Tabled, settings::Style};
+use llmfit_core::providers::OllamaLoadedModel;
+use tabled::{Table, Tabled, builder::Builder, settings::Style};
 
 #[derive(Tabled)]
 struct ModelRow {
@@ -132,6 +135,13 @@ pub fn display_model_detail(fit: &ModelFit) {
     }
     println!("  Min RAM: {:.1} GB (CPU inference)", fit.model.min_ram_gb);
     println!("  Recommended RAM: {:.1} GB", fit.model.recommended_ram_gb);
+    let disk = match fit.disk_fit {
+        DiskFit::Fits => "fits on disk".green(),
+        DiskFit::Tight => "tight on disk".yellow(),
+        DiskFit::NoSpace => "not enough disk space".red(),
+        DiskFit::Unknown => "free space unknown".dimmed(),
+    };
+    println!("  Download: ~{:.1} GB ({})", fit.download_gb, disk);
 
     // MoE Architecture info
     if fit.model.is_moe {
@@ -226,6 +236,318 @@ pub fn display_search_results(models: &[&LlmModel], query: &str) {
     println!("{}", table);
 }
 
+#[derive(Tabled)]
+struct AdviceRow {
+    #[tabled(rename = "#")]
+    rank: usize,
+    #[tabled(rename = "GPU")]
+    gpu: String,
+    #[tabled(rename = "Total VRAM")]
+    vram: String,
+    #[tabled(rename = "RAM")]
+    ram: String,
+    #[tabled(rename = "Price")]
+    price: String,
+    #[tabled(rename = "Bandwidth")]
+    bandwidth: String,
+    #[tabled(rename = "Worst Fit")]
+    worst_fit: String,
+    #[tabled(rename = "Min tok/s")]
+    min_tps: String,
+}
+
+pub fn display_advice(request: &AdvisorRequest, results: &[AdvisorResult]) {
+    println!("\n{}", "=== Hardware Purchase Advice ===".bold().cyan());
+    for model in &request.models {
+        println!("  - {}", model.name);
+    }
+    if let Some(ctx) = request.target_context {
+        println!("Target context: {} tokens", ctx);
+    }
+    println!();
+
+    if results.is_empty() {
+        println!(
+            "{}",
+            "No catalog configuration runs all of these models at the requested fit level and speed."
+                .yellow()
+        );
+        return;
+    }
+
+    let rows: Vec<AdviceRow> = results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let worst = r.worst_fit();
+            let min_tps = r
+                .fits
+                .iter()
+                .map(|f| f.estimated_tps)
+                .fold(f64::INFINITY, f64::min);
+            AdviceRow {
+                rank: i + 1,
+                gpu: if r.config.gpu_count > 1 {
+                    format!("{} x{}", r.config.gpu.name, r.config.gpu_count)
+                } else {
+                    r.config.gpu.name.clone()
+                },
+                vram: format!("{:.0} GB", r.config.total_vram_gb()),
+                ram: format!("{:.0} GB", r.config.ram_gb),
+                price: r
+                    .config
+                    .total_price_usd()
+                    .map(|p| format!("${:.0}", p))
+                    .unwrap_or_else(|| "n/a".to_string()),
+                bandwidth: format!("{:.0} GB/s", r.config.gpu.bandwidth_gb_s),
+                worst_fit: worst.label().to_string(),
+                min_tps: format!("{:.1}", min_tps),
+            }
+        })
+        .collect();
+
+    let table = Table::new(rows).with(Style::rounded()).to_string();
+    println!("{}", table);
+}
+
+/// True when a model runs on some of the compared machines but not all.
+pub fn fit_differs(fits: &[ModelFit]) -> bool {
+    let runnable = fits
+        .iter()
+        .filter(|f| f.fit_level != FitLevel::TooTight)
+        .count();
+    runnable > 0 && runnable < fits.len()
+}
+
+pub fn display_comparison(machines: &[(String, SystemSpecs)], rows: &[Vec<ModelFit>]) {
+    println!("\n{}", "=== Hardware Comparison ===".bold().cyan());
+    for (label, specs) in machines {
+        let gpu = match (&specs.gpu_name, specs.gpu_vram_gb) {
+            (Some(name), Some(vram)) if specs.gpu_count > 1 => {
+                format!("{} x{} ({:.0} GB each)", name, specs.gpu_count, vram)
+            }
+            (Some(name), Some(vram)) => format!("{} ({:.0} GB)", name, vram),
+            (Some(name), None) => name.clone(),
+            _ => "no GPU".to_string(),
+        };
+        println!(
+            "  {}: {} cores, {:.0} GB RAM, {}",
+            label.bold(),
+            specs.total_cpu_cores,
+            specs.total_ram_gb,
+            gpu
+        );
+    }
+    println!();
+
+    if rows.is_empty() {
+        println!("{}", "No models to compare.".yellow());
+        return;
+    }
+
+    let mut builder = Builder::default();
+    let mut header = vec![String::new(), "Model".to_string()];
+    header.extend(machines.iter().map(|(label, _)| label.clone()));
+    builder.push_record(header);
+
+    let mut differing = 0;
+    for fits in rows {
+        let differs = fit_differs(fits);
+        if differs {
+            differing += 1;
+        }
+        let mut record = vec![
+            if differs { "*" } else { "" }.to_string(),
+            fits[0].model.name.clone(),
+        ];
+        record.extend(fits.iter().map(|f| {
+            if f.fit_level == FitLevel::TooTight {
+                format!("{} {}", f.fit_emoji(), f.fit_text())
+            } else {
+                format!(
+                    "{} {} {} {:.1} tok/s",
+                    f.fit_emoji(),
+                    f.fit_text(),
+                    f.best_quant,
+                    f.estimated_tps
+                )
+            }
+        }));
+        builder.push_record(record);
+    }
+
+    let table = builder.build().with(Style::rounded()).to_string();
+    println!("{}", table);
+    if differing > 0 {
+        println!(
+            "{} {} model(s) run on some machines but not others.",
+            "*".yellow().bold(),
+            differing
+        );
+    }
+}
+
+#[derive(Tabled)]
+struct PlacementRow {
+    #[tabled(rename = "Host")]
+    host: String,
+    #[tabled(rename = "Model")]
+    model: String,
+    #[tabled(rename = "Replica")]
+    replica: u32,
+    #[tabled(rename = "GPUs")]
+    gpus: String,
+    #[tabled(rename = "Mode")]
+    mode: String,
+    #[tabled(rename = "Fit")]
+    fit: String,
+    #[tabled(rename = "VRAM")]
+    vram: String,
+    #[tabled(rename = "RAM")]
+    ram: String,
+    #[tabled(rename = "tok/s")]
+    tps: String,
+}
+
+#[derive(Tabled)]
+struct HostUsageRow {
+    #[tabled(rename = "Host")]
+    host: String,
+    #[tabled(rename = "GPU Memory")]
+    gpu: String,
+    #[tabled(rename = "RAM")]
+    ram: String,
+}
+
+pub fn display_fleet_plan(plan: &FleetPlan) {
+    println!("\n{}", "=== Fleet Placement Plan ===".bold().cyan());
+    println!(
+        "{} replica(s) placed on {} host(s)\n",
+        plan.placements.len(),
+        plan.hosts.len()
+    );
+
+    if !plan.placements.is_empty() {
+        let rows: Vec<PlacementRow> = plan
+            .placements
+            .iter()
+            .map(|p| PlacementRow {
+                host: p.host.clone(),
+                model: p.model.clone(),
+                replica: p.replica,
+                gpus: if p.gpu_indices.is_empty() {
+                    "-".to_string()
+                } else {
+                    p.gpu_indices
+                        .iter()
+                        .map(|i| i.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                },
+                mode: p.fit.run_mode_text().to_string(),
+                fit: format!("{} {}", p.fit.fit_emoji(), p.fit.fit_text()),
+                vram: format!("{:.1} GB", p.vram_gb),
+                ram: format!("{:.1} GB", p.ram_gb),
+                tps: format!("{:.1}", p.fit.estimated_tps),
+            })
+            .collect();
+        println!("{}", Table::new(rows).with(Style::rounded()));
+    }
+
+    let usage: Vec<HostUsageRow> = plan
+        .hosts
+        .iter()
+        .map(|h| HostUsageRow {
+            host: h.name.clone(),
+            gpu: gpu_usage_text(h),
+            ram: if h.unified_memory {
+                "(unified)".to_string()
+            } else {
+                format!("{:.1} / {:.1} GB", h.ram_used_gb, h.ram_total_gb)
+            },
+        })
+        .collect();
+    println!("\n{}", "Host usage".bold());
+    println!("{}", Table::new(usage).with(Style::rounded()));
+
+    if !plan.unplaced.is_empty() {
+        println!("\n{}", "Unplaced".bold().red());
+        for u in &plan.unplaced {
+            println!("  - {} x{}: {}", u.model.yellow(), u.replicas, u.reason);
+        }
+    }
+}
+
+fn gpu_usage_text(host: &HostUsage) -> String {
+    if host.gpu_total_gb.is_empty() {
+        return "no GPU".to_string();
+    }
+    host.gpu_total_gb
+        .iter()
+        .zip(&host.gpu_used_gb)
+        .enumerate()
+        .map(|(i, (total, used))| format!("#{}: {:.1} / {:.1} GB", i, used, total))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Tabled)]
+struct LoadedRow {
+    #[tabled(rename = "Model")]
+    name: String,
+    #[tabled(rename = "Size")]
+    size: String,
+    #[tabled(rename = "VRAM")]
+    vram: String,
+    #[tabled(rename = "RAM")]
+    ram: String,
+    #[tabled(rename = "GPU")]
+    gpu: String,
+    #[tabled(rename = "Context")]
+    context: String,
+    #[tabled(rename = "llmfit est.")]
+    estimate: String,
+    #[tabled(rename = "Until")]
+    until: String,
+}
+
+/// Models Ollama has loaded, next to llmfit's memory estimate for each.
+pub fn display_ollama_status(loaded: &[OllamaLoadedModel], estimates: &[Option<&ModelFit>]) {
+    println!("\n{}", "=== Loaded Ollama Models ===".bold().cyan());
+    if loaded.is_empty() {
+        println!("No models loaded.");
+        return;
+    }
+
+    let rows: Vec<LoadedRow> = loaded
+        .iter()
+        .zip(estimates)
+        .map(|(m, fit)| LoadedRow {
+            name: m.name.clone(),
+            size: format!("{:.1} GB", m.size_gb()),
+            vram: format!("{:.1} GB", m.vram_gb()),
+            ram: format!("{:.1} GB", m.ram_gb()),
+            gpu: format!("{:.0}%", m.gpu_percent()),
+            context: m
+                .context_length
+                .map(|c| format!("{}k", c / 1024))
+                .unwrap_or_else(|| "-".to_string()),
+            estimate: fit
+                .map(|f| format!("{:.1} GB", f.memory_required_gb))
+                .unwrap_or_else(|| "-".to_string()),
+            until: m.expires_at.clone().unwrap_or_else(|| "-".to_string()),
+        })
+        .collect();
+    println!("{}", Table::new(rows).with(Style::rounded()));
+
+    let vram: f64 = loaded.iter().map(|m| m.vram_gb()).sum();
+    let ram: f64 = loaded.iter().map(|m| m.ram_gb()).sum();
+    println!(
+        "Ollama holds {:.1} GB of VRAM and {:.1} GB of RAM. Fit with --exclude-loaded to keep them resident.",
+        vram, ram
+    );
+}
+
 // ────────────────────────────────────────────────────────────────────
 // JSON output for machine consumption (OpenClaw skills, scripts, etc.)
 // ────────────────────────────────────────────────────────────────────
@@ -254,6 +576,159 @@ pub fn display_json_fits(specs: &SystemSpecs, fits: &[ModelFit]) {
     );
 }
 
+/// Serialize purchase advice to JSON and print to stdout.
+pub fn display_json_advice(results: &[AdvisorResult]) {
+    let configs: Vec<serde_json::Value> = results
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "gpu": r.config.gpu.name,
+                "gpu_count": r.config.gpu_count,
+                "backend": r.config.gpu.backend.label(),
+                "vram_per_gpu_gb": round2(r.config.gpu.vram_gb),
+                "total_vram_gb": round2(r.config.total_vram_gb()),
+                "ram_gb": round2(r.config.ram_gb),
+                "bandwidth_gb_s": round1(r.config.gpu.bandwidth_gb_s),
+                "price_usd": r.config.total_price_usd().map(round2),
+                "worst_fit": r.worst_fit().label(),
+                "models": r.fits.iter().map(fit_to_json).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    let output = serde_json::json!({ "configurations": configs });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&output).expect("JSON serialization failed")
+    );
+}
+
+/// Serialize a multi-machine comparison to JSON and print to stdout.
+pub fn display_json_comparison(machines: &[(String, SystemSpecs)], rows: &[Vec<ModelFit>]) {
+    let machines_json: Vec<serde_json::Value> = machines
+        .iter()
+        .map(|(label, specs)| {
+            serde_json::json!({
+                "label": label,
+                "system": system_json(specs),
+            })
+        })
+        .collect();
+    let models: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|fits| {
+            let results: Vec<serde_json::Value> = machines
+                .iter()
+                .zip(fits)
+                .map(|((label, _), f)| {
+                    serde_json::json!({
+                        "machine": label,
+                        "fit_level": f.fit_text(),
+                        "run_mode": f.run_mode_text(),
+                        "best_quant": f.best_quant,
+                        "estimated_tps": round1(f.estimated_tps),
+                        "score": round1(f.score),
+                        "memory_required_gb": round2(f.memory_required_gb),
+                        "memory_available_gb": round2(f.memory_available_gb),
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "name": fits[0].model.name,
+                "differs": fit_differs(fits),
+                "results": results,
+            })
+        })
+        .collect();
+    let output = serde_json::json!({
+        "machines": machines_json,
+        "models": models,
+    });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&output).expect("JSON serialization failed")
+    );
+}
+
+/// Serialize a fleet placement plan to JSON and print to stdout.
+pub fn display_json_fleet_plan(plan: &FleetPlan) {
+    let placements: Vec<serde_json::Value> = plan
+        .placements
+        .iter()
+        .map(|p| {
+            serde_json::json!({
+                "host": p.host,
+                "model": p.model,
+                "replica": p.replica,
+                "gpu_indices": p.gpu_indices,
+                "vram_gb": round2(p.vram_gb),
+                "ram_gb": round2(p.ram_gb),
+                "run_mode": p.fit.run_mode_text(),
+                "fit_level": p.fit.fit_text(),
+                "estimated_tps": round1(p.fit.estimated_tps),
+            })
+        })
+        .collect();
+    let hosts: Vec<serde_json::Value> = plan
+        .hosts
+        .iter()
+        .map(|h| {
+            serde_json::json!({
+                "name": h.name,
+                "gpu_total_gb": h.gpu_total_gb.iter().copied().map(round2).collect::<Vec<_>>(),
+                "gpu_used_gb": h.gpu_used_gb.iter().copied().map(round2).collect::<Vec<_>>(),
+                "ram_total_gb": round2(h.ram_total_gb),
+                "ram_used_gb": round2(h.ram_used_gb),
+                "unified_memory": h.unified_memory,
+            })
+        })
+        .collect();
+    let unplaced: Vec<serde_json::Value> = plan
+        .unplaced
+        .iter()
+        .map(|u| {
+            serde_json::json!({
+                "model": u.model,
+                "replicas": u.replicas,
+                "reason": u.reason,
+            })
+        })
+        .collect();
+    let output = serde_json::json!({
+        "placements": placements,
+        "hosts": hosts,
+        "unplaced": unplaced,
+    });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&output).expect("JSON serialization failed")
+    );
+}
+
+/// Serialize loaded Ollama models to JSON and print to stdout.
+pub fn display_json_ollama_status(loaded: &[OllamaLoadedModel], estimates: &[Option<&ModelFit>]) {
+    let models: Vec<serde_json::Value> = loaded
+        .iter()
+        .zip(estimates)
+        .map(|(m, fit)| {
+            serde_json::json!({
+                "name": m.name,
+                "size_gb": round2(m.size_gb()),
+                "vram_gb": round2(m.vram_gb()),
+                "ram_gb": round2(m.ram_gb()),
+                "gpu_percent": round1(m.gpu_percent()),
+                "context_length": m.context_length,
+                "expires_at": m.expires_at,
+                "estimated_memory_gb": fit.map(|f| round2(f.memory_required_gb)),
+            })
+        })
+        .collect();
+    let output = serde_json::json!({ "loaded": models });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&output).expect("JSON serialization failed")
+    );
+}
+
 fn system_json(specs: &SystemSpecs) -> serde_json::Value {
     let gpus_json: Vec<serde_json::Value> = specs
         .gpus
@@ -265,6 +740,10 @@ fn system_json(specs: &SystemSpecs) -> serde_json::Value {
                 "backend": g.backend.label(),
                 "count": g.count,
                 "unified_memory": g.unified_memory,
+                "vram_used_gb": g.vram_used_gb.map(round2),
+                "pcie_link": g.pcie_link,
+                "memory_bandwidth_gb_s": g.memory_bandwidth_gb_s,
+                "provenance": g.provenance,
             })
         })
         .collect();
@@ -281,11 +760,20 @@ fn system_json(specs: &SystemSpecs) -> serde_json::Value {
         "unified_memory": specs.unified_memory,
         "backend": specs.backend.label(),
         "gpus": gpus_json,
+        "cgroup_limits": specs.cgroup_limits,
+        "storage": specs.storage,
+        "cpu_features": specs.cpu_features,
+        "memory_topology": specs.memory_topology,
+        "memory_bandwidth_gb_s": specs.memory_bandwidth_gb_s().map(round1),
+        "board": specs.board,
+        "npus": specs.npus,
+        "power": specs.power,
+        "detection": specs.detection,
     })
 }
 
 fn fit_to_json(fit: &ModelFit) -> serde_json::Value {
-    serde_json::json!({
+    let mut json = serde_json::json!({
         "name": fit.model.name,
         "provider": fit.model.provider,
         "parameter_count": fit.model.parameter_count,
@@ -310,8 +798,15 @@ fn fit_to_json(fit: &ModelFit) -> serde_json::Value {
         "memory_required_gb": round2(fit.memory_required_gb),
         "memory_available_gb": round2(fit.memory_available_gb),
         "utilization_pct": round1(fit.utilization_pct),
+        "download_gb": round2(fit.download_gb),
+        "disk_fit": fit.disk_fit.label(),
         "notes": fit.notes,
-    })
+    });
+    // Only `--runtime vllm` plans for vLLM
+    if let Some(plan) = &fit.vllm {
+        json["vllm"] = serde_json::json!(plan);
+    }
+    json
 }
 
 fn round1(v: f64) -> f64 {
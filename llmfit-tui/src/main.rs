@@ -4,9 +4,13 @@ mod tui_events;
 mod tui_ui;
 
 use clap::{Parser, Subcommand};
+use llmfit_core::advisor;
 use llmfit_core::fit::ModelFit;
+use llmfit_core::fleet;
 use llmfit_core::hardware::SystemSpecs;
-use llmfit_core::models::ModelDatabase;
+use llmfit_core::models::{LlmModel, ModelDatabase};
+use llmfit_core::probe::RecordingProbe;
+use llmfit_core::providers::{self, ModelProvider};
 
 #[derive(Parser)]
 #[command(name = "llmfit")]
@@ -32,16 +36,56 @@ struct Cli {
     #[arg(long)]
     json: bool,
 
+    #[command(flatten)]
+    hw: HardwareArgs,
+}
+
+/// Options that adjust detected hardware before fitting.
+#[derive(clap::Args)]
+struct HardwareArgs {
     /// Override GPU VRAM size (e.g. "32G", "32000M", "1.5T").
     /// Useful when GPU memory autodetection fails.
-    #[arg(long, value_name = "SIZE")]
+    #[arg(long, value_name = "SIZE", global = true)]
     memory: Option<String>,
+
+    /// Fit against VRAM that is currently free rather than total VRAM
+    #[arg(long, global = true)]
+    free_vram: bool,
+
+    /// Fit against VRAM left after the models Ollama has loaded, so a new
+    /// model won't evict them
+    #[arg(long, global = true)]
+    exclude_loaded: bool,
+
+    /// Override RAM type and speed (e.g. "DDR5-5600", or "DDR4-3200x8" for
+    /// 8 channels). Used for CPU speed estimates when dmidecode is unavailable.
+    #[arg(long, value_name = "SPEC", global = true)]
+    ram_speed: Option<String>,
+
+    /// Detect hardware from a dump written by `llmfit system --dump`
+    /// instead of probing this machine.
+    #[arg(long, value_name = "FILE", global = true)]
+    from_dump: Option<std::path::PathBuf>,
+
+    /// Probe hardware again instead of reusing cached detection results
+    #[arg(long, global = true)]
+    refresh_hw: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Show system hardware specifications
-    System,
+    System {
+        /// Record every command output and file read used by detection
+        /// into FILE, for attaching to a misdetection report.
+        #[arg(long, value_name = "FILE")]
+        dump: Option<std::path::PathBuf>,
+
+        /// Also show which probe produced each RAM and GPU figure, and why
+        /// the others failed
+        #[arg(short, long)]
+        verbose: bool,
+    },
 
     /// List all available LLM models
     List,
@@ -83,7 +127,7 @@ enum Commands {
         #[arg(long, default_value = "marginal")]
         min_fit: String,
 
-        /// Filter by inference runtime: mlx, llamacpp, any
+        /// Filter by inference runtime: mlx, llamacpp, npu, vllm, any
         #[arg(long, default_value = "any")]
         runtime: String,
 
@@ -91,29 +135,188 @@ enum Commands {
         #[arg(long, default_value = "true")]
         json: bool,
     },
+
+    /// Show models Ollama has loaded and the memory they actually use
+    Status,
+
+    /// Delete an installed model from a local provider
+    Remove(RemoveArgs),
+
+    /// Find the cheapest GPU configuration that runs a set of models
+    Advise(AdviseArgs),
+
+    /// Compare model fit across machines saved with `llmfit --json system`
+    CompareHw(CompareHwArgs),
+
+    /// Plan model placement across a fleet of hosts
+    Fleet {
+        #[command(subcommand)]
+        command: FleetCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum FleetCommands {
+    /// Assign model replicas to hosts within their memory budgets
+    Plan(FleetPlanArgs),
+}
+
+#[derive(clap::Args)]
+struct FleetPlanArgs {
+    /// Directory of host spec files (output of `llmfit --json system`, one per host)
+    hosts: std::path::PathBuf,
+
+    /// Models to place, optionally with a replica count (e.g. "Llama-3.1-8B=3")
+    #[arg(required = true)]
+    models: Vec<String>,
+
+    /// Minimum fit level for every replica: perfect, good, marginal
+    #[arg(long, default_value = "marginal")]
+    min_fit: String,
 }
 
-/// Detect system specs with optional GPU memory override.
-fn detect_specs(memory_override: &Option<String>) -> SystemSpecs {
-    let specs = SystemSpecs::detect();
-    if let Some(mem_str) = memory_override {
+#[derive(clap::Args)]
+struct RemoveArgs {
+    /// Model name or partial name, or a provider's own tag (e.g. "llama3.1:8b")
+    model: String,
+
+    /// Only remove from this provider: ollama, mlx, llamacpp, lmstudio
+    #[arg(long)]
+    provider: Option<String>,
+
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+#[derive(clap::Args)]
+struct CompareHwArgs {
+    /// System spec files (output of `llmfit --json system`), one per machine
+    #[arg(required = true, num_args = 2..)]
+    files: Vec<std::path::PathBuf>,
+
+    /// Only show models that run on some machines but not others
+    #[arg(long)]
+    diff: bool,
+
+    /// Include models that run on none of the machines
+    #[arg(long)]
+    all: bool,
+
+    /// Limit number of models shown
+    #[arg(short = 'n', long)]
+    limit: Option<usize>,
+}
+
+#[derive(clap::Args)]
+struct AdviseArgs {
+    /// Models that must all fit (name or partial name)
+    #[arg(required = true)]
+    models: Vec<String>,
+
+    /// Context length every model must support (e.g. 8192)
+    #[arg(long, value_name = "TOKENS")]
+    ctx: Option<u32>,
+
+    /// Minimum fit level for every model: perfect, good, marginal
+    #[arg(long, default_value = "good")]
+    min_fit: String,
+
+    /// Minimum estimated tok/s every model must reach
+    #[arg(long, value_name = "TPS")]
+    min_tps: Option<f64>,
+
+    /// Maximum number of identical GPUs per configuration
+    #[arg(long, default_value = "4")]
+    max_gpus: u32,
+
+    /// Rank configurations by: price, vram
+    #[arg(long, default_value = "price")]
+    by: String,
+
+    /// CPU cores of the machine to buy (default: this host's)
+    #[arg(long, value_name = "N")]
+    cpu_cores: Option<usize>,
+
+    /// Custom GPU catalog (JSON array of name, vram_gb, bandwidth_gb_s, backend, price_usd)
+    #[arg(long, value_name = "FILE")]
+    catalog: Option<std::path::PathBuf>,
+
+    /// Limit number of configurations shown
+    #[arg(short = 'n', long, default_value = "5")]
+    limit: usize,
+}
+
+/// Detect system specs, applying the GPU memory override and free-VRAM mode.
+fn detect_specs(hw: &HardwareArgs) -> SystemSpecs {
+    detect_specs_inner(hw, hw.free_vram)
+}
+
+/// Like `detect_specs`, also reading VRAM in use to show it.
+fn detect_specs_with_usage(hw: &HardwareArgs) -> SystemSpecs {
+    detect_specs_inner(hw, true)
+}
+
+fn detect_specs_inner(hw: &HardwareArgs, usage: bool) -> SystemSpecs {
+    let specs = match &hw.from_dump {
+        Some(path) => SystemSpecs::detect_from_dump(path).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }),
+        None if usage => SystemSpecs::detect_cached(hw.refresh_hw).with_vram_usage(),
+        None => SystemSpecs::detect_cached(hw.refresh_hw),
+    };
+    apply_hardware_overrides(specs, hw)
+}
+
+fn apply_hardware_overrides(mut specs: SystemSpecs, hw: &HardwareArgs) -> SystemSpecs {
+    if let Some(mem_str) = &hw.memory {
         match llmfit_core::hardware::parse_memory_size(mem_str) {
-            Some(gb) => specs.with_gpu_memory_override(gb),
+            Some(gb) => specs = specs.with_gpu_memory_override(gb),
             None => {
                 eprintln!(
                     "Warning: could not parse --memory value '{}'. Expected format: 32G, 32000M, 1.5T",
                     mem_str
                 );
-                specs
             }
         }
-    } else {
-        specs
     }
+    if hw.exclude_loaded && hw.free_vram {
+        eprintln!(
+            "Warning: --exclude-loaded is ignored with --free-vram, which already leaves out what Ollama holds"
+        );
+    } else if hw.exclude_loaded && hw.from_dump.is_some() {
+        eprintln!(
+            "Warning: --exclude-loaded is ignored with --from-dump: Ollama's loaded models are on this machine, not the dumped one"
+        );
+    }
+    if hw.free_vram {
+        specs = specs.with_free_vram();
+    } else if hw.exclude_loaded
+        && hw.from_dump.is_none()
+        && let Some(loaded) = providers::OllamaProvider::new().loaded_models()
+    {
+        // Free VRAM already leaves out what Ollama holds.
+        specs = specs.with_reserved_vram(loaded.iter().map(|m| m.vram_gb()).sum());
+    }
+    if let Some(speed_str) = &hw.ram_speed {
+        match llmfit_core::hardware::parse_memory_speed(speed_str) {
+            Some((memory_type, speed, channels)) => {
+                specs = specs.with_memory_speed_override(memory_type, speed, channels)
+            }
+            None => {
+                eprintln!(
+                    "Warning: could not parse --ram-speed value '{}'. Expected format: DDR5-5600, DDR4-3200x8",
+                    speed_str
+                );
+            }
+        }
+    }
+    specs
 }
 
-fn run_fit(perfect: bool, limit: Option<usize>, json: bool, memory_override: &Option<String>) {
-    let specs = detect_specs(memory_override);
+fn run_fit(perfect: bool, limit: Option<usize>, json: bool, hw: &HardwareArgs) {
+    let specs = detect_specs(hw);
     let db = ModelDatabase::new();
 
     if !json {
@@ -125,6 +328,7 @@ fn run_fit(perfect: bool, limit: Option<usize>, json: bool, memory_override: &Op
         .iter()
         .map(|m| ModelFit::analyze(m, &specs))
         .collect();
+    providers::apply_disk_fit(&mut fits);
 
     if perfect {
         fits.retain(|f| f.fit_level == llmfit_core::fit::FitLevel::Perfect);
@@ -143,7 +347,7 @@ fn run_fit(perfect: bool, limit: Option<usize>, json: bool, memory_override: &Op
     }
 }
 
-fn run_tui(memory_override: &Option<String>) -> std::io::Result<()> {
+fn run_tui(hw: &HardwareArgs) -> std::io::Result<()> {
     // Setup terminal
     crossterm::terminal::enable_raw_mode()?;
     let mut stdout = std::io::stdout();
@@ -157,7 +361,7 @@ fn run_tui(memory_override: &Option<String>) -> std::io::Result<()> {
     let mut terminal = ratatui::Terminal::new(backend)?;
 
     // Create app state
-    let specs = detect_specs(memory_override);
+    let specs = detect_specs_with_usage(hw);
     let mut app = tui_app::App::with_specs(specs);
 
     // Main loop
@@ -191,16 +395,25 @@ fn run_recommend(
     min_fit: String,
     runtime_filter: String,
     json: bool,
-    memory_override: &Option<String>,
+    hw: &HardwareArgs,
 ) {
-    let specs = detect_specs(memory_override);
+    let specs = detect_specs(hw);
     let db = ModelDatabase::new();
 
+    // vLLM is never picked automatically; asking for it re-runs the fit
+    // under its memory model.
+    let runtime_filter = runtime_filter.to_lowercase();
+    let analyze = if runtime_filter == "vllm" {
+        ModelFit::analyze_vllm
+    } else {
+        ModelFit::analyze
+    };
     let mut fits: Vec<ModelFit> = db
         .get_all_models()
         .iter()
-        .map(|m| ModelFit::analyze(m, &specs))
+        .map(|m| analyze(m, &specs))
         .collect();
+    providers::apply_disk_fit(&mut fits);
 
     // Filter by minimum fit level
     let min_level = match min_fit.to_lowercase().as_str() {
@@ -221,8 +434,9 @@ fn run_recommend(
     });
 
     // Filter by runtime
-    match runtime_filter.to_lowercase().as_str() {
+    match runtime_filter.as_str() {
         "mlx" => fits.retain(|f| f.runtime == llmfit_core::fit::InferenceRuntime::Mlx),
+        "npu" => fits.retain(|f| f.runtime == llmfit_core::fit::InferenceRuntime::Npu),
         "llamacpp" | "llama.cpp" | "llama_cpp" => {
             fits.retain(|f| f.runtime == llmfit_core::fit::InferenceRuntime::LlamaCpp)
         }
@@ -258,18 +472,374 @@ fn run_recommend(
     }
 }
 
+fn run_status(json: bool, hw: &HardwareArgs) {
+    let ollama = providers::OllamaProvider::new();
+    let Some(loaded) = ollama.loaded_models() else {
+        eprintln!("Error: Ollama is not running");
+        std::process::exit(1);
+    };
+
+    // llmfit's own estimate for each loaded tag, to set against its residency.
+    let specs = detect_specs(hw);
+    let details = ollama.installed_details();
+    let mut fits: Vec<ModelFit> = ModelDatabase::new()
+        .get_all_models()
+        .iter()
+        .map(|m| ModelFit::analyze(m, &specs))
+        .collect();
+    providers::apply_ollama_installed(&mut fits, &details, &specs);
+    let estimates: Vec<Option<&ModelFit>> = loaded
+        .iter()
+        .map(|m| providers::fit_for_ollama_tag(&fits, &details, &m.name))
+        .collect();
+
+    if json {
+        display::display_json_ollama_status(&loaded, &estimates);
+    } else {
+        display::display_ollama_status(&loaded, &estimates);
+    }
+}
+
+/// Resolve a model query to a single database entry.
+/// An exact (case-insensitive) name match wins over partial matches.
+fn resolve_model<'a>(db: &'a ModelDatabase, query: &str) -> Result<&'a LlmModel, String> {
+    if let Some(m) = db
+        .get_all_models()
+        .iter()
+        .find(|m| m.name.eq_ignore_ascii_case(query))
+    {
+        return Ok(m);
+    }
+    let results = db.find_model(query);
+    match results.len() {
+        0 => Err(format!("No model found matching '{}'", query)),
+        1 => Ok(results[0]),
+        _ => {
+            let names: Vec<&str> = results.iter().map(|m| m.name.as_str()).collect();
+            Err(format!(
+                "Multiple models match '{}'. Please be more specific:\n  - {}",
+                query,
+                names.join("\n  - ")
+            ))
+        }
+    }
+}
+
+/// Provider name reduced for matching `--provider` ("LM Studio" → "lmstudio").
+fn provider_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn run_remove(args: RemoveArgs, json: bool) {
+    let all: Vec<Box<dyn ModelProvider>> = vec![
+        Box::new(providers::OllamaProvider::new()),
+        Box::new(providers::MlxProvider::new()),
+        Box::new(providers::LlamaServerProvider::new()),
+        Box::new(providers::LmStudioProvider::new()),
+    ];
+    let selected: Vec<&dyn ModelProvider> = all
+        .iter()
+        .map(|p| p.as_ref())
+        .filter(|p| {
+            args.provider
+                .as_ref()
+                .is_none_or(|name| provider_key(name) == provider_key(p.name()))
+        })
+        .collect();
+    if selected.is_empty() {
+        eprintln!(
+            "Unknown provider '{}'. Expected ollama, mlx, llamacpp or lmstudio",
+            args.provider.unwrap_or_default()
+        );
+        std::process::exit(1);
+    }
+
+    // A provider's own name for the model wins; otherwise look for installed
+    // builds of the database model it names.
+    let installed: Vec<_> = selected.iter().map(|p| p.installed_models()).collect();
+    let query = args.model.to_lowercase();
+    let mut candidates: Vec<(&dyn ModelProvider, String)> = selected
+        .iter()
+        .zip(&installed)
+        .filter(|(_, set)| set.contains(&query))
+        .map(|(p, _)| (*p, args.model.clone()))
+        .collect();
+    let db = ModelDatabase::new();
+    let model = resolve_model(&db, &args.model);
+    if candidates.is_empty()
+        && let Ok(model) = &model
+    {
+        for (p, set) in selected.iter().zip(&installed) {
+            for tag in p.installed_tags(&model.name, set) {
+                candidates.push((*p, tag));
+            }
+        }
+    }
+    // Path-like tags (LM Studio's "<publisher>/<repo>") aren't in the
+    // installed set; pass them through once --provider names the provider.
+    if candidates.is_empty() && selected.len() == 1 && args.model.contains('/') {
+        candidates.push((selected[0], args.model.clone()));
+    }
+
+    let (provider, tag) = match candidates.len() {
+        0 => {
+            match model {
+                Err(e) if e.starts_with("Multiple") => eprintln!("{}", e),
+                _ => eprintln!("'{}' is not installed in any provider", args.model),
+            }
+            std::process::exit(1);
+        }
+        1 => candidates.remove(0),
+        _ => {
+            eprintln!(
+                "'{}' is installed under several names. Pass one of them, with --provider if needed:",
+                args.model
+            );
+            for (p, tag) in &candidates {
+                eprintln!("  - {} ({})", tag, p.name());
+            }
+            std::process::exit(1);
+        }
+    };
+
+    if !args.yes {
+        eprint!("Remove {} from {}? [y/N] ", tag, provider.name());
+        let mut answer = String::new();
+        let _ = std::io::stdin().read_line(&mut answer);
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            eprintln!("Cancelled");
+            return;
+        }
+    }
+
+    let freed_gb = match provider.remove(&tag) {
+        Ok(bytes) => bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Re-query every provider so the report reflects what's left.
+    let still_installed: Vec<&str> = match &model {
+        Ok(model) => all
+            .iter()
+            .filter(|p| {
+                !p.installed_tags(&model.name, &p.installed_models())
+                    .is_empty()
+            })
+            .map(|p| p.name())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    if json {
+        let output = serde_json::json!({
+            "removed": tag,
+            "provider": provider.name(),
+            "freed_gb": (freed_gb * 100.0).round() / 100.0,
+            "still_installed_in": still_installed,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).expect("JSON serialization failed")
+        );
+    } else {
+        println!(
+            "Removed {} from {}, freeing {:.1} GB",
+            tag,
+            provider.name(),
+            freed_gb
+        );
+        if !still_installed.is_empty() {
+            println!("Still installed in: {}", still_installed.join(", "));
+        }
+    }
+}
+
+fn run_advise(args: AdviseArgs, json: bool) {
+    let db = ModelDatabase::new();
+    let mut models = Vec::new();
+    for query in &args.models {
+        match resolve_model(&db, query) {
+            Ok(m) => models.push(m),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let catalog = match &args.catalog {
+        Some(path) => match advisor::load_gpu_catalog(path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => advisor::default_gpu_catalog(),
+    };
+
+    let mut request = advisor::AdvisorRequest::new(models);
+    request.target_context = args.ctx;
+    request.min_tps = args.min_tps;
+    request.max_gpus = args.max_gpus;
+    if let Some(cores) = args.cpu_cores {
+        request.cpu_cores = cores;
+    }
+    request.min_fit = match args.min_fit.to_lowercase().as_str() {
+        "perfect" => llmfit_core::fit::FitLevel::Perfect,
+        "marginal" => llmfit_core::fit::FitLevel::Marginal,
+        _ => llmfit_core::fit::FitLevel::Good,
+    };
+    request.objective = match args.by.to_lowercase().as_str() {
+        "vram" | "smallest" | "size" => advisor::AdvisorObjective::Smallest,
+        _ => advisor::AdvisorObjective::Cheapest,
+    };
+
+    let mut results = advisor::advise(&request, &catalog);
+    results.truncate(args.limit);
+
+    if json {
+        display::display_json_advice(&results);
+    } else {
+        display::display_advice(&request, &results);
+    }
+}
+
+fn run_compare_hw(args: CompareHwArgs, json: bool) {
+    let mut machines = Vec::new();
+    for path in &args.files {
+        match SystemSpecs::load_json_file(path) {
+            Ok(specs) => {
+                let label = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string());
+                machines.push((label, specs));
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let db = ModelDatabase::new();
+    let mut rows: Vec<Vec<ModelFit>> = db
+        .get_all_models()
+        .iter()
+        .map(|m| {
+            machines
+                .iter()
+                .map(|(_, specs)| ModelFit::analyze(m, specs))
+                .collect()
+        })
+        .collect();
+
+    if args.diff {
+        rows.retain(|fits| display::fit_differs(fits));
+    } else if !args.all {
+        rows.retain(|fits| {
+            fits.iter()
+                .any(|f| f.fit_level != llmfit_core::fit::FitLevel::TooTight)
+        });
+    }
+
+    // Best result on any machine first, so the most useful models lead.
+    let best_score = |fits: &Vec<ModelFit>| fits.iter().map(|f| f.score).fold(0.0, f64::max);
+    rows.sort_by(|a, b| {
+        best_score(b)
+            .partial_cmp(&best_score(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    if let Some(n) = args.limit {
+        rows.truncate(n);
+    }
+
+    if json {
+        display::display_json_comparison(&machines, &rows);
+    } else {
+        display::display_comparison(&machines, &rows);
+    }
+}
+
+fn run_fleet_plan(args: FleetPlanArgs, json: bool) {
+    let hosts = match fleet::load_fleet_dir(&args.hosts) {
+        Ok(h) => h,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let db = ModelDatabase::new();
+    let mut demands = Vec::new();
+    for spec in &args.models {
+        let (query, replicas) = match spec.rsplit_once('=') {
+            Some((name, count)) => match count.trim().parse::<u32>() {
+                Ok(n) => (name.trim(), n),
+                Err(_) => {
+                    eprintln!("Invalid replica count in '{}'", spec);
+                    std::process::exit(1);
+                }
+            },
+            None => (spec.as_str(), 1),
+        };
+        match resolve_model(&db, query) {
+            Ok(model) => demands.push(fleet::FleetDemand { model, replicas }),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let min_fit = match args.min_fit.to_lowercase().as_str() {
+        "perfect" => llmfit_core::fit::FitLevel::Perfect,
+        "good" => llmfit_core::fit::FitLevel::Good,
+        _ => llmfit_core::fit::FitLevel::Marginal,
+    };
+    let plan = fleet::plan_fleet(&hosts, &demands, min_fit);
+
+    if json {
+        display::display_json_fleet_plan(&plan);
+    } else {
+        display::display_fleet_plan(&plan);
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
     // If a subcommand is given, use classic CLI mode
     if let Some(command) = cli.command {
         match command {
-            Commands::System => {
-                let specs = detect_specs(&cli.memory);
+            Commands::System { dump, verbose } => {
+                let specs = match dump {
+                    Some(path) => {
+                        let probe = RecordingProbe::new();
+                        let specs = SystemSpecs::detect_with(&probe).with_vram_usage_from(&probe);
+                        if let Err(e) = probe.into_dump().save(&path) {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                        eprintln!("Hardware dump written to {}", path.display());
+                        apply_hardware_overrides(specs, &cli.hw)
+                    }
+                    None => detect_specs_with_usage(&cli.hw),
+                };
                 if cli.json {
                     display::display_json_system(&specs);
                 } else {
                     specs.display();
+                    if verbose {
+                        specs.display_detection();
+                    }
                 }
             }
 
@@ -279,7 +849,7 @@ fn main() {
             }
 
             Commands::Fit { perfect, limit } => {
-                run_fit(perfect, limit, cli.json, &cli.memory);
+                run_fit(perfect, limit, cli.json, &cli.hw);
             }
 
             Commands::Search { query } => {
@@ -290,7 +860,7 @@ fn main() {
 
             Commands::Info { model } => {
                 let db = ModelDatabase::new();
-                let specs = detect_specs(&cli.memory);
+                let specs = detect_specs(&cli.hw);
                 let results = db.find_model(&model);
 
                 if results.is_empty() {
@@ -306,7 +876,8 @@ fn main() {
                     return;
                 }
 
-                let fit = ModelFit::analyze(results[0], &specs);
+                let mut fit = ModelFit::analyze(results[0], &specs);
+                providers::apply_disk_fit(std::slice::from_mut(&mut fit));
                 if cli.json {
                     display::display_json_fits(&specs, &[fit]);
                 } else {
@@ -321,20 +892,32 @@ fn main() {
                 runtime,
                 json,
             } => {
-                run_recommend(limit, use_case, min_fit, runtime, json, &cli.memory);
+                run_recommend(limit, use_case, min_fit, runtime, json, &cli.hw);
             }
+
+            Commands::Status => run_status(cli.json, &cli.hw),
+
+            Commands::Remove(args) => run_remove(args, cli.json),
+
+            Commands::Advise(args) => run_advise(args, cli.json),
+
+            Commands::CompareHw(args) => run_compare_hw(args, cli.json),
+
+            Commands::Fleet { command } => match command {
+                FleetCommands::Plan(args) => run_fleet_plan(args, cli.json),
+            },
         }
         return;
     }
 
     // If --cli flag, use classic fit output
     if cli.cli {
-        run_fit(cli.perfect, cli.limit, cli.json, &cli.memory);
+        run_fit(cli.perfect, cli.limit, cli.json, &cli.hw);
         return;
     }
 
     // Default: launch TUI
-    if let Err(e) = run_tui(&cli.memory) {
+    if let Err(e) = run_tui(&cli.hw) {
         eprintln!("Error running TUI: {}", e);
         std::process::exit(1);
     }
@@ -1,8 +1,9 @@
-use llmfit_core::fit::{FitLevel, ModelFit, SortColumn};
-use llmfit_core::hardware::SystemSpecs;
+use llmfit_core::fit::{DiskFit, FitLevel, InferenceRuntime, ModelFit, SortColumn};
+use llmfit_core::hardware::{SystemSpecs, free_disk_gb};
 use llmfit_core::models::ModelDatabase;
 use llmfit_core::providers::{
-    self, MlxProvider, ModelProvider, OllamaProvider, PullEvent, PullHandle,
+    self, LlamaServerProvider, LmStudioProvider, MlxProvider, ModelProvider, OllamaLoadedModel,
+    OllamaModelInfo, OllamaProvider, OpenAiCompatProvider, PullEvent, PullHandle,
 };
 
 use std::collections::HashSet;
@@ -13,6 +14,37 @@ pub enum InputMode {
     Normal,
     Search,
     ProviderPopup,
+    ConfirmRemove,
+}
+
+/// A configured OpenAI-compatible endpoint and what it last reported.
+pub struct EndpointStatus {
+    provider: OpenAiCompatProvider,
+    pub available: bool,
+    pub served: HashSet<String>,
+}
+
+impl EndpointStatus {
+    fn probe(provider: OpenAiCompatProvider) -> Self {
+        let mut status = EndpointStatus {
+            provider,
+            available: false,
+            served: HashSet::new(),
+        };
+        status.refresh();
+        status
+    }
+
+    /// One `/v1/models` request gives both reachability and the model list.
+    fn refresh(&mut self) {
+        let ids = self.provider.served_models();
+        self.available = ids.is_some();
+        self.served = providers::served_model_names(&ids.unwrap_or_default());
+    }
+
+    pub fn name(&self) -> &str {
+        self.provider.name()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -73,16 +105,28 @@ pub struct App {
     // Detail view
     pub show_detail: bool,
 
+    // Loaded-models panel
+    pub show_loaded: bool,
+
     // Provider popup
     pub provider_cursor: usize,
 
     // Provider state
     pub ollama_available: bool,
     pub ollama_installed: HashSet<String>,
+    /// Models Ollama has in memory, from `/api/ps`.
+    pub ollama_loaded: Vec<OllamaLoadedModel>,
     ollama: OllamaProvider,
     pub mlx_available: bool,
     pub mlx_installed: HashSet<String>,
     mlx: MlxProvider,
+    pub llamacpp_available: bool,
+    pub llamacpp_installed: HashSet<String>,
+    llamacpp: LlamaServerProvider,
+    pub lmstudio_available: bool,
+    pub lmstudio_installed: HashSet<String>,
+    lmstudio: LmStudioProvider,
+    pub endpoints: Vec<EndpointStatus>,
 
     // Download state
     pub pull_active: Option<PullHandle>,
@@ -91,6 +135,9 @@ pub struct App {
     pub pull_model_name: Option<String>,
     /// Animation frame counter, incremented every tick while pulling.
     pub tick_count: u64,
+
+    // Removal awaiting confirmation: provider name and tag
+    pub pending_remove: Option<(String, String)>,
 }
 
 impl App {
@@ -109,6 +156,7 @@ impl App {
         } else {
             HashSet::new()
         };
+        let ollama_loaded = ollama.loaded_models().unwrap_or_default();
 
         // Detect MLX
         let mlx = MlxProvider::new();
@@ -120,6 +168,22 @@ impl App {
             mlx.installed_models()
         };
 
+        // Detect llama-server; cached GGUFs count even when it's down
+        let llamacpp = LlamaServerProvider::new();
+        let llamacpp_available = llamacpp.is_available();
+        let llamacpp_installed = llamacpp.installed_models();
+
+        // Detect LM Studio; its models dir is scanned even when the server is off
+        let lmstudio = LmStudioProvider::new();
+        let lmstudio_available = lmstudio.is_available();
+        let lmstudio_installed = lmstudio.installed_models();
+
+        // Configured OpenAI-compatible endpoints, one provider each
+        let (endpoints, endpoints_error) = match OpenAiCompatProvider::from_config() {
+            Ok(list) => (list.into_iter().map(EndpointStatus::probe).collect(), None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+
         // Analyze all models
         let mut all_fits: Vec<ModelFit> = db
             .get_all_models()
@@ -127,10 +191,25 @@ impl App {
             .map(|m| {
                 let mut fit = ModelFit::analyze(m, &specs);
                 fit.installed = providers::is_model_installed(&m.name, &ollama_installed)
-                    || providers::is_model_installed_mlx(&m.name, &mlx_installed);
+                    || providers::is_model_installed_mlx(&m.name, &mlx_installed)
+                    || providers::is_model_installed_gguf(&m.name, &llamacpp_installed)
+                    || providers::is_model_installed_lmstudio(&m.name, &lmstudio_installed)
+                    || endpoints
+                        .iter()
+                        .any(|e: &EndpointStatus| providers::is_model_served(&m.name, &e.served));
                 fit
             })
             .collect();
+        // Installed Ollama models fit at their real quant; unknown ones are added
+        let ollama_details = if ollama_available {
+            ollama.installed_details()
+        } else {
+            Vec::new()
+        };
+        providers::apply_ollama_installed(&mut all_fits, &ollama_details, &specs);
+        providers::apply_disk_fit_with(&mut all_fits, |fit| {
+            pull_dir(fit, mlx_available, ollama_available, llamacpp_available)
+        });
 
         // Sort by fit level then RAM usage
         all_fits = llmfit_core::fit::rank_models_by_fit(all_fits);
@@ -163,18 +242,28 @@ impl App {
             sort_column: SortColumn::Score,
             selected_row: 0,
             show_detail: false,
+            show_loaded: false,
             provider_cursor: 0,
             ollama_available,
             ollama_installed,
+            ollama_loaded,
             ollama,
             mlx_available,
             mlx_installed,
             mlx,
+            llamacpp_available,
+            llamacpp_installed,
+            llamacpp,
+            lmstudio_available,
+            lmstudio_installed,
+            lmstudio,
+            endpoints,
             pull_active: None,
-            pull_status: None,
+            pull_status: endpoints_error,
             pull_percent: None,
             pull_model_name: None,
             tick_count: 0,
+            pending_remove: None,
         };
 
         app.apply_filters();
@@ -313,6 +402,25 @@ impl App {
 
     pub fn toggle_detail(&mut self) {
         self.show_detail = !self.show_detail;
+        self.show_loaded = false;
+    }
+
+    /// Show or hide the loaded-models panel, re-querying Ollama on open.
+    pub fn toggle_loaded(&mut self) {
+        self.show_loaded = !self.show_loaded;
+        if self.show_loaded {
+            self.ollama_loaded = self.ollama.loaded_models().unwrap_or_default();
+        }
+    }
+
+    /// llmfit's fit for a loaded Ollama tag, for comparing its estimate
+    /// with the memory the model actually holds.
+    pub fn fit_for_loaded(&self, tag: &str) -> Option<&ModelFit> {
+        let info = OllamaModelInfo {
+            name: tag.to_string(),
+            ..Default::default()
+        };
+        providers::fit_for_ollama_tag(&self.all_fits, &[info], tag)
     }
 
     pub fn open_provider_popup(&mut self) {
@@ -369,11 +477,15 @@ impl App {
         self.apply_filters();
     }
 
+    /// Whether any provider that can pull models is reachable.
+    pub fn any_provider_available(&self) -> bool {
+        self.ollama_available || self.mlx_available || self.llamacpp_available
+    }
+
     /// Start pulling the currently selected model via the best available provider.
     pub fn start_download(&mut self) {
-        let any_available = self.ollama_available || self.mlx_available;
-        if !any_available {
-            self.pull_status = Some("No provider available (Ollama/MLX)".to_string());
+        if !self.any_provider_available() {
+            self.pull_status = Some("No provider available (Ollama/MLX/llama.cpp)".to_string());
             return;
         }
         if self.pull_active.is_some() {
@@ -386,17 +498,30 @@ impl App {
             self.pull_status = Some("Already installed".to_string());
             return;
         }
+        // OpenVINO/ONNX NPU builds come from Hugging Face, which no provider
+        // here downloads; an Ollama or GGUF pull wouldn't run on the NPU.
+        if fit.runtime == InferenceRuntime::Npu {
+            self.pull_status =
+                Some("NPU builds can't be pulled; download them from Hugging Face".to_string());
+            return;
+        }
 
         // Choose provider based on runtime
-        let use_mlx = fit.runtime == llmfit_core::fit::InferenceRuntime::Mlx && self.mlx_available;
+        let use_mlx = fit.runtime == InferenceRuntime::Mlx && self.mlx_available;
 
         if use_mlx {
             let tag = providers::mlx_pull_tag(&fit.model.name);
             let model_name = fit.model.name.clone();
+            let size_gb = fit.model.download_size_gb("mlx-4bit");
+            let dir = providers::download_dir(InferenceRuntime::Mlx);
+            let Some(disk_note) = self.check_disk(&dir, size_gb) else {
+                return;
+            };
             match self.mlx.start_pull(&tag) {
                 Ok(handle) => {
                     self.pull_model_name = Some(model_name);
-                    self.pull_status = Some(format!("Pulling mlx-community/{}...", tag));
+                    self.pull_status =
+                        Some(format!("Pulling mlx-community/{}...{}", tag, disk_note));
                     self.pull_percent = None;
                     self.pull_active = Some(handle);
                 }
@@ -410,10 +535,40 @@ impl App {
                 return;
             };
             let model_name = fit.model.name.clone();
+            let size_gb = fit.model.download_size_gb(&fit.model.quantization);
+            let dir = providers::download_dir(InferenceRuntime::LlamaCpp);
+            let Some(disk_note) = self.check_disk(&dir, size_gb) else {
+                return;
+            };
             match self.ollama.start_pull(&tag) {
                 Ok(handle) => {
                     self.pull_model_name = Some(model_name);
-                    self.pull_status = Some(format!("Pulling {}...", tag));
+                    self.pull_status = Some(format!("Pulling {}...{}", tag, disk_note));
+                    self.pull_percent = Some(0.0);
+                    self.pull_active = Some(handle);
+                }
+                Err(e) => {
+                    self.pull_status = Some(format!("Pull failed: {}", e));
+                }
+            }
+        } else if self.llamacpp_available {
+            // GGUF builds exist for every quant; pull the one fit chose.
+            let quant = if llmfit_core::models::QUANT_HIERARCHY.contains(&fit.best_quant.as_str()) {
+                fit.best_quant.clone()
+            } else {
+                "Q4_K_M".to_string()
+            };
+            let tag = providers::gguf_pull_tag(&fit.model.name, &quant);
+            let model_name = fit.model.name.clone();
+            let size_gb = fit.model.download_size_gb(&quant);
+            let dir = providers::llama_cpp_cache_dir();
+            let Some(disk_note) = self.check_disk(&dir, size_gb) else {
+                return;
+            };
+            match self.llamacpp.start_pull(&tag) {
+                Ok(handle) => {
+                    self.pull_model_name = Some(model_name);
+                    self.pull_status = Some(format!("Pulling {}...{}", tag, disk_note));
                     self.pull_percent = Some(0.0);
                     self.pull_active = Some(handle);
                 }
@@ -426,6 +581,102 @@ impl App {
         }
     }
 
+    /// Local providers that can remove models, with their installed sets.
+    fn removable_providers(&self) -> [(&dyn ModelProvider, &HashSet<String>); 4] {
+        [
+            (&self.ollama, &self.ollama_installed),
+            (&self.mlx, &self.mlx_installed),
+            (&self.llamacpp, &self.llamacpp_installed),
+            (&self.lmstudio, &self.lmstudio_installed),
+        ]
+    }
+
+    /// Ask to remove the selected model's first installed build, checking
+    /// providers in the order pulls prefer them.
+    pub fn start_remove(&mut self) {
+        if self.pull_active.is_some() {
+            self.pull_status = Some("Wait for the download to finish".to_string());
+            return;
+        }
+        let Some(fit) = self.selected_fit() else {
+            return;
+        };
+        let target = self
+            .removable_providers()
+            .into_iter()
+            .find_map(|(provider, installed)| {
+                let tag = provider
+                    .installed_tags(&fit.model.name, installed)
+                    .into_iter()
+                    .next()?;
+                Some((provider.name().to_string(), tag))
+            });
+        match target {
+            Some((provider, tag)) => {
+                self.pull_status = Some(format!("Remove {} from {}? (y/n)", tag, provider));
+                self.pull_percent = None;
+                self.pending_remove = Some((provider, tag));
+                self.input_mode = InputMode::ConfirmRemove;
+            }
+            None => {
+                self.pull_status = Some("Not installed in a local provider".to_string());
+            }
+        }
+    }
+
+    /// Carry out the confirmed removal and refresh installed state.
+    pub fn confirm_remove(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let Some((name, tag)) = self.pending_remove.take() else {
+            return;
+        };
+        let result = self
+            .removable_providers()
+            .into_iter()
+            .find(|(provider, _)| provider.name() == name)
+            .map(|(provider, _)| provider.remove(&tag));
+        self.pull_status = Some(match result {
+            Some(Ok(bytes)) => format!(
+                "Removed {} from {}, freeing {:.1} GB",
+                tag,
+                name,
+                bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+            ),
+            Some(Err(e)) => format!("Remove failed: {}", e),
+            None => format!("{} is not available", name),
+        });
+        self.refresh_installed();
+    }
+
+    pub fn cancel_remove(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.pending_remove = None;
+        self.pull_status = Some("Remove cancelled".to_string());
+    }
+
+    /// Check that a `size_gb` download fits in `dir`.
+    /// Refuses (sets `pull_status`, returns `None`) when it won't fit;
+    /// otherwise returns a suffix for the pull status, warning when tight.
+    fn check_disk(&mut self, dir: &std::path::Path, size_gb: f64) -> Option<String> {
+        let free_gb = free_disk_gb(dir);
+        match DiskFit::from_sizes(size_gb, free_gb) {
+            DiskFit::NoSpace => {
+                self.pull_status = Some(format!(
+                    "Not enough disk space: ~{:.1} GB needed, {:.1} GB free in {}",
+                    size_gb,
+                    free_gb.unwrap_or(0.0),
+                    dir.display()
+                ));
+                None
+            }
+            DiskFit::Tight => Some(format!(
+                " (low disk: {:.1} GB left after download)",
+                free_gb.unwrap_or(0.0) - size_gb
+            )),
+            DiskFit::Fits | DiskFit::Unknown => Some(String::new()),
+        }
+    }
+
     /// Poll the active pull for progress. Called each TUI tick.
     pub fn tick_pull(&mut self) {
         if self.pull_active.is_some() {
@@ -472,11 +723,64 @@ impl App {
     /// Re-query all providers for installed models and update all_fits.
     pub fn refresh_installed(&mut self) {
         self.ollama_installed = self.ollama.installed_models();
+        self.ollama_loaded = self.ollama.loaded_models().unwrap_or_default();
         self.mlx_installed = self.mlx.installed_models();
+        self.llamacpp_installed = self.llamacpp.installed_models();
+        self.lmstudio_installed = self.lmstudio.installed_models();
+        for endpoint in &mut self.endpoints {
+            endpoint.refresh();
+        }
+        // Start over from the database: fits pinned to an installed quant
+        // go back to the default analysis, and rows synthesized for Ollama
+        // tags that were removed drop out.
+        self.all_fits = ModelDatabase::new()
+            .get_all_models()
+            .iter()
+            .map(|m| ModelFit::analyze(m, &self.specs))
+            .collect();
         for fit in &mut self.all_fits {
             fit.installed = providers::is_model_installed(&fit.model.name, &self.ollama_installed)
-                || providers::is_model_installed_mlx(&fit.model.name, &self.mlx_installed);
+                || providers::is_model_installed_mlx(&fit.model.name, &self.mlx_installed)
+                || providers::is_model_installed_gguf(&fit.model.name, &self.llamacpp_installed)
+                || providers::is_model_installed_lmstudio(
+                    &fit.model.name,
+                    &self.lmstudio_installed,
+                )
+                || self
+                    .endpoints
+                    .iter()
+                    .any(|e| providers::is_model_served(&fit.model.name, &e.served));
         }
+        let ollama_details = self.ollama.installed_details();
+        providers::apply_ollama_installed(&mut self.all_fits, &ollama_details, &self.specs);
+        // A finished pull consumed disk space; re-probe it.
+        providers::apply_disk_fit_with(&mut self.all_fits, |fit| {
+            pull_dir(
+                fit,
+                self.mlx_available,
+                self.ollama_available,
+                self.llamacpp_available,
+            )
+        });
         self.re_sort();
     }
 }
+
+/// Where `App::start_download` would save `fit`, given which providers are
+/// up: the same choice of provider, so disk fit is checked where the pull
+/// actually writes. With none up, the runtime's usual dir; `None` for NPU
+/// builds, which can't be pulled.
+fn pull_dir(
+    fit: &ModelFit,
+    mlx_available: bool,
+    ollama_available: bool,
+    llamacpp_available: bool,
+) -> Option<std::path::PathBuf> {
+    Some(match fit.runtime {
+        InferenceRuntime::Npu => return None,
+        InferenceRuntime::Mlx if mlx_available => providers::download_dir(InferenceRuntime::Mlx),
+        _ if ollama_available => providers::download_dir(InferenceRuntime::LlamaCpp),
+        _ if llamacpp_available => providers::llama_cpp_cache_dir(),
+        runtime => providers::download_dir(runtime),
+    })
+}
@@ -19,6 +19,7 @@ pub fn handle_events(app: &mut App) -> std::io::Result<bool> {
             InputMode::Normal => handle_normal_mode(app, key),
             InputMode::Search => handle_search_mode(app, key),
             InputMode::ProviderPopup => handle_provider_popup_mode(app, key),
+            InputMode::ConfirmRemove => handle_confirm_remove_mode(app, key),
         }
         return Ok(true);
     }
@@ -31,6 +32,8 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
         KeyCode::Char('q') | KeyCode::Esc => {
             if app.show_detail {
                 app.show_detail = false;
+            } else if app.show_loaded {
+                app.show_loaded = false;
             } else {
                 app.should_quit = true;
             }
@@ -57,15 +60,19 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
         KeyCode::Char('p') => app.open_provider_popup(),
 
         // Installed-first sort toggle (any provider)
-        KeyCode::Char('i') if app.ollama_available || app.mlx_available => {
-            app.toggle_installed_first()
-        }
+        KeyCode::Char('i') if app.any_provider_available() => app.toggle_installed_first(),
 
         // Download model via best provider
-        KeyCode::Char('d') if app.ollama_available || app.mlx_available => app.start_download(),
+        KeyCode::Char('d') if app.any_provider_available() => app.start_download(),
 
         // Refresh installed models
-        KeyCode::Char('r') if app.ollama_available || app.mlx_available => app.refresh_installed(),
+        KeyCode::Char('r') if app.any_provider_available() => app.refresh_installed(),
+
+        // Remove the selected model (asks for confirmation)
+        KeyCode::Char('x') => app.start_remove(),
+
+        // Models Ollama has loaded
+        KeyCode::Char('l') if app.ollama_available => app.toggle_loaded(),
 
         // Detail view
         KeyCode::Enter => app.toggle_detail(),
@@ -109,3 +116,10 @@ fn handle_provider_popup_mode(app: &mut App, key: KeyEvent) {
         _ => {}
     }
 }
+
+fn handle_confirm_remove_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => app.confirm_remove(),
+        _ => app.cancel_remove(),
+    }
+}
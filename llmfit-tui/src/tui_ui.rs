@@ -10,8 +10,8 @@ use ratatui::{
 };
 
 use crate::tui_app::{App, FitFilter, InputMode};
-use llmfit_core::fit::FitLevel;
 use llmfit_core::fit::SortColumn;
+use llmfit_core::fit::{DiskFit, FitLevel};
 use llmfit_core::hardware::is_running_in_wsl;
 use llmfit_core::providers;
 
@@ -29,7 +29,9 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
     draw_system_bar(frame, app, outer[0]);
     draw_search_and_filters(frame, app, outer[1]);
 
-    if app.show_detail {
+    if app.show_loaded {
+        draw_loaded(frame, app, outer[2]);
+    } else if app.show_detail {
         draw_detail(frame, app, outer[2]);
     } else {
         draw_table(frame, app, outer[2]);
@@ -61,13 +63,17 @@ fn draw_system_bar(frame: &mut Frame, app: &App, area: Rect) {
         } else {
             match primary.vram_gb {
                 Some(vram) if vram > 0.0 => {
+                    let used = primary
+                        .vram_used_gb
+                        .map(|u| format!(", {:.1} used", u))
+                        .unwrap_or_default();
                     if primary.count > 1 {
                         format!(
-                            "{} x{} ({:.1} GB each, {})",
-                            primary.name, primary.count, vram, backend
+                            "{} x{} ({:.1} GB each{}, {})",
+                            primary.name, primary.count, vram, used, backend
                         )
                     } else {
-                        format!("{} ({:.1} GB, {})", primary.name, vram, backend)
+                        format!("{} ({:.1} GB{}, {})", primary.name, vram, used, backend)
                     }
                 }
                 Some(_) => format!("{} (shared, {})", primary.name, backend),
@@ -108,7 +114,22 @@ fn draw_system_bar(frame: &mut Frame, app: &App, area: Rect) {
         Color::DarkGray
     };
 
-    let text = Line::from(vec![
+    let lmstudio_info = if app.lmstudio_available {
+        format!("LM Studio: ✓ ({} installed)", app.lmstudio_installed.len())
+    } else if !app.lmstudio_installed.is_empty() {
+        format!("LM Studio: ({} on disk)", app.lmstudio_installed.len())
+    } else {
+        "LM Studio: ✗".to_string()
+    };
+    let lmstudio_color = if app.lmstudio_available {
+        Color::Green
+    } else if !app.lmstudio_installed.is_empty() {
+        Color::Yellow
+    } else {
+        Color::DarkGray
+    };
+
+    let mut spans = vec![
         Span::styled(" CPU: ", Style::default().fg(Color::DarkGray)),
         Span::styled(
             format!(
@@ -124,7 +145,13 @@ fn draw_system_bar(frame: &mut Frame, app: &App, area: Rect) {
                 "{:.1} GB avail / {:.1} GB total{}",
                 app.specs.available_ram_gb,
                 app.specs.total_ram_gb,
-                if is_running_in_wsl() { " (WSL)" } else { "" }
+                if is_running_in_wsl() {
+                    " (WSL)"
+                } else if app.specs.cgroup_limits.is_some() {
+                    " (container limit)"
+                } else {
+                    ""
+                }
             ),
             Style::default().fg(Color::Cyan),
         ),
@@ -134,7 +161,43 @@ fn draw_system_bar(frame: &mut Frame, app: &App, area: Rect) {
         Span::styled(ollama_info, Style::default().fg(ollama_color)),
         Span::styled("  │  ", Style::default().fg(Color::DarkGray)),
         Span::styled(mlx_info, Style::default().fg(mlx_color)),
-    ]);
+        Span::styled("  │  ", Style::default().fg(Color::DarkGray)),
+        Span::styled(lmstudio_info, Style::default().fg(lmstudio_color)),
+    ];
+    for endpoint in &app.endpoints {
+        let (info, color) = if endpoint.available {
+            (
+                format!("{}: ✓ ({} served)", endpoint.name(), endpoint.served.len()),
+                Color::Green,
+            )
+        } else {
+            (format!("{}: ✗", endpoint.name()), Color::DarkGray)
+        };
+        spans.push(Span::styled("  │  ", Style::default().fg(Color::DarkGray)));
+        spans.push(Span::styled(info, Style::default().fg(color)));
+    }
+    if app.llamacpp_available {
+        spans.push(Span::styled("  │  ", Style::default().fg(Color::DarkGray)));
+        spans.push(Span::styled(
+            format!("llama.cpp: ✓ ({} cached)", app.llamacpp_installed.len()),
+            Style::default().fg(Color::Green),
+        ));
+    }
+    if let Some(power) = &app.specs.power {
+        let (icon, color) = if power.on_battery {
+            ("🔋", Color::Red)
+        } else if power.gpu_power_ratio().is_some_and(|r| r < 0.95) {
+            ("⚡", Color::Yellow)
+        } else {
+            ("⚡", Color::Green)
+        };
+        spans.push(Span::styled("  │  ", Style::default().fg(Color::DarkGray)));
+        spans.push(Span::styled(
+            format!("{} {}", icon, power.label()),
+            Style::default().fg(color),
+        ));
+    }
+    let text = Line::from(spans);
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -164,7 +227,9 @@ fn draw_search_and_filters(frame: &mut Frame, app: &App, area: Rect) {
     // Search box
     let search_style = match app.input_mode {
         InputMode::Search => Style::default().fg(Color::Yellow),
-        InputMode::Normal | InputMode::ProviderPopup => Style::default().fg(Color::DarkGray),
+        InputMode::Normal | InputMode::ProviderPopup | InputMode::ConfirmRemove => {
+            Style::default().fg(Color::DarkGray)
+        }
     };
 
     let search_text = if app.search_query.is_empty() && app.input_mode == InputMode::Normal {
@@ -345,6 +410,8 @@ fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
                 llmfit_core::fit::RunMode::MoeOffload => Color::Cyan,
                 llmfit_core::fit::RunMode::CpuOffload => Color::Yellow,
                 llmfit_core::fit::RunMode::CpuOnly => Color::DarkGray,
+                llmfit_core::fit::RunMode::Npu => Color::Magenta,
+                llmfit_core::fit::RunMode::DiskStream => Color::Red,
             };
 
             let score_color = if fit.score >= 70.0 {
@@ -371,7 +438,9 @@ fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
                 " ✓".to_string()
             } else if is_pulling {
                 pull_indicator(app.pull_percent, app.tick_count)
-            } else if !has_ollama {
+            } else if fit.disk_fit == DiskFit::NoSpace {
+                " ✗".to_string()
+            } else if !has_ollama && !app.llamacpp_available {
                 " —".to_string()
             } else {
                 " ".to_string()
@@ -380,6 +449,8 @@ fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
                 Color::Green
             } else if is_pulling {
                 Color::Yellow
+            } else if fit.disk_fit == DiskFit::NoSpace {
+                Color::Red
             } else if !has_ollama {
                 Color::DarkGray
             } else {
@@ -538,10 +609,11 @@ fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
             Span::styled("  Runtime:     ", Style::default().fg(Color::DarkGray)),
             Span::styled(
                 fit.runtime_text(),
-                Style::default().fg(if fit.runtime == llmfit_core::fit::InferenceRuntime::Mlx {
-                    Color::Cyan
-                } else {
-                    Color::White
+                Style::default().fg(match fit.runtime {
+                    llmfit_core::fit::InferenceRuntime::Mlx => Color::Cyan,
+                    llmfit_core::fit::InferenceRuntime::Npu => Color::Magenta,
+                    llmfit_core::fit::InferenceRuntime::Vllm => Color::Green,
+                    llmfit_core::fit::InferenceRuntime::LlamaCpp => Color::White,
                 }),
             ),
             Span::styled(
@@ -552,18 +624,47 @@ fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
         Line::from(vec![
             Span::styled("  Installed:   ", Style::default().fg(Color::DarkGray)),
             {
-                let ollama_installed =
-                    providers::is_model_installed(&fit.model.name, &app.ollama_installed);
-                let mlx_installed =
-                    providers::is_model_installed_mlx(&fit.model.name, &app.mlx_installed);
-                let any_available = app.ollama_available || app.mlx_available;
-
-                if ollama_installed && mlx_installed {
-                    Span::styled("✓ Ollama  ✓ MLX", Style::default().fg(Color::Green).bold())
-                } else if ollama_installed {
-                    Span::styled("✓ Ollama", Style::default().fg(Color::Green).bold())
-                } else if mlx_installed {
-                    Span::styled("✓ MLX", Style::default().fg(Color::Green).bold())
+                let mut installed_in: Vec<&str> = [
+                    (
+                        "Ollama",
+                        providers::is_model_installed(&fit.model.name, &app.ollama_installed),
+                    ),
+                    (
+                        "MLX",
+                        providers::is_model_installed_mlx(&fit.model.name, &app.mlx_installed),
+                    ),
+                    (
+                        "llama.cpp",
+                        providers::is_model_installed_gguf(
+                            &fit.model.name,
+                            &app.llamacpp_installed,
+                        ),
+                    ),
+                    (
+                        "LM Studio",
+                        providers::is_model_installed_lmstudio(
+                            &fit.model.name,
+                            &app.lmstudio_installed,
+                        ),
+                    ),
+                ]
+                .into_iter()
+                .filter_map(|(name, installed)| installed.then_some(name))
+                .collect();
+                installed_in.extend(
+                    app.endpoints
+                        .iter()
+                        .filter(|e| providers::is_model_served(&fit.model.name, &e.served))
+                        .map(|e| e.name()),
+                );
+                let any_available =
+                    app.ollama_available || app.mlx_available || app.llamacpp_available;
+
+                if !installed_in.is_empty() {
+                    Span::styled(
+                        format!("✓ {}", installed_in.join("  ✓ ")),
+                        Style::default().fg(Color::Green).bold(),
+                    )
                 } else if any_available {
                     Span::styled(
                         "✗ No  (press d to pull)",
@@ -577,6 +678,26 @@ fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
                 }
             },
         ]),
+        Line::from(vec![
+            Span::styled("  Download:    ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                format!("~{:.1} GB", fit.download_gb),
+                Style::default().fg(Color::White),
+            ),
+            match fit.disk_fit {
+                DiskFit::Fits => Span::styled("  fits on disk", Style::default().fg(Color::Green)),
+                DiskFit::Tight => {
+                    Span::styled("  tight on disk", Style::default().fg(Color::Yellow))
+                }
+                DiskFit::NoSpace => Span::styled(
+                    "  not enough disk space",
+                    Style::default().fg(Color::Red).bold(),
+                ),
+                DiskFit::Unknown => {
+                    Span::styled("  free space unknown", Style::default().fg(Color::DarkGray))
+                }
+            },
+        ]),
     ];
 
     // Scoring section
@@ -816,6 +937,86 @@ fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
+fn draw_loaded(frame: &mut Frame, app: &App, area: Rect) {
+    let header = Row::new(
+        [
+            "Model", "Size", "VRAM", "RAM", "GPU", "Context", "Est.", "Until",
+        ]
+        .map(|h| {
+            Cell::from(h).style(
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+        }),
+    );
+
+    let rows: Vec<Row> = app
+        .ollama_loaded
+        .iter()
+        .map(|m| {
+            let gpu_color = if m.gpu_percent() >= 100.0 {
+                Color::Green
+            } else if m.gpu_percent() > 0.0 {
+                Color::Yellow
+            } else {
+                Color::DarkGray
+            };
+            // Residency well above llmfit's estimate usually means a larger
+            // context than the model's default.
+            let (estimate, estimate_color) = match app.fit_for_loaded(&m.name) {
+                Some(fit) if m.size_gb() > fit.memory_required_gb * 1.2 => {
+                    (format!("{:.1} GB", fit.memory_required_gb), Color::Yellow)
+                }
+                Some(fit) => (format!("{:.1} GB", fit.memory_required_gb), Color::White),
+                None => ("-".to_string(), Color::DarkGray),
+            };
+            Row::new(vec![
+                Cell::from(m.name.clone()).style(Style::default().fg(Color::White)),
+                Cell::from(format!("{:.1} GB", m.size_gb())),
+                Cell::from(format!("{:.1} GB", m.vram_gb())),
+                Cell::from(format!("{:.1} GB", m.ram_gb())),
+                Cell::from(format!("{:.0}%", m.gpu_percent()))
+                    .style(Style::default().fg(gpu_color)),
+                Cell::from(
+                    m.context_length
+                        .map(|c| format!("{}k", c / 1024))
+                        .unwrap_or_else(|| "-".to_string()),
+                ),
+                Cell::from(estimate).style(Style::default().fg(estimate_color)),
+                Cell::from(m.expires_at.clone().unwrap_or_else(|| "-".to_string()))
+                    .style(Style::default().fg(Color::DarkGray)),
+            ])
+        })
+        .collect();
+
+    let vram: f64 = app.ollama_loaded.iter().map(|m| m.vram_gb()).sum();
+    let ram: f64 = app.ollama_loaded.iter().map(|m| m.ram_gb()).sum();
+    let title = if app.ollama_loaded.is_empty() {
+        " Loaded in Ollama: none ".to_string()
+    } else {
+        format!(" Loaded in Ollama: {:.1} GB VRAM, {:.1} GB RAM ", vram, ram)
+    };
+
+    let widths = [
+        Constraint::Min(24),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Length(5),
+        Constraint::Length(8),
+        Constraint::Length(9),
+        Constraint::Length(26),
+    ];
+    let table = Table::new(rows, widths).header(header).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray))
+            .title(title),
+    );
+    frame.render_widget(table, area);
+}
+
 fn draw_provider_popup(frame: &mut Frame, app: &App) {
     let area = frame.area();
 
@@ -918,7 +1119,15 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                     } else {
                         "i:installed↑"
                     };
-                    format!("  {}  d:pull  r:refresh", installed_key)
+                    let loaded_key = if app.ollama_available {
+                        "  l:loaded"
+                    } else {
+                        ""
+                    };
+                    format!(
+                        "  {}  d:pull  x:remove  r:refresh{}",
+                        installed_key, loaded_key
+                    )
                 } else {
                     String::new()
                 };
@@ -938,6 +1147,7 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                 "  ↑↓/jk:navigate  Space:toggle  a:all/none  Esc:close".to_string(),
                 "PROVIDERS",
             ),
+            InputMode::ConfirmRemove => ("  y:remove  any other key:cancel".to_string(), "REMOVE"),
         };
 
         // Split into two lines: keys + progress
@@ -980,14 +1190,22 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             } else {
                 "Enter:detail"
             };
-            let any_provider = app.ollama_available || app.mlx_available;
+            let any_provider = app.any_provider_available();
             let ollama_keys = if any_provider {
                 let installed_key = if app.installed_first {
                     "i:all"
                 } else {
                     "i:installed↑"
                 };
-                format!("  {}  d:pull  r:refresh", installed_key)
+                let loaded_key = if app.ollama_available {
+                    "  l:loaded"
+                } else {
+                    ""
+                };
+                format!(
+                    "  {}  d:pull  x:remove  r:refresh{}",
+                    installed_key, loaded_key
+                )
             } else {
                 String::new()
             };
@@ -1007,6 +1225,7 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             "  ↑↓/jk:navigate  Space:toggle  a:all/none  Esc:close".to_string(),
             "PROVIDERS",
         ),
+        InputMode::ConfirmRemove => ("  y:remove  any other key:cancel".to_string(), "REMOVE"),
     };
 
     let status_line = Line::from(vec![